@@ -1,13 +1,19 @@
 //! `awc` based `pretend` client
+//!
+//! Clients can be built from a [`ClientConfig`]. Proxies, root certificates and
+//! identities are not supported, and the pool size limits the number of
+//! connections of the client, for all hosts.
 
 #![warn(missing_docs)]
 #![forbid(unsafe_code)]
 
 pub use awc;
 
+use awc::http::header::USER_AGENT;
 use awc::http::{HeaderName, HeaderValue};
-use awc::Client as AClient;
+use awc::{Client as AClient, ClientBuilder, Connector};
 use pretend::client::{async_trait, Bytes, LocalClient, Method};
+use pretend::config::{ClientConfig, RedirectPolicy, UnsupportedOption};
 use pretend::http::header::{HeaderName as PHeaderName, HeaderValue as PHeaderValue};
 use pretend::{Error, HeaderMap, Response, Result, Url};
 
@@ -25,6 +31,52 @@ impl Client {
     pub fn new(client: AClient) -> Self {
         Client { client }
     }
+
+    /// Constructor with a configuration
+    ///
+    /// This constructor creates a client implementation
+    /// for `pretend` using an `awc` client built from
+    /// the supplied configuration.
+    pub fn from_config(config: &ClientConfig) -> Result<Self> {
+        let builder = configure_builder(AClient::builder(), config)?;
+        Ok(Client {
+            client: builder.finish(),
+        })
+    }
+}
+
+fn configure_builder(mut builder: ClientBuilder, config: &ClientConfig) -> Result<ClientBuilder> {
+    if config.proxy().is_some() {
+        return Err(Error::client(UnsupportedOption::new("proxy")));
+    }
+    if !config.root_certificates().is_empty() {
+        return Err(Error::client(UnsupportedOption::new("root_certificates")));
+    }
+    if config.identity().is_some() {
+        return Err(Error::client(UnsupportedOption::new("identity")));
+    }
+
+    let mut connector = Connector::new();
+    if let Some(timeout) = config.connect_timeout() {
+        connector = connector.timeout(timeout);
+    }
+    if let Some(size) = config.pool_size() {
+        connector = connector.limit(size);
+    }
+    builder = builder.connector(connector.finish());
+
+    if let Some(timeout) = config.timeout() {
+        builder = builder.timeout(timeout);
+    }
+    match config.redirect_policy() {
+        Some(RedirectPolicy::None) => builder = builder.disable_redirects(),
+        Some(RedirectPolicy::Limit(limit)) => builder = builder.max_redirects(limit),
+        None => {}
+    }
+    if let Some(user_agent) = config.user_agent() {
+        builder = builder.header(USER_AGENT, user_agent);
+    }
+    Ok(builder)
 }
 
 #[async_trait(?Send)]
@@ -53,7 +105,8 @@ impl LocalClient for Client {
         let headers = headers.iter().map(create_header).collect::<HeaderMap>();
         let future = response.body();
         let result = future.await.map_err(Error::body)?;
-        Ok(Response::new(status, headers, Bytes::from(result.to_vec())))
+        let body = Bytes::from(result.to_vec());
+        Ok(Response::new(status, headers, body).with_url(url))
     }
 }
 
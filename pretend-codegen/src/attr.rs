@@ -1,14 +1,51 @@
 use crate::errors::INVALID_ATTR;
-use proc_macro2::Span;
+use proc_macro2::{Ident, Span};
 use syn::parse::{Error, Parse, ParseStream, Result};
-use syn::Token;
+use syn::{parenthesized, LitStr, Token};
 
 pub(crate) struct PretendAttr {
     pub local: bool,
+    pub openapi: bool,
+    pub server: bool,
+    pub blocking_variant: Option<Ident>,
+    pub defaults: Defaults,
+}
+
+// Trait-level defaults, applied to every method
+#[derive(Default)]
+pub(crate) struct Defaults {
+    pub path: Option<String>,
+    pub headers: Vec<(String, String)>,
+    pub query: Vec<(String, String)>,
+}
+
+impl Defaults {
+    // The prefix and the path are joined with exactly one `/`
+    pub(crate) fn prefix_path(&self, path: String) -> String {
+        let prefix = match &self.path {
+            Some(prefix) => prefix.trim_end_matches('/'),
+            None => return path,
+        };
+        let path = path.trim_start_matches('/');
+        if path.is_empty() || path.starts_with('?') {
+            format!("{}{}", prefix, path)
+        } else {
+            format!("{}/{}", prefix, path)
+        }
+    }
 }
 
 mod kw {
     syn::custom_keyword!(Send);
+    syn::custom_keyword!(openapi);
+    syn::custom_keyword!(server);
+    syn::custom_keyword!(path);
+    syn::custom_keyword!(header);
+    syn::custom_keyword!(query);
+    syn::custom_keyword!(accept);
+    syn::custom_keyword!(blocking_variant);
+    syn::custom_keyword!(name);
+    syn::custom_keyword!(value);
 }
 
 impl Parse for PretendAttr {
@@ -21,11 +58,81 @@ impl Parse for PretendAttr {
 }
 
 fn try_parse(input: ParseStream) -> Result<PretendAttr> {
-    if input.peek(Token![?]) {
-        input.parse::<Token![?]>()?;
-        input.parse::<kw::Send>()?;
-        Ok(PretendAttr { local: true })
+    let mut attr = PretendAttr {
+        local: false,
+        openapi: false,
+        server: false,
+        blocking_variant: None,
+        defaults: Defaults::default(),
+    };
+    let mut accept = None;
+
+    while !input.is_empty() {
+        if input.peek(Token![?]) && !attr.local {
+            input.parse::<Token![?]>()?;
+            input.parse::<kw::Send>()?;
+            attr.local = true;
+        } else if input.peek(kw::openapi) && !attr.openapi {
+            input.parse::<kw::openapi>()?;
+            attr.openapi = true;
+        } else if input.peek(kw::server) && !attr.server {
+            input.parse::<kw::server>()?;
+            attr.server = true;
+        } else if input.peek(kw::blocking_variant) && attr.blocking_variant.is_none() {
+            input.parse::<kw::blocking_variant>()?;
+            input.parse::<Token![=]>()?;
+            let variant = input.parse::<LitStr>()?;
+            attr.blocking_variant = Some(variant.parse()?);
+        } else if input.peek(kw::path) && attr.defaults.path.is_none() {
+            input.parse::<kw::path>()?;
+            attr.defaults.path = Some(parse_value(input)?);
+        } else if input.peek(kw::header) {
+            input.parse::<kw::header>()?;
+            attr.defaults.headers.push(parse_name_value(input)?);
+        } else if input.peek(kw::query) {
+            input.parse::<kw::query>()?;
+            attr.defaults.query.push(parse_name_value(input)?);
+        } else if input.peek(kw::accept) && accept.is_none() {
+            input.parse::<kw::accept>()?;
+            accept = Some(parse_value(input)?);
+        } else {
+            return Err(input.error(INVALID_ATTR));
+        }
+
+        if !input.is_empty() {
+            input.parse::<Token![,]>()?;
+        }
+    }
+
+    if let Some(accept) = accept {
+        let header = ("Accept".to_string(), accept);
+        attr.defaults.headers.push(header);
+    }
+    Ok(attr)
+}
+
+// Parse `= "value"`
+fn parse_value(input: ParseStream) -> Result<String> {
+    input.parse::<Token![=]>()?;
+    let value = input.parse::<LitStr>()?;
+    Ok(value.value())
+}
+
+// Parse `(name = "name", value = "value")`
+fn parse_name_value(input: ParseStream) -> Result<(String, String)> {
+    let content;
+    parenthesized!(content in input);
+    content.parse::<kw::name>()?;
+    let name = parse_value(&content)?;
+    content.parse::<Token![,]>()?;
+    content.parse::<kw::value>()?;
+    let value = parse_value(&content)?;
+    if !content.is_empty() {
+        content.parse::<Token![,]>()?;
+    }
+    if content.is_empty() {
+        Ok((name, value))
     } else {
-        Ok(PretendAttr { local: false })
+        Err(content.error(INVALID_ATTR))
     }
 }
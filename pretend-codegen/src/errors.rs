@@ -3,18 +3,23 @@ use syn::{Error, Result};
 
 pub(crate) const CODEGEN_FAILURE: &str = "Failed to generate pretend implementation";
 pub(crate) const METHOD_FAILURE: &str = "Failed to generate method implementation";
-pub(crate) const INVALID_ATTR: &str = "Expected `#[pretend]` or `#[pretend(?Send)]`";
+pub(crate) const INVALID_ATTR: &str =
+    "Expected `#[pretend]`, or `#[pretend(...)]` with `?Send`, `openapi`, `server`, `path`, `header`, `query`, `accept` and `blocking_variant` options";
 pub(crate) const UNSUPPORTED_ATTR_SYNC: &str =
     "`?Send` is not supported for blocking implementation";
+pub(crate) const UNSUPPORTED_BLOCKING_VARIANT: &str =
+    "`blocking_variant` is only supported for async implementation";
 pub(crate) const NO_METHOD: &str = "Please declare at least one method for this trait";
 pub(crate) const INCONSISTENT_ASYNC: &str =
     "Unable to deduce if this trait is async or not. Please mark either all methods or none as async.";
 pub(crate) const INCONSISTENT_ASYNC_ASYNC_HINT: &str = "async method defined here";
 pub(crate) const INCONSISTENT_ASYNC_NON_ASYNC_HINT: &str = "non-async method defined here";
-pub(crate) const UNSUPPORTED_TRAIT_ITEM: &str = "Only methods are supported";
-pub(crate) const UNSUPPORTED_GENERICS: &str = "Generics are not supported";
+pub(crate) const UNSUPPORTED_TRAIT_ITEM: &str =
+    "Only methods and associated constants with a default value are supported";
+pub(crate) const UNSUPPORTED_CONST_GENERICS: &str = "Const generics are not supported";
 pub(crate) const UNSUPPORTED_RECEIVER: &str = "Method must take `&self` as receiver";
-pub(crate) const MISSING_REQUEST: &str = "Method must have the `#[request]` attribute";
+pub(crate) const MISSING_REQUEST: &str =
+    "Method must have the `#[request]` attribute, or a default implementation";
 pub(crate) const TOO_MANY_REQUESTS: &str = "Method must have the `#[request]` attribute only once";
 pub(crate) const TOO_MANY_REQUESTS_HINT: &str = "`#[request]` attribute defined here";
 pub(crate) const INVALID_REQUEST: &str =
@@ -27,7 +27,7 @@ lazy_static! {
     static ref PARAM_RE: Regex = Regex::new(r"\{([^}]+)\}").unwrap();
 }
 
-fn find_params(path: &str) -> Vec<&str> {
+pub(crate) fn find_params(path: &str) -> Vec<&str> {
     PARAM_RE
         .captures_iter(path)
         .filter_map(|cap| cap.get(1))
@@ -4,17 +4,23 @@ mod format;
 mod method;
 mod utils;
 
-use crate::attr::PretendAttr;
+use crate::attr::{Defaults, PretendAttr};
 use crate::errors::{
     ErrorsExt, Report, CODEGEN_FAILURE, INCONSISTENT_ASYNC, INCONSISTENT_ASYNC_ASYNC_HINT,
     INCONSISTENT_ASYNC_NON_ASYNC_HINT, NO_METHOD, UNSUPPORTED_ATTR_SYNC,
+    UNSUPPORTED_BLOCKING_VARIANT,
+};
+use crate::method::{
+    describe_method, is_default_method, serve_method, trait_item, trait_item_implem,
 };
-use crate::method::{trait_item, trait_item_implem};
 use crate::utils::WithTokens;
 use proc_macro::TokenStream;
 use proc_macro2::{Ident, Span, TokenStream as TokenStream2};
-use quote::quote;
-use syn::{parse_macro_input, Error, ItemTrait, Result, Signature, TraitItem};
+use quote::{format_ident, quote};
+use std::iter;
+use syn::{
+    parse_macro_input, parse_quote, Error, ItemTrait, Result, Signature, TraitItem, Visibility,
+};
 
 #[proc_macro_attribute]
 pub fn pretend(attr: TokenStream, item: TokenStream) -> TokenStream {
@@ -29,19 +35,63 @@ fn implement_pretend(attr: PretendAttr, item: ItemTrait) -> Result<TokenStream2>
     let name = &item.ident;
     let vis = &item.vis;
     let items = &item.items;
+
+    let defaults = &attr.defaults;
+    let openapi = if attr.openapi {
+        implement_openapi(name, vis, items, defaults)
+    } else {
+        TokenStream2::new()
+    };
+
+    let kind = parse_client_kind(name, attr.local, items)?;
+    let client = implement_client(&item, name, &kind, defaults)?;
+
+    let server = if attr.server {
+        implement_server(name, vis, items, &kind, defaults)
+    } else {
+        TokenStream2::new()
+    };
+
+    let blocking_variant = match &attr.blocking_variant {
+        Some(variant) => implement_blocking_variant(variant, &item, &kind, defaults)?,
+        None => TokenStream2::new(),
+    };
+
+    let tokens = quote! {
+        #client
+        #openapi
+        #server
+        #blocking_variant
+    };
+    Ok(tokens)
+}
+
+// Declare the trait and implement it for `Pretend`
+//
+// Endpoints are named after `endpoint_trait`, so that variants
+// share the same endpoints.
+fn implement_client(
+    item: &ItemTrait,
+    endpoint_trait: &Ident,
+    kind: &ClientKind,
+    defaults: &Defaults,
+) -> Result<TokenStream2> {
+    let name = &item.ident;
+    let vis = &item.vis;
+    let items = &item.items;
     let attrs = &item.attrs;
     let trait_items = items.iter().map(trait_item).collect::<Vec<_>>();
 
-    let kind = parse_client_kind(name, attr, items)?;
     let methods = items
         .iter()
-        .map(|item| trait_item_implem(item, &kind))
+        .map(|item| trait_item_implem(item, endpoint_trait, kind, defaults))
         .collect::<Report<_>>()
         .into_result(|| Error::new(Span::call_site(), CODEGEN_FAILURE))?;
 
-    let attr = async_trait_attr(&kind);
-    let client = client_implem(&kind);
-    let send_sync = send_sync_traits_impl(&kind);
+    let attr = async_trait_attr(kind);
+    let client = client_implem(kind);
+    let resolver = resolver_implem(kind);
+    let send_sync = send_sync_traits_impl(kind);
     let tokens = quote! {
         #attr
         #(#attrs)*
@@ -50,10 +100,11 @@ fn implement_pretend(attr: PretendAttr, item: ItemTrait) -> Result<TokenStream2>
         }
 
         #attr
-        impl<C, R, I> #name for pretend::Pretend<C, R, I>
-            where C: #client #send_sync,
-                  R: pretend::resolver::ResolveUrl #send_sync,
-                  I: pretend::interceptor::InterceptRequest #send_sync,
+        impl<__C, __R, __I, __M> #name for pretend::Pretend<__C, __R, __I, __M>
+            where __C: #client #send_sync,
+                  __R: #resolver #send_sync,
+                  __I: pretend::interceptor::InterceptRequest #send_sync,
+                  __M: pretend::metrics::RecordMetrics #send_sync,
         {
             #(#methods)*
         }
@@ -61,27 +112,180 @@ fn implement_pretend(attr: PretendAttr, item: ItemTrait) -> Result<TokenStream2>
     Ok(tokens)
 }
 
+// Blocking variants have the same requests, but are not async
+//
+// Async default methods can't be converted, and are not part of the variant.
+fn implement_blocking_variant(
+    variant: &Ident,
+    item: &ItemTrait,
+    kind: &ClientKind,
+    defaults: &Defaults,
+) -> Result<TokenStream2> {
+    if let ClientKind::Blocking = kind {
+        return Err(Error::new_spanned(variant, UNSUPPORTED_BLOCKING_VARIANT));
+    }
+
+    let name = &item.ident;
+    let doc = format!("Blocking variant of [`{}`]", name);
+    let attrs = item.attrs.iter().filter(|attr| !attr.path.is_ident("doc"));
+    let attrs = attrs
+        .cloned()
+        .chain(iter::once(parse_quote!(#[doc = #doc])));
+
+    let items = item.items.iter().filter_map(|item| match item {
+        TraitItem::Method(method) if is_default_method(method) => {
+            if method.sig.asyncness.is_some() {
+                None
+            } else {
+                Some(item.clone())
+            }
+        }
+        TraitItem::Method(method) => {
+            let mut method = method.clone();
+            method.sig.asyncness = None;
+            Some(TraitItem::Method(method))
+        }
+        _ => Some(item.clone()),
+    });
+
+    let item = ItemTrait {
+        attrs: attrs.collect(),
+        ident: variant.clone(),
+        items: items.collect(),
+        ..item.clone()
+    };
+    implement_client(&item, name, &ClientKind::Blocking, defaults)
+}
+
+// Describe the trait with a marker type
+//
+// Trait objects can't be used, as traits are not always object safe.
+fn implement_openapi(
+    name: &Ident,
+    vis: &Visibility,
+    items: &[TraitItem],
+    defaults: &Defaults,
+) -> TokenStream2 {
+    let description = format_ident!("{}Description", name);
+    let description_doc = format!("OpenAPI description of `{}`", name);
+    let operations = items.iter().filter_map(|item| match item {
+        TraitItem::Method(method) => Some(describe_method(method, name, defaults)),
+        _ => None,
+    });
+
+    quote! {
+        #[doc = #description_doc]
+        #[derive(Clone, Copy, Debug, Default)]
+        #vis struct #description;
+
+        impl pretend::openapi::DescribeApi for #description {
+            fn describe(document: &mut pretend::openapi::Document) {
+                #({ #operations })*
+            }
+        }
+    }
+}
+
+fn implement_server(
+    name: &Ident,
+    vis: &Visibility,
+    items: &[TraitItem],
+    kind: &ClientKind,
+    defaults: &Defaults,
+) -> TokenStream2 {
+    let handler = format_ident!("{}Handler", name);
+    let server = format_ident!("{}Server", name);
+    let handler_doc = format!("Handlers of the `{}` stub server", name);
+    let server_doc = format!("Stub server for `{}`", name);
+
+    let methods = items.iter().filter_map(|item| match item {
+        TraitItem::Method(method) => serve_method(method, kind, defaults),
+        _ => None,
+    });
+    let (handlers, routes): (Vec<_>, Vec<_>) = methods.unzip();
+
+    let attr = async_trait_attr(kind);
+    let client = client_implem(kind);
+    let send_sync = send_sync_traits_impl(kind);
+    let (asyncness, dot_await) = match kind {
+        ClientKind::Async | ClientKind::AsyncLocal => (quote! { async }, quote! { .await }),
+        ClientKind::Blocking => (TokenStream2::new(), TokenStream2::new()),
+    };
+
+    quote! {
+        #attr
+        #[doc = #handler_doc]
+        #vis trait #handler {
+            #(#handlers)*
+        }
+
+        #[doc = #server_doc]
+        #vis struct #server<H> {
+            handler: H,
+        }
+
+        impl<H> #server<H> {
+            /// Constructor
+            #vis fn new(handler: H) -> Self {
+                #server { handler }
+            }
+
+            /// Handler of this stub server
+            #vis fn handler(&self) -> &H {
+                &self.handler
+            }
+
+            #asyncness fn dispatch(
+                &self,
+                support: &pretend::internal::ServerSupport,
+            ) -> pretend::server::Reply
+                where H: #handler #send_sync,
+            {
+                #(#routes)*
+                support.not_found()
+            }
+        }
+
+        #attr
+        impl<H> #client for #server<H>
+            where H: #handler #send_sync,
+        {
+            #asyncness fn execute(
+                &self,
+                method: pretend::client::Method,
+                url: pretend::Url,
+                headers: pretend::HeaderMap,
+                body: Option<pretend::client::Bytes>,
+            ) -> pretend::Result<pretend::Response<pretend::client::Bytes>> {
+                let support = pretend::internal::ServerSupport::new(method, url, headers, body);
+                let reply = self.dispatch(&support) #dot_await;
+                Ok(reply.into())
+            }
+        }
+    }
+}
+
 enum ClientKind {
     Async,
     AsyncLocal,
     Blocking,
 }
 
-fn parse_client_kind(name: &Ident, attr: PretendAttr, items: &[TraitItem]) -> Result<ClientKind> {
+fn parse_client_kind(name: &Ident, local: bool, items: &[TraitItem]) -> Result<ClientKind> {
     let asyncs = items.iter().filter_map(is_method_async).collect::<Vec<_>>();
     let is_async = asyncs.iter().all(|item| item.value);
     let is_not_async = asyncs.iter().all(|item| !item.value);
 
     match (is_async, is_not_async) {
         (true, false) => {
-            if attr.local {
+            if local {
                 Ok(ClientKind::AsyncLocal)
             } else {
                 Ok(ClientKind::Async)
             }
         }
         (false, true) => {
-            if attr.local {
+            if local {
                 Err(Error::new(Span::call_site(), UNSUPPORTED_ATTR_SYNC))
             } else {
                 Ok(ClientKind::Blocking)
@@ -108,9 +312,9 @@ fn parse_client_kind(name: &Ident, attr: PretendAttr, items: &[TraitItem]) -> Re
     }
 }
 
-fn is_method_async(item: &TraitItem) -> Option<WithTokens<bool, Signature>> {
+fn is_method_async(item: &TraitItem) -> Option<WithTokens<'_, bool, Signature>> {
     match item {
-        TraitItem::Method(method) => {
+        TraitItem::Method(method) if !is_default_method(method) => {
             let is_async = method.sig.asyncness.is_some();
             Some(WithTokens::new(is_async, &method.sig))
         }
@@ -144,6 +348,20 @@ fn client_implem(kind: &ClientKind) -> TokenStream2 {
     }
 }
 
+fn resolver_implem(kind: &ClientKind) -> TokenStream2 {
+    match kind {
+        ClientKind::Async => quote! {
+            pretend::resolver::AsyncResolveUrl
+        },
+        ClientKind::AsyncLocal => quote! {
+            pretend::resolver::ResolveUrl
+        },
+        ClientKind::Blocking => quote! {
+            pretend::resolver::ResolveUrl
+        },
+    }
+}
+
 fn send_sync_traits_impl(kind: &ClientKind) -> TokenStream2 {
     match kind {
         ClientKind::Async => quote! {
@@ -2,23 +2,28 @@ mod attr;
 mod body;
 mod checks;
 mod headers;
+mod openapi;
 mod query;
 mod request;
+mod server;
 
 use self::body::implement_body;
-use self::checks::{check_correct_receiver, check_no_generics};
+use self::checks::{check_correct_receiver, check_no_const_generics};
 use self::headers::implement_headers;
 use self::query::implement_query;
 use self::request::get_request;
+use crate::attr::Defaults;
 use crate::errors::UNSUPPORTED_TRAIT_ITEM;
 use crate::format::format;
 use crate::ClientKind;
 use proc_macro2::{Ident, Span, TokenStream};
 use quote::quote;
 use std::mem;
-use syn::{Attribute, Error, Result, TraitItem, TraitItemMethod};
+use syn::{Attribute, Error, Result, Signature, TraitItem, TraitItemMethod};
 
 pub(crate) use self::attr::{parse_header_attr, parse_request_attr};
+pub(crate) use self::openapi::describe_method;
+pub(crate) use self::server::serve_method;
 
 pub(crate) enum BodyKind {
     None,
@@ -42,32 +47,62 @@ pub(crate) fn trait_item(item: &TraitItem) -> TraitItem {
     }
 }
 
-pub(crate) fn trait_item_implem(item: &TraitItem, kind: &ClientKind) -> Result<TokenStream> {
+pub(crate) fn trait_item_implem(
+    item: &TraitItem,
+    trait_name: &Ident,
+    kind: &ClientKind,
+    defaults: &Defaults,
+) -> Result<TokenStream> {
     match item {
-        TraitItem::Method(method) => implement_method(method, kind),
+        TraitItem::Method(method) if is_default_method(method) => Ok(TokenStream::new()),
+        TraitItem::Method(method) => implement_method(method, trait_name, kind, defaults),
+        TraitItem::Const(item) if item.default.is_some() => Ok(TokenStream::new()),
         _ => Err(Error::new_spanned(item, UNSUPPORTED_TRAIT_ITEM)),
     }
 }
 
+// Methods with a default implementation, and without `#[request]`,
+// are kept as they are
+pub(crate) fn is_default_method(method: &TraitItemMethod) -> bool {
+    let attrs = &method.attrs;
+    let has_request = attrs.iter().any(|attr| parse_request_attr(attr).is_some());
+    method.default.is_some() && !has_request
+}
+
 fn is_attribute(attr: &Attribute) -> bool {
     let is_request = parse_request_attr(attr).is_some();
     let is_header = parse_header_attr(attr).is_some();
     is_request || is_header
 }
 
-fn implement_method(method: &TraitItemMethod, kind: &ClientKind) -> Result<TokenStream> {
-    check_no_generics(method)?;
+fn implement_method(
+    method: &TraitItemMethod,
+    trait_name: &Ident,
+    kind: &ClientKind,
+    defaults: &Defaults,
+) -> Result<TokenStream> {
+    check_no_const_generics(method)?;
     check_correct_receiver(method)?;
 
-    let query = implement_query(method);
+    let query = implement_query(method, defaults);
     let body = implement_body(method)?;
-    let headers = implement_headers(method)?;
+    let headers = implement_headers(method, defaults)?;
 
     let sig = &method.sig;
-    let (method, path) = get_request(method)?;
+    let (method, path) = get_request(method, defaults)?;
     let method = Ident::new(&method, Span::call_site());
+    let endpoint = implement_endpoint(trait_name, sig, &path);
     let path = format(path, "path");
 
+    let create_url = match kind {
+        ClientKind::Async => quote! {
+            support.create_url_async(&method, path).await
+        },
+        ClientKind::AsyncLocal | ClientKind::Blocking => quote! {
+            support.create_url(&method, path)
+        },
+    };
+
     let execute_request = match kind {
         ClientKind::Async => quote! {
             support.request(method, url, headers, body).await
@@ -87,12 +122,19 @@ fn implement_method(method: &TraitItemMethod, kind: &ClientKind) -> Result<Token
             #headers
             #body
 
-            let support = pretend::internal::MacroSupport::new(self);
-            let url = support.create_url(path)?;
+            let support = pretend::internal::MacroSupport::new(self, #endpoint);
+            let url = #create_url ?;
             #query
 
-            let response = #execute_request ?;
-            pretend::internal::IntoResponse::into_response(response)
+            #execute_request
         }
     })
 }
+
+fn implement_endpoint(trait_name: &Ident, sig: &Signature, path: &str) -> TokenStream {
+    let trait_name = trait_name.to_string();
+    let name = sig.ident.to_string();
+    quote! {
+        pretend::Endpoint::new(#trait_name, #name, #path)
+    }
+}
@@ -12,7 +12,7 @@ pub(crate) fn implement_body(method: &TraitItemMethod) -> Result<TokenStream> {
             let body = pretend::internal::Body::<()>::None;
         },
         BodyKind::Body => quote! {
-            let body = pretend::internal::Body::<()>::Raw(pretend::client::Bytes::from(body));
+            let body = pretend::internal::Body::<()>::Raw(std::convert::Into::into(body));
         },
         BodyKind::Form => quote! {
             let body = pretend::internal::Body::Form(&form);
@@ -24,7 +24,7 @@ pub(crate) fn implement_body(method: &TraitItemMethod) -> Result<TokenStream> {
     Ok(implem)
 }
 
-fn get_body(method: &TraitItemMethod) -> Result<BodyKind> {
+pub(crate) fn get_body(method: &TraitItemMethod) -> Result<BodyKind> {
     let inputs = &method.sig.inputs;
     let single = inputs
         .iter()
@@ -46,7 +46,7 @@ fn get_body(method: &TraitItemMethod) -> Result<BodyKind> {
     }
 }
 
-fn parse_body_kind(ident: &Ident) -> Option<WithTokens<BodyKind, Ident>> {
+fn parse_body_kind(ident: &Ident) -> Option<WithTokens<'_, BodyKind, Ident>> {
     if ident == "body" {
         Some(WithTokens::new(BodyKind::Body, ident))
     } else if ident == "form" {
@@ -1,25 +1,16 @@
-use crate::errors::{UNSUPPORTED_GENERICS, UNSUPPORTED_RECEIVER};
-use syn::{Error, FnArg, Receiver, Result, Signature, TraitItemMethod};
+use crate::errors::{UNSUPPORTED_CONST_GENERICS, UNSUPPORTED_RECEIVER};
+use syn::{Error, FnArg, GenericParam, Receiver, Result, TraitItemMethod};
 
-pub(crate) fn check_no_generics(method: &TraitItemMethod) -> Result<()> {
-    let sig = &method.sig;
-    check_no_generic_params(sig)?;
-    check_no_where_clause(sig)?;
-    Ok(())
-}
-fn check_no_generic_params(sig: &Signature) -> Result<()> {
-    if sig.generics.params.is_empty() {
-        Ok(())
-    } else {
-        Err(Error::new_spanned(&sig.generics, UNSUPPORTED_GENERICS))
-    }
-}
+pub(crate) fn check_no_const_generics(method: &TraitItemMethod) -> Result<()> {
+    let params = &method.sig.generics.params;
+    let const_param = params.iter().find_map(|param| match param {
+        GenericParam::Const(param) => Some(param),
+        _ => None,
+    });
 
-fn check_no_where_clause(sig: &Signature) -> Result<()> {
-    if let Some(where_clause) = sig.generics.where_clause.as_ref() {
-        Err(Error::new_spanned(where_clause, UNSUPPORTED_GENERICS))
-    } else {
-        Ok(())
+    match const_param {
+        Some(param) => Err(Error::new_spanned(param, UNSUPPORTED_CONST_GENERICS)),
+        None => Ok(()),
     }
 }
 
@@ -1,3 +1,4 @@
+use crate::attr::Defaults;
 use crate::errors::{Report, INVALID_HEADER, METHOD_FAILURE};
 use crate::format::format;
 use crate::method::parse_header_attr;
@@ -6,14 +7,17 @@ use proc_macro2::TokenStream;
 use quote::quote;
 use syn::{Attribute, Error, Result, TraitItemMethod};
 
-pub(crate) fn implement_headers(method: &TraitItemMethod) -> Result<TokenStream> {
+pub(crate) fn implement_headers(
+    method: &TraitItemMethod,
+    defaults: &Defaults,
+) -> Result<TokenStream> {
     let attrs = &method.attrs;
     let headers = attrs
         .iter()
         .filter_map(parse_header_attr)
         .collect::<Vec<_>>();
 
-    let implem = if headers.is_empty() {
+    let implem = if headers.is_empty() && defaults.headers.is_empty() {
         quote! {
             let headers = pretend::HeaderMap::new();
         }
@@ -24,9 +28,11 @@ pub(crate) fn implement_headers(method: &TraitItemMethod) -> Result<TokenStream>
             .collect::<Report<_>>()
             .into_result(|| Error::new_spanned(method, METHOD_FAILURE))?;
 
+        let defaults = defaults.headers.iter().map(implement_default_header);
         quote! {
             let mut headers = pretend::HeaderMap::new();
             #(#headers)*
+            #(#defaults)*
         }
     };
     Ok(implem)
@@ -50,3 +56,10 @@ fn implement_header(name: String, value: String) -> TokenStream {
         pretend::internal::build_header(&mut headers, header_name, header_value)?;
     }
 }
+
+// Default headers are only added if the method did not set them
+fn implement_default_header((name, value): &(String, String)) -> TokenStream {
+    quote! {
+        pretend::internal::build_default_header(&mut headers, #name, #value)?;
+    }
+}
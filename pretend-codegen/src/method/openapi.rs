@@ -0,0 +1,167 @@
+use super::body::get_body;
+use super::query::has_query;
+use super::request::get_request;
+use super::{implement_endpoint, BodyKind};
+use crate::attr::Defaults;
+use crate::format::find_params;
+use crate::method::parse_header_attr;
+use crate::utils::uses_generics;
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::quote;
+use syn::{FnArg, GenericArgument, Pat, PathArguments, ReturnType, TraitItemMethod, Type};
+
+pub(crate) fn describe_method(
+    method: &TraitItemMethod,
+    trait_name: &Ident,
+    defaults: &Defaults,
+) -> TokenStream {
+    // Methods are checked when generating their implementation
+    let (http_method, path) = match get_request(method, defaults) {
+        Ok(request) => request,
+        Err(_) => return TokenStream::new(),
+    };
+    let body = get_body(method).unwrap_or(BodyKind::None);
+
+    let sig = &method.sig;
+    let endpoint = implement_endpoint(trait_name, sig, &path);
+    let http_method = Ident::new(&http_method, Span::call_site());
+
+    let mut split = path.splitn(2, '?');
+    let path_params = split.next().map(find_params).unwrap_or_default();
+    let path_params = path_params.into_iter().filter_map(|name| {
+        let ty = scalar_type(method, name)?;
+        Some(quote! {
+            .with_path_parameter::<#ty>(document, #name)
+        })
+    });
+    let query_params = split.next().map(find_params).unwrap_or_default();
+    let query_params = query_params.into_iter().filter_map(|name| {
+        let ty = scalar_type(method, name)?;
+        Some(quote! {
+            .with_query_parameter::<#ty>(document, #name)
+        })
+    });
+
+    let headers = method.attrs.iter().filter_map(parse_header_attr);
+    let headers = headers.filter_map(|item| item.value);
+    let headers = headers.filter_map(|(name, value)| {
+        let is_templated = find_params(&name).is_empty() && !find_params(&value).is_empty();
+        if is_templated {
+            Some(quote! {
+                .with_header_parameter(#name)
+            })
+        } else {
+            None
+        }
+    });
+
+    let query = if has_query(method) {
+        param_type(method, "query").map(|ty| {
+            quote! {
+                .with_query::<#ty>(document)
+            }
+        })
+    } else {
+        None
+    };
+
+    let body = match body {
+        BodyKind::None => None,
+        BodyKind::Body => Some(quote! {
+            .with_raw_body()
+        }),
+        BodyKind::Form => param_type(method, "form").map(|ty| {
+            quote! {
+                .with_form_body::<#ty>(document)
+            }
+        }),
+        BodyKind::Json => param_type(method, "json").map(|ty| {
+            quote! {
+                .with_json_body::<#ty>(document)
+            }
+        }),
+    };
+
+    let response = response_type(&sig.output);
+    let response = response.filter(|ty| !uses_generics(ty, &sig.generics));
+    let response = response.map(|ty| {
+        quote! {
+            .with_response::<#ty>(document)
+        }
+    });
+
+    quote! {
+        let operation = pretend::openapi::Operation::new(
+            #endpoint,
+            pretend::client::Method::#http_method,
+        )
+        #(#path_params)*
+        #(#query_params)*
+        #(#headers)*
+        #query
+        #body
+        #response;
+        document.add_operation(operation);
+    }
+}
+
+fn find_param<'a>(method: &'a TraitItemMethod, name: &str) -> Option<&'a Type> {
+    let inputs = &method.sig.inputs;
+    inputs.iter().find_map(|input| match input {
+        FnArg::Typed(param) => match &*param.pat {
+            Pat::Ident(pat) if pat.ident == name => Some(strip_reference(&param.ty)),
+            _ => None,
+        },
+        _ => None,
+    })
+}
+
+// Types that depend on generic parameters can't be described
+fn param_type<'a>(method: &'a TraitItemMethod, name: &str) -> Option<&'a Type> {
+    let ty = find_param(method, name)?;
+    if uses_generics(ty, &method.sig.generics) {
+        None
+    } else {
+        Some(ty)
+    }
+}
+
+// Generic path and query parameters are described as strings
+fn scalar_type(method: &TraitItemMethod, name: &str) -> Option<TokenStream> {
+    let ty = find_param(method, name)?;
+    if uses_generics(ty, &method.sig.generics) {
+        Some(quote! { String })
+    } else {
+        Some(quote! { #ty })
+    }
+}
+
+pub(super) fn strip_reference(ty: &Type) -> &Type {
+    match ty {
+        Type::Reference(reference) => strip_reference(&reference.elem),
+        Type::Paren(paren) => strip_reference(&paren.elem),
+        Type::Group(group) => strip_reference(&group.elem),
+        _ => ty,
+    }
+}
+
+// Extract `T` from `Result<T>`
+fn response_type(output: &ReturnType) -> Option<&Type> {
+    let ty = match output {
+        ReturnType::Type(_, ty) => ty,
+        ReturnType::Default => return None,
+    };
+    let path = match &**ty {
+        Type::Path(path) => path,
+        _ => return None,
+    };
+    let segment = path.path.segments.last()?;
+    let args = match &segment.arguments {
+        PathArguments::AngleBracketed(args) => args,
+        _ => return None,
+    };
+    args.args.iter().find_map(|arg| match arg {
+        GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    })
+}
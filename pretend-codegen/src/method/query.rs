@@ -1,19 +1,33 @@
+use crate::attr::Defaults;
 use crate::utils::parse_param_name;
 use proc_macro2::TokenStream;
 use quote::quote;
 use syn::TraitItemMethod;
 
-pub(crate) fn implement_query(method: &TraitItemMethod) -> TokenStream {
-    if has_query(method) {
+pub(crate) fn implement_query(method: &TraitItemMethod, defaults: &Defaults) -> TokenStream {
+    let query = if has_query(method) {
         quote! {
             let url = pretend::internal::build_query(url, &query)?;
         }
     } else {
         TokenStream::new()
+    };
+
+    if defaults.query.is_empty() {
+        query
+    } else {
+        let pairs = defaults
+            .query
+            .iter()
+            .map(|(name, value)| quote! { (#name, #value) });
+        quote! {
+            #query
+            let url = pretend::internal::build_default_query(url, &[#(#pairs),*]);
+        }
     }
 }
 
-fn has_query(method: &TraitItemMethod) -> bool {
+pub(crate) fn has_query(method: &TraitItemMethod) -> bool {
     let inputs = &method.sig.inputs;
     inputs
         .iter()
@@ -1,3 +1,4 @@
+use crate::attr::Defaults;
 use crate::errors::{
     ErrorsExt, INVALID_REQUEST, MISSING_REQUEST, TOO_MANY_REQUESTS, TOO_MANY_REQUESTS_HINT,
 };
@@ -5,7 +6,10 @@ use crate::method::parse_request_attr;
 use crate::utils::Single;
 use syn::{Error, Result, TraitItemMethod};
 
-pub(crate) fn get_request(method: &TraitItemMethod) -> Result<(String, String)> {
+pub(crate) fn get_request(
+    method: &TraitItemMethod,
+    defaults: &Defaults,
+) -> Result<(String, String)> {
     let attrs = &method.attrs;
     let single = attrs
         .iter()
@@ -17,7 +21,9 @@ pub(crate) fn get_request(method: &TraitItemMethod) -> Result<(String, String)>
         Single::Single(item) => {
             let value = item.value;
             let tokens = item.tokens;
-            value.ok_or_else(|| Error::new_spanned(tokens, INVALID_REQUEST))
+            let (method, path) =
+                value.ok_or_else(|| Error::new_spanned(tokens, INVALID_REQUEST))?;
+            Ok((method, defaults.prefix_path(path)))
         }
         Single::TooMany(requests) => {
             let errors = requests
@@ -0,0 +1,144 @@
+use super::body::get_body;
+use super::openapi::strip_reference;
+use super::query::has_query;
+use super::request::get_request;
+use super::BodyKind;
+use crate::attr::Defaults;
+use crate::format::find_params;
+use crate::method::parse_header_attr;
+use crate::utils::uses_generics;
+use crate::ClientKind;
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::quote;
+use syn::{FnArg, Pat, TraitItemMethod, Type};
+
+// Generate the handler declaration and the route of a method
+pub(crate) fn serve_method(
+    method: &TraitItemMethod,
+    kind: &ClientKind,
+    defaults: &Defaults,
+) -> Option<(TokenStream, TokenStream)> {
+    // Methods are checked when generating their implementation
+    let (http_method, path) = get_request(method, defaults).ok()?;
+    let body = get_body(method).ok()?;
+    let http_method = Ident::new(&http_method, Span::call_site());
+
+    let path_params = find_params(&path);
+    let headers = method.attrs.iter().filter_map(parse_header_attr);
+    let headers = headers.filter_map(|item| item.value).collect::<Vec<_>>();
+
+    let inputs = method.sig.inputs.iter().filter_map(|input| match input {
+        FnArg::Typed(param) => match &*param.pat {
+            Pat::Ident(pat) => Some((&pat.ident, &*param.ty)),
+            _ => None,
+        },
+        _ => None,
+    });
+
+    // Values whose type depend on generic parameters are passed undecoded
+    let generics = &method.sig.generics;
+    let bytes = quote! { pretend::client::Bytes };
+    let string = quote! { String };
+
+    let mut params = Vec::new();
+    let mut args = Vec::new();
+    for (ident, ty) in inputs {
+        let name = ident.to_string();
+        let is_generic = uses_generics(ty, generics);
+        let ty = owned_type(ty);
+        let (ty, arg) = match (name.as_str(), &body) {
+            ("body", BodyKind::Body) => (bytes.clone(), quote! { route.body() }),
+            ("form", BodyKind::Form) | ("json", BodyKind::Json) if is_generic => {
+                (bytes.clone(), quote! { route.body() })
+            }
+            ("form", BodyKind::Form) => (ty, extract(quote! { route.form() })),
+            ("json", BodyKind::Json) => (ty, extract(quote! { route.json() })),
+            ("query", _) if has_query(method) && is_generic => {
+                (string.clone(), quote! { route.raw_query() })
+            }
+            ("query", _) if has_query(method) => (ty, extract(quote! { route.query() })),
+            _ => {
+                let arg = if path_params.contains(&name.as_str()) {
+                    quote! { route.param(#name) }
+                } else {
+                    match find_header(&headers, &name) {
+                        Some((header_name, header_value)) => {
+                            quote! { route.header(#header_name, #header_value, #name) }
+                        }
+                        None => continue,
+                    }
+                };
+                let ty = if is_generic { string.clone() } else { ty };
+                (ty, extract(arg))
+            }
+        };
+        params.push(quote! { #ident: #ty });
+        args.push(arg);
+    }
+
+    let docs = method.attrs.iter().filter(|attr| attr.path.is_ident("doc"));
+    let asyncness = &method.sig.asyncness;
+    let ident = &method.sig.ident;
+    let handler = quote! {
+        #(#docs)*
+        #asyncness fn #ident(&self, #(#params),*) -> pretend::server::Reply;
+    };
+
+    let call = match kind {
+        ClientKind::Async | ClientKind::AsyncLocal => quote! {
+            self.handler.#ident(#(#args),*).await
+        },
+        ClientKind::Blocking => quote! {
+            self.handler.#ident(#(#args),*)
+        },
+    };
+    let method = quote! { &pretend::client::Method::#http_method };
+    let route = if args.is_empty() {
+        quote! {
+            if support.route(#method, #path).is_some() {
+                return #call;
+            }
+        }
+    } else {
+        quote! {
+            if let Some(route) = support.route(#method, #path) {
+                return #call;
+            }
+        }
+    };
+    Some((handler, route))
+}
+
+// Handlers take owned values
+fn owned_type(ty: &Type) -> TokenStream {
+    match strip_reference(ty) {
+        Type::Path(path) if path.qself.is_none() && path.path.is_ident("str") => quote! {
+            String
+        },
+        Type::Slice(slice) => {
+            let elem = &slice.elem;
+            quote! {
+                Vec<#elem>
+            }
+        }
+        ty => quote! {
+            #ty
+        },
+    }
+}
+
+fn find_header<'a>(headers: &'a [(String, String)], param: &str) -> Option<&'a (String, String)> {
+    headers.iter().find(|(name, value)| {
+        let is_static = find_params(name).is_empty();
+        is_static && find_params(value).contains(&param)
+    })
+}
+
+fn extract(arg: TokenStream) -> TokenStream {
+    quote! {
+        match #arg {
+            Ok(value) => value,
+            Err(reply) => return reply,
+        }
+    }
+}
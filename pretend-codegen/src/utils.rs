@@ -6,8 +6,9 @@ pub(crate) use attr::parse_name_value_2_attr;
 pub(crate) use single::Single;
 pub(crate) use withtokens::WithTokens;
 
-use proc_macro2::Ident;
-use syn::{FnArg, Pat};
+use proc_macro2::{Ident, TokenStream, TokenTree};
+use quote::ToTokens;
+use syn::{FnArg, GenericParam, Generics, Pat, Type};
 
 pub(crate) fn parse_param_name(input: &FnArg) -> Option<&Ident> {
     match input {
@@ -18,3 +19,24 @@ pub(crate) fn parse_param_name(input: &FnArg) -> Option<&Ident> {
         _ => None,
     }
 }
+
+// Check if a type depends on the generic parameters of a method
+pub(crate) fn uses_generics(ty: &Type, generics: &Generics) -> bool {
+    let params = generics
+        .params
+        .iter()
+        .filter_map(|param| match param {
+            GenericParam::Type(param) => Some(&param.ident),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    has_generic_tokens(ty.to_token_stream(), &params)
+}
+
+fn has_generic_tokens(tokens: TokenStream, params: &[&Ident]) -> bool {
+    tokens.into_iter().any(|token| match token {
+        TokenTree::Ident(ident) => ident == "impl" || params.iter().any(|param| ident == **param),
+        TokenTree::Group(group) => has_generic_tokens(group.stream(), params),
+        _ => false,
+    })
+}
@@ -1,5 +1,6 @@
 use std::iter::FromIterator;
 
+#[allow(clippy::enum_variant_names)]
 pub(crate) enum Single<T> {
     None,
     Single(T),
@@ -0,0 +1,290 @@
+//! `curl` based `pretend` client
+//!
+//! This client uses `libcurl` through the `curl` crate. `libcurl` options, like
+//! the CA store, proxy authentication or host resolution overrides, are set with
+//! a configuration function that is called before each request.
+//!
+//! ```rust
+//! use pretend_curl::curl::easy::{Auth, List};
+//! use pretend_curl::Client;
+//!
+//! let client = Client::with_configuration(|easy| {
+//!     let mut auth = Auth::new();
+//!     auth.ntlm(true);
+//!     easy.proxy_auth(&auth)?;
+//!
+//!     let mut resolve = List::new();
+//!     resolve.append("example.com:443:127.0.0.1")?;
+//!     easy.resolve(resolve)
+//! });
+//! ```
+//!
+//! Clients can also be built from a [`ClientConfig`]. Root certificates are
+//! used instead of the system CA store.
+//!
+//! Requests are executed with a single `curl` handle, that is reused to keep
+//! connections alive. Concurrent requests on the same client are serialized.
+//! For asynchronous requests, `pretend-isahc` also relies on `libcurl`.
+
+#![warn(missing_docs)]
+#![forbid(unsafe_code)]
+
+pub use curl;
+
+use curl::easy::{Auth, Easy, List};
+use pretend::client::{BlockingClient, Bytes, Method};
+use pretend::config::{ClientConfig, Identity, RedirectPolicy, UnsupportedOption};
+use pretend::http::header::{HeaderName, CONTENT_TYPE};
+use pretend::http::HeaderValue;
+use pretend::{Error, HeaderMap, Response, Result, StatusCode, Url};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, PoisonError};
+
+type Configuration = dyn Fn(&mut Easy) -> std::result::Result<(), curl::Error> + Send + Sync;
+
+/// `curl` based `pretend` client
+pub struct Client {
+    easy: Mutex<Easy>,
+    configuration: Option<Arc<Configuration>>,
+}
+
+impl Default for Client {
+    fn default() -> Self {
+        Client::new()
+    }
+}
+
+impl Client {
+    /// Constructor
+    ///
+    /// This constructor creates a client implementation
+    /// for `pretend` using default `curl` options.
+    pub fn new() -> Self {
+        Client {
+            easy: Mutex::new(Easy::new()),
+            configuration: None,
+        }
+    }
+
+    /// Constructor with a configuration
+    ///
+    /// This constructor creates a client implementation
+    /// for `pretend` that configures the `curl` handle with
+    /// the supplied function before each request.
+    pub fn with_configuration<F>(configuration: F) -> Self
+    where
+        F: Fn(&mut Easy) -> std::result::Result<(), curl::Error> + Send + Sync + 'static,
+    {
+        Client {
+            easy: Mutex::new(Easy::new()),
+            configuration: Some(Arc::new(configuration)),
+        }
+    }
+
+    /// Constructor with a configuration
+    ///
+    /// This constructor creates a client implementation
+    /// for `pretend` that configures the `curl` handle from
+    /// the supplied configuration.
+    pub fn from_config(config: &ClientConfig) -> Result<Self> {
+        let mut certificates = Vec::new();
+        for certificate in config.root_certificates() {
+            let pem = certificate.pem().map_err(Error::client)?;
+            certificates.extend_from_slice(&pem);
+            certificates.push(b'\n');
+        }
+        match config.identity() {
+            None | Some(Identity::Pem(_)) | Some(Identity::Pkcs12 { .. }) => {}
+            Some(_) => return Err(Error::client(UnsupportedOption::new("identity"))),
+        }
+
+        let config = config.clone();
+        Ok(Client::with_configuration(move |easy| {
+            configure(easy, &config, &certificates)
+        }))
+    }
+}
+
+impl Clone for Client {
+    fn clone(&self) -> Self {
+        Client {
+            easy: Mutex::new(Easy::new()),
+            configuration: self.configuration.clone(),
+        }
+    }
+}
+
+impl fmt::Debug for Client {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Client").finish()
+    }
+}
+
+impl BlockingClient for Client {
+    fn execute(
+        &self,
+        method: Method,
+        url: Url,
+        headers: HeaderMap,
+        body: Option<Bytes>,
+    ) -> Result<Response<Bytes>> {
+        let mut easy = self.easy.lock().unwrap_or_else(PoisonError::into_inner);
+        // Options are reset, but connections are kept alive
+        easy.reset();
+        if let Some(configuration) = &self.configuration {
+            configuration(&mut easy).map_err(Error::client)?;
+        }
+
+        prepare_request(&mut easy, method, url, headers, body).map_err(Error::request)?;
+
+        let mut body = Vec::new();
+        let mut header_lines = Vec::new();
+        {
+            let mut transfer = easy.transfer();
+            let result = transfer.write_function(|data| {
+                body.extend_from_slice(data);
+                Ok(data.len())
+            });
+            result.map_err(Error::response)?;
+            let result = transfer.header_function(|line| {
+                header_lines.push(line.to_vec());
+                true
+            });
+            result.map_err(Error::response)?;
+            transfer.perform().map_err(Error::response)?;
+        }
+
+        let status = easy.response_code().map_err(Error::response)?;
+        let status = StatusCode::from_u16(status as u16).map_err(Error::response)?;
+        let headers = parse_headers(&header_lines)?;
+        let response = Response::new(status, headers, Bytes::from(body));
+
+        let url = easy.effective_url().map_err(Error::response)?;
+        let url = url.map(Url::parse).transpose().map_err(Error::response)?;
+        match url {
+            Some(url) => Ok(response.with_url(url)),
+            None => Ok(response),
+        }
+    }
+}
+
+fn prepare_request(
+    easy: &mut Easy,
+    method: Method,
+    url: Url,
+    headers: HeaderMap,
+    body: Option<Bytes>,
+) -> std::result::Result<(), curl::Error> {
+    easy.url(url.as_str())?;
+
+    let mut list = List::new();
+    for (name, value) in headers.iter() {
+        let mut header = format!("{}: ", name).into_bytes();
+        header.extend_from_slice(value.as_bytes());
+        list.append(&String::from_utf8_lossy(&header))?;
+    }
+
+    match body {
+        Some(body) => {
+            // curl sends a form content type by default
+            if !headers.contains_key(CONTENT_TYPE) {
+                list.append("Content-Type:")?;
+            }
+            easy.post_fields_copy(&body)?;
+            if method != Method::POST {
+                easy.custom_request(method.as_str())?;
+            }
+        }
+        None if method == Method::GET => easy.get(true)?,
+        None if method == Method::HEAD => easy.nobody(true)?,
+        None => easy.custom_request(method.as_str())?,
+    }
+    easy.http_headers(list)
+}
+
+// Only headers of the last response are kept, when curl
+// receives several responses, like informational ones
+fn parse_headers(lines: &[Vec<u8>]) -> Result<HeaderMap> {
+    let mut headers = HeaderMap::new();
+    for line in lines {
+        if line.starts_with(b"HTTP/") {
+            headers.clear();
+            continue;
+        }
+
+        let line = trim(line);
+        let index = line.iter().position(|byte| *byte == b':');
+        if let Some(index) = index {
+            let name = String::from_utf8_lossy(&line[..index]);
+            let name = HeaderName::from_str(name.trim()).map_err(Error::response)?;
+            let value = HeaderValue::from_bytes(trim(&line[index + 1..]));
+            let value = value.map_err(Error::response)?;
+            headers.append(name, value);
+        }
+    }
+    Ok(headers)
+}
+
+fn trim(bytes: &[u8]) -> &[u8] {
+    let start = bytes.iter().position(|byte| !byte.is_ascii_whitespace());
+    let end = bytes.iter().rposition(|byte| !byte.is_ascii_whitespace());
+    match (start, end) {
+        (Some(start), Some(end)) => &bytes[start..=end],
+        _ => &[],
+    }
+}
+
+fn configure(
+    easy: &mut Easy,
+    config: &ClientConfig,
+    certificates: &[u8],
+) -> std::result::Result<(), curl::Error> {
+    if let Some(timeout) = config.connect_timeout() {
+        easy.connect_timeout(timeout)?;
+    }
+    if let Some(timeout) = config.timeout() {
+        easy.timeout(timeout)?;
+    }
+    if let Some(proxy) = config.proxy() {
+        easy.proxy(proxy.url().as_str())?;
+        if let Some((username, password)) = proxy.basic_auth() {
+            let mut auth = Auth::new();
+            auth.basic(true);
+            easy.proxy_auth(&auth)?;
+            easy.proxy_username(username)?;
+            easy.proxy_password(password)?;
+        }
+    }
+    if !certificates.is_empty() {
+        easy.ssl_cainfo_blob(certificates)?;
+    }
+    match config.identity() {
+        Some(Identity::Pem(pem)) => {
+            easy.ssl_cert_type("PEM")?;
+            easy.ssl_cert_blob(pem)?;
+            easy.ssl_key_blob(pem)?;
+        }
+        Some(Identity::Pkcs12 { der, password }) => {
+            easy.ssl_cert_type("P12")?;
+            easy.ssl_cert_blob(der)?;
+            easy.key_password(password)?;
+        }
+        _ => {}
+    }
+    match config.redirect_policy() {
+        Some(RedirectPolicy::None) => easy.follow_location(false)?,
+        Some(RedirectPolicy::Limit(limit)) => {
+            easy.follow_location(true)?;
+            easy.max_redirections(limit as u32)?;
+        }
+        None => {}
+    }
+    if let Some(user_agent) = config.user_agent() {
+        easy.useragent(user_agent)?;
+    }
+    if let Some(size) = config.pool_size() {
+        easy.max_connects(size as u32)?;
+    }
+    Ok(())
+}
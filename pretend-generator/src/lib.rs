@@ -0,0 +1,262 @@
+//! Generate `pretend` traits from OpenAPI documents
+//!
+//! This crate reads an OpenAPI 3 document, in JSON or YAML, and generates
+//! a trait annotated with `#[pretend]`, as well as the `serde` model types
+//! used by the operations.
+//!
+//! # Usage from `build.rs`
+//!
+//! The generated code can be written to `OUT_DIR` by a build script,
+//!
+//! ```no_run
+//! use pretend_generator::{Flavor, Generator};
+//! use std::env;
+//! use std::path::Path;
+//!
+//! let output = Path::new(&env::var("OUT_DIR").unwrap()).join("petstore.rs");
+//! Generator::new()
+//!     .with_flavor(Flavor::Blocking)
+//!     .with_trait_name("PetStore")
+//!     .generate_file("petstore.yaml", output)
+//!     .unwrap();
+//! ```
+//!
+//! and included in the crate.
+//!
+//! ```ignore
+//! include!(concat!(env!("OUT_DIR"), "/petstore.rs"));
+//! ```
+//!
+//! The generated code uses `pretend` and `serde` with the `derive` feature, that
+//! should both be dependencies of the crate including it.
+//!
+//! # Usage as a binary
+//!
+//! The `pretend-generator` binary prints the generated code for a document.
+//!
+//! ```text
+//! pretend-generator [--async | --local | --blocking] [--trait-name NAME] SPEC [OUTPUT]
+//! ```
+//!
+//! # Generated code
+//!
+//! Each operation is described as a method of the generated trait.
+//!
+//! - Methods are named after the `operationId`, or after the HTTP method and path.
+//! - Path and required header parameters are passed as method parameters. Optional
+//!   header parameters are skipped, and can be set with a request interceptor.
+//! - Query parameters are grouped in a struct, passed as the `query` parameter.
+//! - JSON and form request bodies are passed as `json` and `form`, while other
+//!   request bodies are passed as raw bytes with `body`.
+//! - JSON responses are returned as [`Json`], or as [`JsonResult`] when the
+//!   document describes an error response. Text responses are returned as `String`
+//!   and other responses as `Vec<u8>`.
+//!
+//! Schemas in `components` are generated as structs, enums or type aliases.
+//! Components named like the `Box`, `Option`, `String` and `Vec` types are suffixed
+//! with `_`, as the generated code uses these types.
+//! Inline objects and enums are generated as types named after where they are used.
+//! Fields referring back to the struct that contains them are boxed, while type
+//! aliases referring to themselves are not supported.
+//! Schemas that can't be represented by a Rust type, like `oneOf`, are
+//! represented by `serde_json::Value`.
+//!
+//! Cookie parameters, array query parameters and references to other documents
+//! are not supported.
+//!
+//! [`Json`]: https://docs.rs/pretend/latest/pretend/struct.Json.html
+//! [`JsonResult`]: https://docs.rs/pretend/latest/pretend/enum.JsonResult.html
+
+#![warn(missing_docs)]
+#![forbid(unsafe_code)]
+
+mod model;
+mod names;
+mod operation;
+
+pub use openapiv3;
+
+use crate::model::{doc, Models};
+use crate::names::{checked_ident, type_name};
+use crate::operation::implement_operation;
+use openapiv3::{OpenAPI, ReferenceOr};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use std::path::Path;
+use std::{env, fs, io, result};
+use thiserror::Error;
+
+/// Generator errors
+#[derive(Error, Debug)]
+pub enum Error {
+    /// Error when reading the document or writing the generated code
+    #[error("Failed to read or write file")]
+    Io(#[from] io::Error),
+    /// Error when parsing the document
+    #[error("Invalid OpenAPI document")]
+    Parse(#[from] serde_yaml::Error),
+    /// Error when the document uses a construct that can't be generated
+    #[error("Unsupported OpenAPI construct: {0}")]
+    Unsupported(String),
+    /// Error when formatting the generated code
+    #[error("Failed to format generated code")]
+    Format(#[from] syn::Error),
+}
+
+/// Generator result type
+pub type Result<T> = result::Result<T, Error>;
+
+/// Flavor of the generated trait
+///
+/// The flavor selects the kind of client that can be used
+/// with the generated trait.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Flavor {
+    /// Async methods, for `Client`
+    Async,
+    /// Async methods, for `LocalClient`
+    ///
+    /// The trait is annotated with `#[pretend(?Send)]`.
+    AsyncLocal,
+    /// Blocking methods, for `BlockingClient`
+    Blocking,
+}
+
+/// OpenAPI code generator
+///
+/// See module level documentation for more information.
+#[derive(Clone, Debug)]
+pub struct Generator {
+    flavor: Flavor,
+    trait_name: Option<String>,
+}
+
+impl Generator {
+    /// Constructor
+    ///
+    /// This constructor creates a generator for async traits. The name of the
+    /// trait is derived from the title of the document.
+    pub fn new() -> Self {
+        Generator {
+            flavor: Flavor::Async,
+            trait_name: None,
+        }
+    }
+
+    /// Set the flavor of the generated trait
+    pub fn with_flavor(self, flavor: Flavor) -> Self {
+        Generator { flavor, ..self }
+    }
+
+    /// Set the name of the generated trait
+    ///
+    /// Keywords are suffixed with `_`. Generation fails with
+    /// [`Error::Unsupported`] if the name is not a valid identifier.
+    pub fn with_trait_name<N>(self, trait_name: N) -> Self
+    where
+        N: Into<String>,
+    {
+        let trait_name = Some(trait_name.into());
+        Generator { trait_name, ..self }
+    }
+
+    /// Generate code for a document
+    pub fn generate(&self, spec: &OpenAPI) -> Result<String> {
+        let tokens = self.implement(spec)?;
+        let file = syn::parse2(tokens)?;
+        let code = prettyplease::unparse(&file);
+        Ok(format!("{}\n{}", HEADER, code))
+    }
+
+    /// Generate code for a document, in JSON or YAML
+    pub fn generate_from_str(&self, spec: &str) -> Result<String> {
+        let spec = serde_yaml::from_str(spec)?;
+        self.generate(&spec)
+    }
+
+    /// Generate code for a document file, and write it to the output file
+    ///
+    /// When used from `build.rs`, cargo is instructed to run the build
+    /// script again if the document changes.
+    pub fn generate_file<P, Q>(&self, spec: P, output: Q) -> Result<()>
+    where
+        P: AsRef<Path>,
+        Q: AsRef<Path>,
+    {
+        let spec = spec.as_ref();
+        if env::var_os("OUT_DIR").is_some() {
+            println!("cargo:rerun-if-changed={}", spec.display());
+        }
+
+        let spec = fs::read_to_string(spec)?;
+        let code = self.generate_from_str(&spec)?;
+        fs::write(output, code)?;
+        Ok(())
+    }
+
+    fn implement(&self, spec: &OpenAPI) -> Result<TokenStream> {
+        let mut models = Models::new(spec);
+        if let Some(components) = &spec.components {
+            for (name, schema) in &components.schemas {
+                models.check_component(name, schema)?;
+            }
+            for (name, schema) in &components.schemas {
+                models.implement_component(name, schema)?;
+            }
+        }
+
+        let mut methods = Vec::new();
+        for (path, item) in spec.paths.iter() {
+            let item = match item {
+                ReferenceOr::Item(item) => item,
+                ReferenceOr::Reference { reference } => {
+                    return Err(Error::Unsupported(format!("path reference {}", reference)));
+                }
+            };
+            for (http_method, operation) in item.iter() {
+                let method = implement_operation(
+                    &mut models,
+                    path,
+                    http_method,
+                    &item.parameters,
+                    operation,
+                    self.flavor,
+                )?;
+                methods.push(method);
+            }
+        }
+
+        let name = match &self.trait_name {
+            Some(name) => match checked_ident(name) {
+                Some(name) => name,
+                None => return Err(Error::Unsupported(format!("trait name {}", name))),
+            },
+            None => format_ident!("{}Api", type_name(&spec.info.title)),
+        };
+        let attr = match self.flavor {
+            Flavor::AsyncLocal => quote! { #[pretend::pretend(?Send)] },
+            Flavor::Async | Flavor::Blocking => quote! { #[pretend::pretend] },
+        };
+        let description = spec.info.description.as_ref().unwrap_or(&spec.info.title);
+        let doc = doc(Some(description));
+        let models = models.into_items();
+
+        Ok(quote! {
+            #doc
+            #attr
+            pub trait #name {
+                #(#methods)*
+            }
+
+            #(#models)*
+        })
+    }
+}
+
+impl Default for Generator {
+    fn default() -> Self {
+        Generator::new()
+    }
+}
+
+const HEADER: &str = "// Generated by pretend-generator. Do not edit.";
@@ -0,0 +1,52 @@
+use pretend_generator::{Flavor, Generator};
+use std::error::Error;
+use std::{env, fs, process};
+
+const USAGE: &str =
+    "Usage: pretend-generator [--async | --local | --blocking] [--trait-name NAME] SPEC [OUTPUT]";
+
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("error: {}", err);
+        let mut source = err.source();
+        while let Some(err) = source {
+            eprintln!("  caused by: {}", err);
+            source = err.source();
+        }
+        process::exit(1);
+    }
+}
+
+fn run() -> Result<(), Box<dyn Error>> {
+    let mut generator = Generator::new();
+    let mut files = Vec::new();
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--async" => generator = generator.with_flavor(Flavor::Async),
+            "--local" => generator = generator.with_flavor(Flavor::AsyncLocal),
+            "--blocking" => generator = generator.with_flavor(Flavor::Blocking),
+            "--trait-name" => {
+                let name = args.next().ok_or(USAGE)?;
+                generator = generator.with_trait_name(name);
+            }
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return Ok(());
+            }
+            _ if arg.starts_with('-') => return Err(USAGE.into()),
+            _ => files.push(arg),
+        }
+    }
+
+    match files.as_slice() {
+        [spec] => {
+            let spec = fs::read_to_string(spec)?;
+            print!("{}", generator.generate_from_str(&spec)?);
+        }
+        [spec, output] => generator.generate_file(spec, output)?,
+        _ => return Err(USAGE.into()),
+    }
+    Ok(())
+}
@@ -0,0 +1,402 @@
+use crate::names::{field_ident, field_name, type_ident, type_name};
+use crate::{Error, Result};
+use openapiv3::{
+    AdditionalProperties, IntegerFormat, NumberFormat, OpenAPI, ReferenceOr, Schema, SchemaKind,
+    StringType, Type, VariantOrUnknownOrEmpty,
+};
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote};
+use std::borrow::Borrow;
+use std::collections::HashSet;
+
+const SCHEMA_PREFIX: &str = "#/components/schemas/";
+
+pub(crate) type SchemaRef<'a> = ReferenceOr<&'a Schema>;
+
+pub(crate) struct Field<'a> {
+    pub(crate) name: &'a str,
+    pub(crate) schema: SchemaRef<'a>,
+    pub(crate) required: bool,
+    pub(crate) description: Option<&'a str>,
+}
+
+pub(crate) struct Models<'a> {
+    spec: &'a OpenAPI,
+    names: HashSet<String>,
+    items: Vec<TokenStream>,
+    // Component being generated, including its inline types
+    component: Option<String>,
+}
+
+impl<'a> Models<'a> {
+    pub(crate) fn new(spec: &'a OpenAPI) -> Self {
+        // Reserve the names of components, so that inline types don't use them
+        let names = spec.components.iter();
+        let names = names.flat_map(|components| components.schemas.keys());
+        let names = names.map(|name| type_ident(name).to_string()).collect();
+
+        Models {
+            spec,
+            names,
+            items: Vec::new(),
+            component: None,
+        }
+    }
+
+    pub(crate) fn spec(&self) -> &'a OpenAPI {
+        self.spec
+    }
+
+    pub(crate) fn into_items(self) -> Vec<TokenStream> {
+        self.items
+    }
+
+    // Type aliases that refer to themselves can't be generated
+    //
+    // Components are checked before being generated, as references
+    // can't be resolved when there are such cycles.
+    pub(crate) fn check_component(
+        &self,
+        name: &str,
+        schema: &'a ReferenceOr<Schema>,
+    ) -> Result<()> {
+        if self.aliases(as_schema_ref(schema), name, &mut HashSet::new()) {
+            Err(Error::Unsupported(format!("recursive type alias {}", name)))
+        } else {
+            Ok(())
+        }
+    }
+
+    pub(crate) fn implement_component(
+        &mut self,
+        name: &str,
+        schema: &'a ReferenceOr<Schema>,
+    ) -> Result<()> {
+        let ident = type_ident(name);
+        match schema {
+            ReferenceOr::Reference { reference } => {
+                let ty = reference_type(reference)?;
+                self.items.push(quote! {
+                    pub type #ident = #ty;
+                });
+                Ok(())
+            }
+            ReferenceOr::Item(schema) => {
+                self.component = Some(name.to_string());
+                let result = self.implement_schema(ident, schema);
+                self.component = None;
+                result
+            }
+        }
+    }
+
+    pub(crate) fn implement_struct(
+        &mut self,
+        ident: Ident,
+        description: Option<&str>,
+        fields: Vec<Field<'a>>,
+    ) -> Result<()> {
+        let doc = doc(description);
+        let fields = fields
+            .into_iter()
+            .map(|field| self.implement_field(&ident, field))
+            .collect::<Result<Vec<_>>>()?;
+
+        self.items.push(quote! {
+            #doc
+            #[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+            pub struct #ident {
+                #(#fields)*
+            }
+        });
+        Ok(())
+    }
+
+    // Resolve references to schemas in components
+    pub(crate) fn resolve(&self, schema: SchemaRef<'a>) -> Result<&'a Schema> {
+        match schema {
+            ReferenceOr::Item(schema) => Ok(schema),
+            ReferenceOr::Reference { reference } => match self.component_schema(&reference) {
+                Some((_, schema)) => self.resolve(as_schema_ref(schema)),
+                None => Err(Error::Unsupported(format!("reference {}", reference))),
+            },
+        }
+    }
+
+    // Type of a schema
+    //
+    // Inline objects and enums are generated as new types, named after `hint`.
+    pub(crate) fn type_of(&mut self, schema: SchemaRef<'a>, hint: &str) -> Result<TokenStream> {
+        match schema {
+            ReferenceOr::Reference { reference } => reference_type(&reference),
+            ReferenceOr::Item(schema) => self.schema_type(schema, hint),
+        }
+    }
+
+    fn implement_schema(&mut self, ident: Ident, schema: &'a Schema) -> Result<()> {
+        let description = schema.schema_data.description.as_deref();
+        match &schema.schema_kind {
+            SchemaKind::Type(Type::Object(object)) if !object.properties.is_empty() => {
+                let fields = object.properties.iter().map(|(name, schema)| Field {
+                    name,
+                    schema: as_schema_ref(schema),
+                    required: object.required.contains(name),
+                    description: None,
+                });
+                self.implement_struct(ident, description, fields.collect())
+            }
+            SchemaKind::Type(Type::String(string)) if is_enum(string) => {
+                self.implement_enum(ident, description, string);
+                Ok(())
+            }
+            _ => {
+                let doc = doc(description);
+                let ty = self.schema_type(schema, &ident.to_string())?;
+                self.items.push(quote! {
+                    #doc
+                    pub type #ident = #ty;
+                });
+                Ok(())
+            }
+        }
+    }
+
+    fn implement_enum(&mut self, ident: Ident, description: Option<&str>, string: &StringType) {
+        let doc = doc(description);
+        let mut names = HashSet::new();
+        let variants = string.enumeration.iter().flatten().map(|value| {
+            let variant = unique_name(&mut names, &type_name(value));
+            let variant = format_ident!("{}", variant);
+            quote! {
+                #[serde(rename = #value)]
+                #variant,
+            }
+        });
+        let variants = variants.collect::<Vec<_>>();
+
+        self.items.push(quote! {
+            #doc
+            #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, serde::Deserialize, serde::Serialize)]
+            pub enum #ident {
+                #(#variants)*
+            }
+        });
+    }
+
+    fn implement_field(&mut self, parent: &Ident, field: Field<'a>) -> Result<TokenStream> {
+        let name = field.name;
+        let ident = field_ident(name);
+        let hint = format!("{}{}", parent, type_name(name));
+        let nullable = self.resolve(field.schema.clone())?.schema_data.nullable;
+        let description = match &field.schema {
+            ReferenceOr::Item(schema) => schema.schema_data.description.as_deref(),
+            ReferenceOr::Reference { .. } => None,
+        };
+        let doc = doc(field.description.or(description));
+
+        let boxed = self.is_recursive(&field.schema);
+        let ty = self.type_of(field.schema, &hint)?;
+        let ty = if boxed {
+            quote! { Box<#ty> }
+        } else {
+            ty
+        };
+        let rename = if field_name(name) != name {
+            quote! { #[serde(rename = #name)] }
+        } else {
+            TokenStream::new()
+        };
+
+        let field = if field.required && !nullable {
+            quote! {
+                #doc
+                #rename
+                pub #ident: #ty,
+            }
+        } else {
+            quote! {
+                #doc
+                #rename
+                #[serde(default, skip_serializing_if = "Option::is_none")]
+                pub #ident: Option<#ty>,
+            }
+        };
+        Ok(field)
+    }
+
+    fn schema_type(&mut self, schema: &'a Schema, hint: &str) -> Result<TokenStream> {
+        let ty = match &schema.schema_kind {
+            SchemaKind::Type(Type::String(string)) => {
+                if is_enum(string) {
+                    let ident = self.inline_ident(hint);
+                    let description = schema.schema_data.description.as_deref();
+                    self.implement_enum(ident.clone(), description, string);
+                    quote! { #ident }
+                } else {
+                    quote! { String }
+                }
+            }
+            SchemaKind::Type(Type::Integer(integer)) => match integer.format {
+                VariantOrUnknownOrEmpty::Item(IntegerFormat::Int32) => quote! { i32 },
+                _ => quote! { i64 },
+            },
+            SchemaKind::Type(Type::Number(number)) => match number.format {
+                VariantOrUnknownOrEmpty::Item(NumberFormat::Float) => quote! { f32 },
+                _ => quote! { f64 },
+            },
+            SchemaKind::Type(Type::Boolean(_)) => quote! { bool },
+            SchemaKind::Type(Type::Array(array)) => match &array.items {
+                Some(items) => {
+                    let hint = format!("{}Item", hint);
+                    let ty = self.type_of(as_schema_ref(items), &hint)?;
+                    quote! { Vec<#ty> }
+                }
+                None => quote! { Vec<serde_json::Value> },
+            },
+            SchemaKind::Type(Type::Object(object)) => {
+                if !object.properties.is_empty() {
+                    let ident = self.inline_ident(hint);
+                    self.implement_schema(ident.clone(), schema)?;
+                    quote! { #ident }
+                } else if let Some(AdditionalProperties::Schema(values)) =
+                    &object.additional_properties
+                {
+                    let hint = format!("{}Value", hint);
+                    let ty = self.type_of(as_schema_ref(values), &hint)?;
+                    quote! { std::collections::HashMap<String, #ty> }
+                } else {
+                    quote! { std::collections::HashMap<String, serde_json::Value> }
+                }
+            }
+            _ => quote! { serde_json::Value },
+        };
+        Ok(ty)
+    }
+
+    // References to a type that contains the component being generated are
+    // boxed, as the component would otherwise have an infinite size
+    fn is_recursive(&self, schema: &SchemaRef<'a>) -> bool {
+        match (&self.component, schema) {
+            (Some(component), ReferenceOr::Reference { .. }) => {
+                self.contains(schema.clone(), component, &mut HashSet::new())
+            }
+            _ => false,
+        }
+    }
+
+    // Whether values of the schema contain a value of the component, directly
+    // or through the fields of other structs
+    fn contains(
+        &self,
+        schema: SchemaRef<'a>,
+        component: &str,
+        visited: &mut HashSet<&'a str>,
+    ) -> bool {
+        match schema {
+            ReferenceOr::Reference { reference } => match self.component_schema(&reference) {
+                Some((name, _)) if name == component => true,
+                Some((name, schema)) if visited.insert(name) => {
+                    self.contains(as_schema_ref(schema), component, visited)
+                }
+                _ => false,
+            },
+            ReferenceOr::Item(schema) => match &schema.schema_kind {
+                SchemaKind::Type(Type::Object(object)) => object
+                    .properties
+                    .values()
+                    .any(|schema| self.contains(as_schema_ref(schema), component, visited)),
+                _ => false,
+            },
+        }
+    }
+
+    // Whether the type alias generated for the schema refers to the component,
+    // directly or through other type aliases
+    fn aliases(
+        &self,
+        schema: SchemaRef<'a>,
+        component: &str,
+        visited: &mut HashSet<&'a str>,
+    ) -> bool {
+        match schema {
+            ReferenceOr::Reference { reference } => match self.component_schema(&reference) {
+                Some((name, _)) if name == component => true,
+                Some((name, schema)) if visited.insert(name) => {
+                    self.aliases(as_schema_ref(schema), component, visited)
+                }
+                _ => false,
+            },
+            ReferenceOr::Item(schema) => match &schema.schema_kind {
+                SchemaKind::Type(Type::Array(array)) => match &array.items {
+                    Some(items) => self.aliases(as_schema_ref(items), component, visited),
+                    None => false,
+                },
+                SchemaKind::Type(Type::Object(object)) if object.properties.is_empty() => {
+                    match &object.additional_properties {
+                        Some(AdditionalProperties::Schema(values)) => {
+                            self.aliases(as_schema_ref(values), component, visited)
+                        }
+                        _ => false,
+                    }
+                }
+                _ => false,
+            },
+        }
+    }
+
+    fn component_schema(&self, reference: &str) -> Option<(&'a str, &'a ReferenceOr<Schema>)> {
+        let name = reference.strip_prefix(SCHEMA_PREFIX)?;
+        let components = self.spec.components.as_ref()?;
+        let (name, schema) = components.schemas.get_key_value(name)?;
+        Some((name.as_str(), schema))
+    }
+
+    pub(crate) fn inline_ident(&mut self, hint: &str) -> Ident {
+        let name = unique_name(&mut self.names, &type_ident(hint).to_string());
+        format_ident!("{}", name)
+    }
+}
+
+pub(crate) fn as_schema_ref<T>(schema: &ReferenceOr<T>) -> SchemaRef<'_>
+where
+    T: Borrow<Schema>,
+{
+    match schema {
+        ReferenceOr::Reference { reference } => ReferenceOr::Reference {
+            reference: reference.clone(),
+        },
+        ReferenceOr::Item(schema) => ReferenceOr::Item(schema.borrow()),
+    }
+}
+
+pub(crate) fn doc(description: Option<&str>) -> TokenStream {
+    let lines = description.into_iter().flat_map(str::lines);
+    let lines = lines.map(|line| format!(" {}", line));
+    quote! {
+        #(#[doc = #lines])*
+    }
+}
+
+fn reference_type(reference: &str) -> Result<TokenStream> {
+    match reference.strip_prefix(SCHEMA_PREFIX) {
+        Some(name) => {
+            let ident = type_ident(name);
+            Ok(quote! { #ident })
+        }
+        None => Err(Error::Unsupported(format!("reference {}", reference))),
+    }
+}
+
+fn is_enum(string: &StringType) -> bool {
+    string.enumeration.iter().any(Option::is_some)
+}
+
+fn unique_name(names: &mut HashSet<String>, name: &str) -> String {
+    let mut unique = name.to_string();
+    let mut index = 1;
+    while !names.insert(unique.clone()) {
+        index += 1;
+        unique = format!("{}{}", name, index);
+    }
+    unique
+}
@@ -0,0 +1,110 @@
+use heck::{ToSnakeCase, ToUpperCamelCase};
+use proc_macro2::Ident;
+use quote::format_ident;
+
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "box", "break", "const", "continue", "crate", "dyn", "else", "enum",
+    "extern", "false", "final", "fn", "for", "if", "impl", "in", "let", "loop", "macro", "match",
+    "mod", "move", "mut", "override", "priv", "pub", "ref", "return", "self", "Self", "static",
+    "struct", "super", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use",
+    "virtual", "where", "while", "yield",
+];
+
+// Parameters with these names are interpreted by `pretend`
+const PRETEND_PARAMS: &[&str] = &["body", "form", "json", "query"];
+
+// Types of the prelude used by the generated code, that must not be shadowed
+const PRELUDE_TYPES: &[&str] = &["Box", "Option", "String", "Vec"];
+
+pub(crate) fn type_name(name: &str) -> String {
+    let name = name.to_upper_camel_case();
+    escape(name, "Value", "V")
+}
+
+pub(crate) fn type_ident(name: &str) -> Ident {
+    let name = type_name(name);
+    if PRELUDE_TYPES.contains(&name.as_str()) {
+        format_ident!("{}_", name)
+    } else {
+        format_ident!("{}", name)
+    }
+}
+
+pub(crate) fn field_name(name: &str) -> String {
+    let name = name.to_snake_case();
+    escape(name, "value", "_")
+}
+
+pub(crate) fn field_ident(name: &str) -> Ident {
+    format_ident!("{}", field_name(name))
+}
+
+// Returns `None` if the name can't be used as an identifier
+pub(crate) fn checked_ident(name: &str) -> Option<Ident> {
+    let name = escape(name.to_string(), "", "");
+    syn::parse_str::<Ident>(&name).ok()
+}
+
+pub(crate) fn param_ident(name: &str) -> Ident {
+    let name = field_name(name);
+    if PRETEND_PARAMS.contains(&name.as_str()) {
+        format_ident!("{}_", name)
+    } else {
+        format_ident!("{}", name)
+    }
+}
+
+fn escape(name: String, empty: &str, prefix: &str) -> String {
+    if name.is_empty() {
+        empty.to_string()
+    } else if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("{}{}", prefix, name)
+    } else if KEYWORDS.contains(&name.as_str()) {
+        format!("{}_", name)
+    } else {
+        name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_type_name() {
+        assert_eq!(type_name("pet_store"), "PetStore");
+        assert_eq!(type_name("Swagger Petstore"), "SwaggerPetstore");
+        assert_eq!(type_name("404"), "V404");
+        assert_eq!(type_name(""), "Value");
+    }
+
+    #[test]
+    fn test_type_ident() {
+        assert_eq!(type_ident("pet_store"), "PetStore");
+        assert_eq!(type_ident("vec"), "Vec_");
+        assert_eq!(type_ident("Option"), "Option_");
+    }
+
+    #[test]
+    fn test_field_name() {
+        assert_eq!(field_name("petId"), "pet_id");
+        assert_eq!(field_name("X-Request-ID"), "x_request_id");
+        assert_eq!(field_name("type"), "type_");
+        assert_eq!(field_name("2fa"), "_2fa");
+    }
+
+    #[test]
+    fn test_checked_ident() {
+        assert_eq!(checked_ident("PetStore").unwrap(), "PetStore");
+        assert_eq!(checked_ident("type").unwrap(), "type_");
+        assert!(checked_ident("my-api").is_none());
+        assert!(checked_ident("2fa").is_none());
+        assert!(checked_ident("").is_none());
+    }
+
+    #[test]
+    fn test_param_ident() {
+        assert_eq!(param_ident("petId"), "pet_id");
+        assert_eq!(param_ident("query"), "query_");
+    }
+}
@@ -0,0 +1,342 @@
+use crate::model::{as_schema_ref, doc, Field, Models};
+use crate::names::{field_name, param_ident};
+use crate::{Error, Flavor, Result};
+use indexmap::IndexMap;
+use openapiv3::{
+    Components, MediaType, Operation, Parameter, ParameterData, ParameterSchemaOrContent,
+    ReferenceOr, RequestBody, Schema, SchemaKind, StatusCode, Type,
+};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+
+const PARAMETER_PREFIX: &str = "#/components/parameters/";
+const REQUEST_BODY_PREFIX: &str = "#/components/requestBodies/";
+const RESPONSE_PREFIX: &str = "#/components/responses/";
+
+// Headers that are ignored when described as parameters
+const IGNORED_HEADERS: &[&str] = &["accept", "content-type", "authorization"];
+
+pub(crate) fn implement_operation<'a>(
+    models: &mut Models<'a>,
+    path: &str,
+    http_method: &str,
+    shared: &'a [ReferenceOr<Parameter>],
+    operation: &'a Operation,
+    flavor: Flavor,
+) -> Result<TokenStream> {
+    let name = match &operation.operation_id {
+        Some(operation_id) => field_name(operation_id),
+        None => field_name(&format!("{} {}", http_method, path)),
+    };
+    let ident = format_ident!("{}", name);
+    let description = operation_description(operation);
+    let doc = doc(description.as_deref());
+
+    let mut path = path.to_string();
+    let mut path_params = Vec::new();
+    let mut params = Vec::new();
+    let mut headers = Vec::new();
+    let mut query = Vec::new();
+
+    for parameter in parameters(models, shared, operation)? {
+        match parameter {
+            Parameter::Path { parameter_data, .. } => {
+                let ident = param_ident(&parameter_data.name);
+                let from = format!("{{{}}}", parameter_data.name);
+                let to = format!("{{{}}}", ident);
+                path = path.replace(&from, &to);
+                path_params.push(ident.to_string());
+
+                let ty = param_type(models, parameter_data, &name)?;
+                params.push(quote! { #ident: #ty });
+            }
+            Parameter::Header { parameter_data, .. } => {
+                // Header values are always formatted, so optional headers can't be described
+                let header = parameter_data.name.as_str();
+                let ignored = IGNORED_HEADERS.contains(&header.to_lowercase().as_str());
+                if ignored || !parameter_data.required {
+                    continue;
+                }
+
+                let ident = param_ident(header);
+                let value = format!("{{{}}}", ident);
+                headers.push(quote! {
+                    #[header(name = #header, value = #value)]
+                });
+
+                let ty = param_type(models, parameter_data, &name)?;
+                params.push(quote! { #ident: #ty });
+            }
+            Parameter::Query { parameter_data, .. } => {
+                let schema = parameter_schema(parameter_data, &name)?;
+                let resolved = models.resolve(as_schema_ref(schema))?;
+                if let SchemaKind::Type(Type::Array(_)) = resolved.schema_kind {
+                    let message = format!("array query parameter {}", parameter_data.name);
+                    return Err(Error::Unsupported(message));
+                }
+
+                query.push(Field {
+                    name: &parameter_data.name,
+                    schema: as_schema_ref(schema),
+                    required: parameter_data.required,
+                    description: parameter_data.description.as_deref(),
+                });
+            }
+            Parameter::Cookie { parameter_data, .. } => {
+                let message = format!("cookie parameter {}", parameter_data.name);
+                return Err(Error::Unsupported(message));
+            }
+        }
+    }
+
+    if has_undeclared_params(&path, &path_params) {
+        let message = format!("undeclared path parameter in {}", path);
+        return Err(Error::Unsupported(message));
+    }
+
+    if !query.is_empty() {
+        let ty = models.inline_ident(&format!("{}Query", name));
+        models.implement_struct(ty.clone(), None, query)?;
+        params.push(quote! { query: &#ty });
+    }
+
+    if let Some(body) = &operation.request_body {
+        let body = request_body(models.spec().components.as_ref(), body)?;
+        if let Some((body, header)) = implement_body(models, body, &name)? {
+            params.push(body);
+            headers.extend(header);
+        }
+    }
+
+    let response = implement_response(models, operation, &name)?;
+    let http_method = http_method.to_uppercase();
+    let asyncness = match flavor {
+        Flavor::Async | Flavor::AsyncLocal => quote! { async },
+        Flavor::Blocking => TokenStream::new(),
+    };
+
+    Ok(quote! {
+        #doc
+        #[request(method = #http_method, path = #path)]
+        #(#headers)*
+        #asyncness fn #ident(&self, #(#params),*) -> pretend::Result<#response>;
+    })
+}
+
+// Undeclared path parameters would be formatted with missing arguments
+fn has_undeclared_params(path: &str, params: &[String]) -> bool {
+    path.split('{').skip(1).any(|segment| {
+        let name = segment.split('}').next().unwrap_or_default();
+        !params.iter().any(|param| param == name)
+    })
+}
+
+fn operation_description(operation: &Operation) -> Option<String> {
+    match (&operation.summary, &operation.description) {
+        (Some(summary), Some(description)) => Some(format!("{}\n\n{}", summary, description)),
+        (Some(summary), None) => Some(summary.clone()),
+        (None, Some(description)) => Some(description.clone()),
+        (None, None) => None,
+    }
+}
+
+// Parameters of the operation override parameters shared by the path
+fn parameters<'a>(
+    models: &Models<'a>,
+    shared: &'a [ReferenceOr<Parameter>],
+    operation: &'a Operation,
+) -> Result<Vec<&'a Parameter>> {
+    let components = models.spec().components.as_ref();
+    let mut parameters: Vec<&'a Parameter> = Vec::new();
+    for parameter in shared.iter().chain(&operation.parameters) {
+        let parameter = resolve(components, parameter, PARAMETER_PREFIX, |c| &c.parameters)?;
+        let data = parameter.parameter_data_ref();
+        let position = parameters.iter().position(|existing| {
+            let existing_data = existing.parameter_data_ref();
+            existing_data.name == data.name && location(existing) == location(parameter)
+        });
+        match position {
+            Some(position) => parameters[position] = parameter,
+            None => parameters.push(parameter),
+        }
+    }
+    Ok(parameters)
+}
+
+fn location(parameter: &Parameter) -> &'static str {
+    match parameter {
+        Parameter::Query { .. } => "query",
+        Parameter::Header { .. } => "header",
+        Parameter::Path { .. } => "path",
+        Parameter::Cookie { .. } => "cookie",
+    }
+}
+
+fn parameter_schema<'a>(
+    parameter_data: &'a ParameterData,
+    operation: &str,
+) -> Result<&'a ReferenceOr<Schema>> {
+    match &parameter_data.format {
+        ParameterSchemaOrContent::Schema(schema) => Ok(schema),
+        ParameterSchemaOrContent::Content(_) => {
+            let message = format!(
+                "parameter {} of {} with content",
+                parameter_data.name, operation
+            );
+            Err(Error::Unsupported(message))
+        }
+    }
+}
+
+// Path and header parameters are formatted, and must implement `Display`
+fn param_type<'a>(
+    models: &mut Models<'a>,
+    parameter_data: &'a ParameterData,
+    operation: &str,
+) -> Result<TokenStream> {
+    let schema = parameter_schema(parameter_data, operation)?;
+    let resolved = models.resolve(as_schema_ref(schema))?;
+    match &resolved.schema_kind {
+        SchemaKind::Type(Type::String(_)) => Ok(quote! { &str }),
+        SchemaKind::Type(Type::Integer(_))
+        | SchemaKind::Type(Type::Number(_))
+        | SchemaKind::Type(Type::Boolean(_)) => {
+            models.type_of(ReferenceOr::Item(resolved), &parameter_data.name)
+        }
+        _ => {
+            let message = format!("parameter {} of {}", parameter_data.name, operation);
+            Err(Error::Unsupported(message))
+        }
+    }
+}
+
+fn implement_body<'a>(
+    models: &mut Models<'a>,
+    body: &'a RequestBody,
+    operation: &str,
+) -> Result<Option<(TokenStream, Option<TokenStream>)>> {
+    let json = body.content.iter().find(|(ty, _)| is_json(ty));
+    let form = body
+        .content
+        .get_key_value("application/x-www-form-urlencoded");
+    let hint = format!("{}Body", operation);
+
+    let body = if let Some((_, media)) = json {
+        let ty = media_type(models, media, &hint)?;
+        Some((quote! { json: &#ty }, None))
+    } else if let Some((_, media)) = form {
+        let ty = media_type(models, media, &hint)?;
+        Some((quote! { form: &#ty }, None))
+    } else if let Some((content_type, _)) = body.content.first() {
+        let header = if content_type.contains('*') {
+            None
+        } else {
+            Some(quote! {
+                #[header(name = "Content-Type", value = #content_type)]
+            })
+        };
+        Some((quote! { body: Vec<u8> }, header))
+    } else {
+        None
+    };
+    Ok(body)
+}
+
+fn implement_response<'a>(
+    models: &mut Models<'a>,
+    operation: &'a Operation,
+    name: &str,
+) -> Result<TokenStream> {
+    let components = models.spec().components.as_ref();
+    let responses = &operation.responses;
+    let success = responses.responses.iter().find(|(code, _)| match code {
+        StatusCode::Code(code) => (200..300).contains(code),
+        StatusCode::Range(range) => *range == 2,
+    });
+    let error = responses.responses.iter().find(|(code, _)| match code {
+        StatusCode::Code(code) => *code >= 400,
+        StatusCode::Range(range) => *range >= 4,
+    });
+
+    let (success, error) = match (success, error, &responses.default) {
+        (Some((_, success)), Some((_, error)), _) => (Some(success), Some(error)),
+        (Some((_, success)), None, default) => (Some(success), default.as_ref()),
+        (None, _, default) => (default.as_ref(), None),
+    };
+
+    let success = match success {
+        Some(success) => resolve(components, success, RESPONSE_PREFIX, |c| &c.responses)?,
+        None => return Ok(quote! { () }),
+    };
+    let error = match error {
+        Some(error) => Some(resolve(components, error, RESPONSE_PREFIX, |c| {
+            &c.responses
+        })?),
+        None => None,
+    };
+
+    let json = success.content.iter().find(|(ty, _)| is_json(ty));
+    let error = error.and_then(|error| error.content.iter().find(|(ty, _)| is_json(ty)));
+    let error = error.filter(|(_, media)| media.schema.is_some());
+
+    if let Some((_, media)) = json {
+        let ty = media_type(models, media, &format!("{}Response", name))?;
+        match error {
+            Some((_, error)) => {
+                let error = media_type(models, error, &format!("{}Error", name))?;
+                Ok(quote! { pretend::JsonResult<#ty, #error> })
+            }
+            None => Ok(quote! { pretend::Json<#ty> }),
+        }
+    } else if success.content.keys().any(|ty| ty.starts_with("text/")) {
+        Ok(quote! { String })
+    } else if success.content.is_empty() {
+        Ok(quote! { () })
+    } else {
+        Ok(quote! { Vec<u8> })
+    }
+}
+
+fn media_type<'a>(
+    models: &mut Models<'a>,
+    media: &'a MediaType,
+    hint: &str,
+) -> Result<TokenStream> {
+    match &media.schema {
+        Some(schema) => models.type_of(as_schema_ref(schema), hint),
+        None => Ok(quote! { serde_json::Value }),
+    }
+}
+
+fn request_body<'a>(
+    components: Option<&'a Components>,
+    body: &'a ReferenceOr<RequestBody>,
+) -> Result<&'a RequestBody> {
+    resolve(components, body, REQUEST_BODY_PREFIX, |c| &c.request_bodies)
+}
+
+fn resolve<'a, T, F>(
+    components: Option<&'a Components>,
+    item: &'a ReferenceOr<T>,
+    prefix: &str,
+    get: F,
+) -> Result<&'a T>
+where
+    F: Fn(&'a Components) -> &'a IndexMap<String, ReferenceOr<T>> + Copy,
+{
+    match item {
+        ReferenceOr::Item(item) => Ok(item),
+        ReferenceOr::Reference { reference } => {
+            let name = reference.strip_prefix(prefix);
+            let item = name.and_then(|name| get(components?).get(name));
+            match item {
+                Some(item) => resolve(components, item, prefix, get),
+                None => Err(Error::Unsupported(format!("reference {}", reference))),
+            }
+        }
+    }
+}
+
+fn is_json(content_type: &str) -> bool {
+    content_type == "application/json" || content_type.ends_with("+json")
+}
@@ -0,0 +1,201 @@
+use pretend_generator::{Error, Flavor, Generator};
+
+const PETSTORE: &str = include_str!("petstore.yaml");
+
+fn generate(generator: Generator) -> String {
+    generator.generate_from_str(PETSTORE).unwrap()
+}
+
+#[test]
+fn test_generate_trait() {
+    let code = generate(Generator::new());
+    assert!(code.contains("#[pretend::pretend]\npub trait PetStoreApi {"));
+    assert!(code.contains("#[request(method = \"GET\", path = \"/pets/{pet_id}\")]"));
+    assert!(
+        code.contains("async fn delete_pets_pet_id(&self, pet_id: i64) -> pretend::Result<()>;")
+    );
+    assert!(code.contains("#[header(name = \"X-Request-ID\", value = \"{x_request_id}\")]"));
+    assert!(code.contains("#[header(name = \"Content-Type\", value = \"image/png\")]"));
+    assert!(code.contains("query: &ListPetsQuery,"));
+    assert!(code.contains("json: &NewPet,"));
+    assert!(code.contains("form: &UpdateOwnerBody,"));
+    assert!(code.contains("body: Vec<u8>"));
+    assert!(code.contains("-> pretend::Result<pretend::Json<Vec<Pet>>>;"));
+    assert!(code.contains("-> pretend::Result<pretend::JsonResult<Pet, Error>>;"));
+    assert!(code.contains("-> pretend::Result<String>;"));
+}
+
+#[test]
+fn test_generate_models() {
+    let code = generate(Generator::new());
+    assert!(code.contains("pub struct Pet {"));
+    assert!(code.contains("    pub id: i64,"));
+    assert!(code.contains("    #[serde(rename = \"birthDate\")]"));
+    assert!(code.contains("    pub birth_date: Option<String>,"));
+    assert!(code.contains("    pub attributes: Option<std::collections::HashMap<String, String>>,"));
+    assert!(code.contains("pub enum PetStatus {"));
+    assert!(code.contains("    #[serde(rename = \"available\")]\n    Available,"));
+    assert!(code.contains("pub enum NewPetType {"));
+    assert!(code.contains("    pub type_: Option<NewPetType>,"));
+    assert!(code.contains("    pub parent: Option<Box<Category>>,"));
+    assert!(code.contains("    pub children: Option<Vec<Category>>,"));
+    assert!(code.contains("pub struct ListPetsQuery {"));
+    assert!(code.contains("pub struct UpdateOwnerResponse {"));
+}
+
+#[test]
+fn test_generate_flavors() {
+    let code = generate(Generator::new().with_flavor(Flavor::AsyncLocal));
+    assert!(code.contains("#[pretend::pretend(?Send)]"));
+    assert!(code.contains("async fn get_pet("));
+
+    let code = generate(Generator::new().with_flavor(Flavor::Blocking));
+    assert!(code.contains("#[pretend::pretend]"));
+    assert!(code.contains("fn get_pet("));
+    assert!(!code.contains("async fn"));
+}
+
+#[test]
+fn test_generate_trait_name() {
+    let code = generate(Generator::new().with_trait_name("Pets"));
+    assert!(code.contains("pub trait Pets {"));
+
+    let code = generate(Generator::new().with_trait_name("type"));
+    assert!(code.contains("pub trait type_ {"));
+
+    let result = Generator::new()
+        .with_trait_name("my-api")
+        .generate_from_str(PETSTORE);
+    match result {
+        Err(Error::Unsupported(message)) => assert_eq!(message, "trait name my-api"),
+        _ => panic!("Expected an unsupported error"),
+    }
+}
+
+#[test]
+fn test_generate_unsupported() {
+    let spec = r#"{
+        "openapi": "3.0.3",
+        "info": { "title": "Test", "version": "1.0" },
+        "paths": {
+            "/items": {
+                "get": {
+                    "parameters": [
+                        { "name": "session", "in": "cookie", "schema": { "type": "string" } }
+                    ],
+                    "responses": { "200": { "description": "Items" } }
+                }
+            }
+        }
+    }"#;
+
+    let result = Generator::new().generate_from_str(spec);
+    match result {
+        Err(Error::Unsupported(message)) => assert_eq!(message, "cookie parameter session"),
+        _ => panic!("Expected an unsupported error"),
+    }
+}
+
+fn generate_schemas(schemas: &str) -> pretend_generator::Result<String> {
+    let spec = format!(
+        r#"{{
+            "openapi": "3.0.3",
+            "info": {{ "title": "Test", "version": "1.0" }},
+            "paths": {{}},
+            "components": {{ "schemas": {} }}
+        }}"#,
+        schemas
+    );
+    Generator::new().generate_from_str(&spec)
+}
+
+#[test]
+fn test_generate_recursive_models() {
+    let schemas = r##"{
+        "Node": {
+            "type": "object",
+            "required": ["next"],
+            "properties": {
+                "next": { "$ref": "#/components/schemas/Link" },
+                "owner": {
+                    "type": "object",
+                    "properties": { "root": { "$ref": "#/components/schemas/Node" } }
+                }
+            }
+        },
+        "Link": { "$ref": "#/components/schemas/Node" }
+    }"##;
+    let code = generate_schemas(schemas).unwrap();
+    assert!(code.contains("    pub next: Box<Link>,"));
+    assert!(code.contains("    pub root: Option<Box<Node>>,"));
+    assert!(code.contains("pub type Link = Node;"));
+
+    let schemas = r##"{
+        "Node": { "$ref": "#/components/schemas/Alias" },
+        "Alias": { "$ref": "#/components/schemas/Node" }
+    }"##;
+    match generate_schemas(schemas) {
+        Err(Error::Unsupported(message)) => assert_eq!(message, "recursive type alias Node"),
+        _ => panic!("Expected an unsupported error"),
+    }
+
+    let schemas = r##"{
+        "Tree": { "type": "array", "items": { "$ref": "#/components/schemas/Tree" } }
+    }"##;
+    match generate_schemas(schemas) {
+        Err(Error::Unsupported(message)) => assert_eq!(message, "recursive type alias Tree"),
+        _ => panic!("Expected an unsupported error"),
+    }
+}
+
+#[test]
+fn test_generate_prelude_names() {
+    let schemas = r##"{
+        "Vec": {
+            "type": "object",
+            "properties": { "items": { "type": "array", "items": { "type": "string" } } }
+        },
+        "Items": { "$ref": "#/components/schemas/Vec" }
+    }"##;
+    let code = generate_schemas(schemas).unwrap();
+    assert!(code.contains("pub struct Vec_ {"));
+    assert!(code.contains("    pub items: Option<Vec<String>>,"));
+    assert!(code.contains("pub type Items = Vec_;"));
+}
+
+#[test]
+fn test_generate_optional_headers() {
+    let spec = r#"{
+        "openapi": "3.0.3",
+        "info": { "title": "Test", "version": "1.0" },
+        "paths": {
+            "/items": {
+                "get": {
+                    "operationId": "listItems",
+                    "parameters": [
+                        { "name": "X-Trace", "in": "header", "schema": { "type": "string" } },
+                        {
+                            "name": "X-Tenant",
+                            "in": "header",
+                            "required": true,
+                            "schema": { "type": "string" }
+                        }
+                    ],
+                    "responses": { "200": { "description": "Items" } }
+                }
+            }
+        }
+    }"#;
+
+    let code = Generator::new().generate_from_str(spec).unwrap();
+    assert!(!code.contains("X-Trace"));
+    assert!(!code.contains("x_trace"));
+    assert!(code.contains("#[header(name = \"X-Tenant\", value = \"{x_tenant}\")]"));
+    assert!(code.contains("async fn list_items(&self, x_tenant: &str) -> pretend::Result<()>;"));
+}
+
+#[test]
+fn test_generate_invalid() {
+    let result = Generator::new().generate_from_str("openapi: 3");
+    assert!(matches!(result, Err(Error::Parse(_))));
+}
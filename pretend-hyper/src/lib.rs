@@ -0,0 +1,209 @@
+//! `hyper` based `pretend` client
+//!
+//! This client wraps a `hyper` client, so connectors, connection pool and
+//! HTTP/2 settings are configured with `hyper` directly.
+//!
+//! ```rust
+//! use pretend_hyper::hyper::client::HttpConnector;
+//! use pretend_hyper::hyper::Client as HClient;
+//! use pretend_hyper::Client;
+//! use std::time::Duration;
+//!
+//! let mut builder = HClient::builder();
+//! builder
+//!     .pool_idle_timeout(Duration::from_secs(30))
+//!     .pool_max_idle_per_host(4)
+//!     .http2_only(true);
+//!
+//! let client = Client::with_builder(&builder, HttpConnector::new());
+//! ```
+//!
+//! Clients can also be built from a `pretend::config::ClientConfig`. Only the connect
+//! timeout and the pool size are supported. `hyper` never follows redirects, so only
+//! `RedirectPolicy::None` is accepted. Other options should be configured on the
+//! connector or with interceptors.
+//!
+//! # Unix domain sockets
+//!
+//! On Unix, [`Client::unix`] creates a client that sends every request to a Unix domain
+//! socket, using HTTP/1.1. The host of the URL is ignored when connecting, but is still
+//! sent in the `Host` header. This is useful to talk to local daemons, like the Docker
+//! Engine API.
+//!
+//! ```rust
+//! # #[cfg(unix)]
+//! # {
+//! use pretend::{Pretend, Url};
+//! use pretend_hyper::Client;
+//!
+//! let url = Url::parse("http://localhost").unwrap();
+//! let pretend = Pretend::for_client(Client::unix("/var/run/docker.sock")).with_url(url);
+//! # }
+//! ```
+//!
+//! # Blocking traits
+//!
+//! This client is asynchronous. Blocking traits can use it, including with Unix domain
+//! sockets, by wrapping it in `pretend::client::blocking::BlockingAdapter`. `hyper` needs
+//! a `tokio` runtime to run requests: enable the `tokio` feature of `pretend` and use
+//! `BlockingAdapter::with_tokio_runtime`, or supply a runtime with
+//! `BlockingAdapter::with_runtime`.
+
+#![warn(missing_docs)]
+#![forbid(unsafe_code)]
+
+#[cfg(unix)]
+mod unix;
+
+pub use hyper;
+#[cfg(unix)]
+pub use unix::{UnixConnector, UnixStream};
+
+use hyper::client::connect::Connect;
+use hyper::client::{Builder, HttpConnector};
+use hyper::{Body, Client as HClient, Request};
+use pretend::client::{async_trait, Bytes, Client as PClient, Method};
+use pretend::config::{ClientConfig, RedirectPolicy, UnsupportedOption};
+use pretend::{Error, HeaderMap, Response, Result, Url};
+use std::mem;
+
+/// `hyper` based `pretend` client
+#[derive(Clone, Debug)]
+pub struct Client<C = HttpConnector> {
+    client: HClient<C, Body>,
+}
+
+impl Default for Client {
+    fn default() -> Self {
+        Client::new()
+    }
+}
+
+impl Client {
+    /// Constructor
+    ///
+    /// This constructor creates a client implementation
+    /// for `pretend` using a default `hyper` client.
+    pub fn new() -> Self {
+        Client {
+            client: HClient::new(),
+        }
+    }
+
+    /// Constructor with a configuration
+    ///
+    /// This constructor creates a client implementation
+    /// for `pretend` using a `hyper` client built from
+    /// the supplied configuration.
+    pub fn from_config(config: &ClientConfig) -> Result<Self> {
+        check_config(config)?;
+
+        let mut connector = HttpConnector::new();
+        connector.set_connect_timeout(config.connect_timeout());
+
+        let mut builder = HClient::builder();
+        if let Some(size) = config.pool_size() {
+            builder.pool_max_idle_per_host(size);
+        }
+        Ok(Client::with_builder(&builder, connector))
+    }
+}
+
+fn check_config(config: &ClientConfig) -> Result<()> {
+    if config.timeout().is_some() {
+        return Err(Error::client(UnsupportedOption::new("timeout")));
+    }
+    if config.proxy().is_some() {
+        return Err(Error::client(UnsupportedOption::new("proxy")));
+    }
+    if !config.root_certificates().is_empty() {
+        return Err(Error::client(UnsupportedOption::new("root_certificates")));
+    }
+    if config.identity().is_some() {
+        return Err(Error::client(UnsupportedOption::new("identity")));
+    }
+    if let Some(RedirectPolicy::Limit(_)) = config.redirect_policy() {
+        return Err(Error::client(UnsupportedOption::new("redirect_policy")));
+    }
+    if config.user_agent().is_some() {
+        return Err(Error::client(UnsupportedOption::new("user_agent")));
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+impl Client<UnixConnector> {
+    /// Constructor for Unix domain sockets
+    ///
+    /// This constructor creates a client implementation
+    /// for `pretend` sending requests to the supplied socket.
+    ///
+    /// The host of requested URLs is ignored when connecting,
+    /// and is only sent in the `Host` header. See the crate
+    /// level documentation for blocking traits.
+    pub fn unix<P>(path: P) -> Self
+    where
+        P: Into<std::path::PathBuf>,
+    {
+        Client {
+            client: HClient::builder().build(UnixConnector::new(path)),
+        }
+    }
+}
+
+impl<C> Client<C> {
+    /// Constructor with custom client
+    ///
+    /// This constructor creates a client implementation
+    /// for `pretend` wrapping the supplied `hyper` client.
+    pub fn with_client(client: HClient<C, Body>) -> Self {
+        Client { client }
+    }
+
+    /// Constructor with a builder and a connector
+    ///
+    /// This constructor creates a client implementation
+    /// for `pretend` using a `hyper` client built with the
+    /// supplied builder and connector.
+    pub fn with_builder(builder: &Builder, connector: C) -> Self
+    where
+        C: Connect + Clone,
+    {
+        Client {
+            client: builder.build(connector),
+        }
+    }
+}
+
+#[async_trait]
+impl<C> PClient for Client<C>
+where
+    C: Connect + Clone + Send + Sync + 'static,
+{
+    async fn execute(
+        &self,
+        method: Method,
+        url: Url,
+        headers: HeaderMap,
+        body: Option<Bytes>,
+    ) -> Result<Response<Bytes>> {
+        let mut builder = Request::builder().method(method).uri(url.as_str());
+        if let Some(request_headers) = builder.headers_mut() {
+            *request_headers = headers;
+        }
+
+        let body = body.map(Body::from).unwrap_or_else(Body::empty);
+        let request = builder.body(body).map_err(Error::request)?;
+
+        let response = self.client.request(request).await;
+        let mut response = response.map_err(Error::response)?;
+
+        let status = response.status();
+        let headers = mem::take(response.headers_mut());
+
+        let bytes = hyper::body::to_bytes(response.into_body()).await;
+        let bytes = bytes.map_err(Error::body)?;
+
+        Ok(Response::new(status, headers, bytes).with_url(url))
+    }
+}
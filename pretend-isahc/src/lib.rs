@@ -1,13 +1,21 @@
 //! `isahc` based `pretend` client
+//!
+//! Clients can be built from a [`ClientConfig`]. `isahc` only supports a single
+//! root certificate, stored in a file.
 
 #![warn(missing_docs)]
 #![forbid(unsafe_code)]
 
 pub use isahc;
 
-use isahc::http::Request;
-use isahc::{AsyncBody, AsyncReadResponseExt, HttpClient};
+use isahc::auth::{Authentication, Credentials};
+use isahc::config::{CaCertificate, ClientCertificate, Configurable, PrivateKey, RedirectPolicy};
+use isahc::http::header::USER_AGENT;
+use isahc::http::{Request, Uri};
+use isahc::{AsyncBody, AsyncReadResponseExt, HttpClient, HttpClientBuilder, ResponseExt};
 use pretend::client::{async_trait, Bytes, Client as PClient, Method};
+use pretend::config::UnsupportedOption;
+use pretend::config::{Certificate, ClientConfig, Identity, RedirectPolicy as PRedirectPolicy};
 use pretend::{Error, HeaderMap, Response, Result, Url};
 use std::mem;
 
@@ -34,6 +42,72 @@ impl Client {
         let client = HttpClient::new().map_err(Error::client)?;
         Ok(Client { client })
     }
+
+    /// Constructor with a configuration
+    ///
+    /// This constructor creates a client implementation
+    /// for `pretend` using an `isahc` client built from
+    /// the supplied configuration.
+    pub fn from_config(config: &ClientConfig) -> Result<Self> {
+        let builder = configure_builder(HttpClient::builder(), config)?;
+        let client = builder.build().map_err(Error::client)?;
+        Ok(Client { client })
+    }
+}
+
+fn configure_builder(
+    mut builder: HttpClientBuilder,
+    config: &ClientConfig,
+) -> Result<HttpClientBuilder> {
+    if let Some(timeout) = config.connect_timeout() {
+        builder = builder.connect_timeout(timeout);
+    }
+    if let Some(timeout) = config.timeout() {
+        builder = builder.timeout(timeout);
+    }
+    if let Some(proxy) = config.proxy() {
+        let uri = proxy.url().as_str().parse::<Uri>();
+        builder = builder.proxy(uri.map_err(Error::client)?);
+        if let Some((username, password)) = proxy.basic_auth() {
+            builder = builder
+                .proxy_authentication(Authentication::basic())
+                .proxy_credentials(Credentials::new(username, password));
+        }
+    }
+    match config.root_certificates() {
+        [] => {}
+        [Certificate::PemFile(path)] => {
+            builder = builder.ssl_ca_certificate(CaCertificate::file(path));
+        }
+        _ => return Err(Error::client(UnsupportedOption::new("root_certificates"))),
+    }
+    if let Some(identity) = config.identity() {
+        let certificate = match identity {
+            Identity::Pem(pem) => {
+                let key = PrivateKey::pem(pem.clone(), None);
+                ClientCertificate::pem(pem.clone(), key)
+            }
+            Identity::Pkcs12 { der, password } => {
+                ClientCertificate::pkcs12(der.clone(), password.clone())
+            }
+            _ => return Err(Error::client(UnsupportedOption::new("identity"))),
+        };
+        builder = builder.ssl_client_certificate(certificate);
+    }
+    if let Some(policy) = config.redirect_policy() {
+        let policy = match policy {
+            PRedirectPolicy::None => RedirectPolicy::None,
+            PRedirectPolicy::Limit(limit) => RedirectPolicy::Limit(limit as u32),
+        };
+        builder = builder.redirect_policy(policy);
+    }
+    if let Some(user_agent) = config.user_agent() {
+        builder = builder.default_header(USER_AGENT, user_agent);
+    }
+    if let Some(size) = config.pool_size() {
+        builder = builder.max_connections_per_host(size);
+    }
+    Ok(builder)
 }
 
 #[async_trait]
@@ -63,9 +137,19 @@ impl PClient for Client {
 
         let status = mem::take(response.status_mut());
         let headers = mem::take(response.headers_mut());
+        let url = response
+            .effective_uri()
+            .map(|uri| Url::parse(&uri.to_string()));
+        let url = url.transpose().map_err(Error::response)?;
+
         let mut body = Vec::new();
         let result = response.copy_to(&mut body).await;
         result.map_err(Error::body)?;
-        Ok(Response::new(status, headers, Bytes::from(body)))
+
+        let response = Response::new(status, headers, Bytes::from(body));
+        match url {
+            Some(url) => Ok(response.with_url(url)),
+            None => Ok(response),
+        }
     }
 }
@@ -1,4 +1,5 @@
 use pretend::client::{BlockingClient as PBlockingClient, Bytes, Method};
+use pretend::config::ClientConfig;
 use pretend::{Error, HeaderMap, Response as PResponse, Result, Url};
 use reqwest::blocking::Client;
 use std::mem;
@@ -17,6 +18,17 @@ impl BlockingClient {
     pub fn new(client: Client) -> Self {
         BlockingClient { client }
     }
+
+    /// Constructor with a configuration
+    ///
+    /// This constructor creates a client implementation
+    /// for `pretend` using a `reqwest` client built from
+    /// the supplied configuration.
+    pub fn from_config(config: &ClientConfig) -> Result<Self> {
+        let builder = configure_builder!(Client::builder(), config);
+        let client = builder.build().map_err(Error::client)?;
+        Ok(BlockingClient { client })
+    }
 }
 
 impl PBlockingClient for BlockingClient {
@@ -36,10 +48,11 @@ impl PBlockingClient for BlockingClient {
 
         let status = response.status();
         let headers = mem::take(response.headers_mut());
+        let url = response.url().clone();
 
         let bytes = response.bytes();
         let bytes = bytes.map_err(Error::body)?;
 
-        Ok(PResponse::new(status, headers, bytes))
+        Ok(PResponse::new(status, headers, bytes).with_url(url))
     }
 }
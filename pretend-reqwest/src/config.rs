@@ -0,0 +1,91 @@
+use pretend::config::{ClientConfig, Proxy, RedirectPolicy, UnsupportedOption};
+use pretend::{Error, Result};
+use reqwest::redirect::Policy;
+
+// Async and blocking builders share the same API, but not the same type
+macro_rules! configure_builder {
+    ($builder:expr, $config:expr) => {{
+        let config: &pretend::config::ClientConfig = $config;
+        let mut builder = $builder;
+        if let Some(timeout) = config.connect_timeout() {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(timeout) = config.timeout() {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(proxy) = config.proxy() {
+            builder = builder.proxy($crate::config::proxy(proxy)?);
+        }
+        #[cfg(any(feature = "native-tls", feature = "rustls-tls"))]
+        for certificate in $crate::config::root_certificates(config)? {
+            builder = builder.add_root_certificate(certificate);
+        }
+        #[cfg(any(feature = "native-tls", feature = "rustls-tls"))]
+        if let Some(identity) = config.identity() {
+            builder = builder.identity($crate::config::identity(identity)?);
+        }
+        #[cfg(not(any(feature = "native-tls", feature = "rustls-tls")))]
+        $crate::config::check_no_tls(config)?;
+        if let Some(policy) = config.redirect_policy() {
+            builder = builder.redirect($crate::config::redirect_policy(policy));
+        }
+        if let Some(user_agent) = config.user_agent() {
+            builder = builder.user_agent(user_agent);
+        }
+        if let Some(size) = config.pool_size() {
+            builder = builder.pool_max_idle_per_host(size);
+        }
+        builder
+    }};
+}
+
+pub(crate) fn proxy(proxy: &Proxy) -> Result<reqwest::Proxy> {
+    let result = reqwest::Proxy::all(proxy.url().as_str());
+    let mut result = result.map_err(Error::client)?;
+    if let Some((username, password)) = proxy.basic_auth() {
+        result = result.basic_auth(username, password);
+    }
+    Ok(result)
+}
+
+#[cfg(any(feature = "native-tls", feature = "rustls-tls"))]
+pub(crate) fn root_certificates(config: &ClientConfig) -> Result<Vec<reqwest::Certificate>> {
+    let certificates = config.root_certificates().iter().map(|certificate| {
+        let pem = certificate.pem().map_err(Error::client)?;
+        reqwest::Certificate::from_pem(&pem).map_err(Error::client)
+    });
+    certificates.collect()
+}
+
+#[cfg(any(feature = "native-tls", feature = "rustls-tls"))]
+pub(crate) fn identity(identity: &pretend::config::Identity) -> Result<reqwest::Identity> {
+    use pretend::config::Identity;
+
+    match identity {
+        #[cfg(feature = "native-tls")]
+        Identity::Pkcs12 { der, password } => {
+            reqwest::Identity::from_pkcs12_der(der, password).map_err(Error::client)
+        }
+        #[cfg(feature = "rustls-tls")]
+        Identity::Pem(pem) => reqwest::Identity::from_pem(pem).map_err(Error::client),
+        _ => Err(Error::client(UnsupportedOption::new("identity"))),
+    }
+}
+
+#[cfg(not(any(feature = "native-tls", feature = "rustls-tls")))]
+pub(crate) fn check_no_tls(config: &ClientConfig) -> Result<()> {
+    if !config.root_certificates().is_empty() {
+        Err(Error::client(UnsupportedOption::new("root_certificates")))
+    } else if config.identity().is_some() {
+        Err(Error::client(UnsupportedOption::new("identity")))
+    } else {
+        Ok(())
+    }
+}
+
+pub(crate) fn redirect_policy(policy: RedirectPolicy) -> Policy {
+    match policy {
+        RedirectPolicy::None => Policy::none(),
+        RedirectPolicy::Limit(limit) => Policy::limited(limit),
+    }
+}
@@ -1,10 +1,26 @@
 //! `reqwest` based `pretend` client
 //!
 //! Feature `blocking` enables the blocking client.
+//!
+//! Clients can be built from a [`ClientConfig`]. Root certificates and identities
+//! are only supported when the `native-tls` (default) or `rustls-tls` feature
+//! is enabled. PKCS #12 identities need `native-tls`, while PEM identities need
+//! `rustls-tls`.
+//!
+//! ```rust
+//! use pretend::config::ClientConfig;
+//! use pretend_reqwest::Client;
+//! use std::time::Duration;
+//!
+//! let config = ClientConfig::new().with_timeout(Duration::from_secs(30));
+//! let client = Client::from_config(&config).unwrap();
+//! ```
 
 #![warn(missing_docs)]
 #![forbid(unsafe_code)]
 
+#[macro_use]
+mod config;
 #[cfg(feature = "blocking")]
 mod blocking;
 
@@ -14,6 +30,7 @@ pub use reqwest;
 pub use blocking::*;
 
 use pretend::client::{async_trait, Bytes, Client as PClient, Method};
+use pretend::config::ClientConfig;
 use pretend::{Error, HeaderMap, Response as PResponse, Result, Url};
 use reqwest::Client as RClient;
 use std::mem;
@@ -32,6 +49,17 @@ impl Client {
     pub fn new(client: RClient) -> Self {
         Client { client }
     }
+
+    /// Constructor with a configuration
+    ///
+    /// This constructor creates a client implementation
+    /// for `pretend` using a `reqwest` client built from
+    /// the supplied configuration.
+    pub fn from_config(config: &ClientConfig) -> Result<Self> {
+        let builder = configure_builder!(RClient::builder(), config);
+        let client = builder.build().map_err(Error::client)?;
+        Ok(Client { client })
+    }
 }
 
 #[async_trait]
@@ -52,10 +80,11 @@ impl PClient for Client {
 
         let status = response.status();
         let headers = mem::take(response.headers_mut());
+        let url = response.url().clone();
 
         let bytes = response.bytes().await;
         let bytes = bytes.map_err(Error::body)?;
 
-        Ok(PResponse::new(status, headers, bytes))
+        Ok(PResponse::new(status, headers, bytes).with_url(url))
     }
 }
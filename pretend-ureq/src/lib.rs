@@ -1,4 +1,8 @@
 //! `ureq` based `pretend` client
+//!
+//! Clients can be built from a [`ClientConfig`]. Root certificates and
+//! identities are not supported, and should be configured on a `ureq`
+//! agent directly.
 
 #![warn(missing_docs)]
 #![forbid(unsafe_code)]
@@ -6,12 +10,13 @@
 pub use ureq;
 
 use pretend::client::{BlockingClient, Bytes, Method};
+use pretend::config::{ClientConfig, RedirectPolicy, UnsupportedOption};
 use pretend::http::header::HeaderName;
 use pretend::http::HeaderValue;
 use pretend::{Error, HeaderMap, Response as PResponse, Result, StatusCode, Url};
 use std::convert::TryFrom;
 use std::io::Read;
-use ureq::Agent;
+use ureq::{Agent, AgentBuilder, Proxy};
 
 /// `ureq` based `pretend` client
 pub struct Client {
@@ -26,6 +31,59 @@ impl Client {
     pub fn new(agent: Agent) -> Self {
         Client { agent }
     }
+
+    /// Constructor with a configuration
+    ///
+    /// This constructor creates a client implementation
+    /// for `pretend` using a `ureq` agent built from
+    /// the supplied configuration.
+    pub fn from_config(config: &ClientConfig) -> Result<Self> {
+        let builder = configure_builder(AgentBuilder::new(), config)?;
+        Ok(Client {
+            agent: builder.build(),
+        })
+    }
+}
+
+fn configure_builder(mut builder: AgentBuilder, config: &ClientConfig) -> Result<AgentBuilder> {
+    if let Some(timeout) = config.connect_timeout() {
+        builder = builder.timeout_connect(timeout);
+    }
+    if let Some(timeout) = config.timeout() {
+        builder = builder.timeout(timeout);
+    }
+    if let Some(proxy) = config.proxy() {
+        // Credentials are passed in the proxy URL
+        let mut url = proxy.url().clone();
+        if let Some((username, password)) = proxy.basic_auth() {
+            let result = url
+                .set_username(username)
+                .and_then(|_| url.set_password(Some(password)));
+            result.map_err(|_| Error::client(UnsupportedOption::new("proxy")))?;
+        }
+        let proxy = Proxy::new(url.as_str());
+        builder = builder.proxy(proxy.map_err(|err| Error::Client(Box::new(err)))?);
+    }
+    if !config.root_certificates().is_empty() {
+        return Err(Error::client(UnsupportedOption::new("root_certificates")));
+    }
+    if config.identity().is_some() {
+        return Err(Error::client(UnsupportedOption::new("identity")));
+    }
+    if let Some(policy) = config.redirect_policy() {
+        let redirects = match policy {
+            RedirectPolicy::None => 0,
+            RedirectPolicy::Limit(limit) => limit as u32,
+        };
+        builder = builder.redirects(redirects);
+    }
+    if let Some(user_agent) = config.user_agent() {
+        builder = builder.user_agent(user_agent);
+    }
+    if let Some(size) = config.pool_size() {
+        builder = builder.max_idle_connections_per_host(size);
+    }
+    Ok(builder)
 }
 
 impl BlockingClient for Client {
@@ -55,6 +113,9 @@ impl BlockingClient for Client {
         let status = StatusCode::from_u16(response.status());
         let status = status.map_err(|err| Error::Response(Box::new(err)))?;
 
+        let url = Url::parse(response.get_url());
+        let url = url.map_err(|err| Error::Response(Box::new(err)))?;
+
         let mut headers = HeaderMap::new();
         for name in response.headers_names() {
             let values = response.all(&name);
@@ -76,6 +137,6 @@ impl BlockingClient for Client {
             .read_to_end(&mut body)
             .map_err(|err| Error::Response(Box::new(err)))?;
 
-        Ok(PResponse::new(status, headers, Bytes::from(body)))
+        Ok(PResponse::new(status, headers, Bytes::from(body)).with_url(url))
     }
 }
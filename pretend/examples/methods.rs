@@ -34,9 +34,9 @@ async fn main() {
     let post = pretend.post().await.unwrap();
     println!("{}", post);
 
-    let put = pretend.post().await.unwrap();
+    let put = pretend.put().await.unwrap();
     println!("{}", put);
 
-    let delete = pretend.post().await.unwrap();
+    let delete = pretend.delete().await.unwrap();
     println!("{}", delete);
 }
@@ -7,6 +7,7 @@ use serde::Deserialize;
 
 #[derive(Clone, Debug, Deserialize)]
 struct Contributor {
+    #[allow(dead_code)]
     login: String,
 }
 
@@ -14,6 +15,7 @@ type Contributors = Vec<Contributor>;
 
 #[derive(Clone, Debug, Deserialize)]
 struct GithubError {
+    #[allow(dead_code)]
     message: String,
 }
 
@@ -14,15 +14,23 @@
 //! via `execute`. This method takes a method, url, header and body (as raw bytes) and should
 //! return a response with raw bytes as body.
 //!
+//! Clients also receive the [`Endpoint`] being called via `execute_with_endpoint`. By default,
+//! the endpoint is ignored and `execute` is called. Clients can override this method to use
+//! this metadata, for example to record metrics per endpoint.
+//!
+//! Async clients can be used as blocking clients with [`blocking::BlockingAdapter`].
+//!
 //! Since this crate uses `async_trait` to support futures in trait, `Client`
 //! implementations should be marked with `#[client::async_trait]` and
 //! `LocalClient` should use `#[client::async_trait(?Send)]`.
 
+pub mod blocking;
+
 pub use async_trait::async_trait;
 pub use bytes::Bytes;
 pub use http::Method;
 
-use crate::{HeaderMap, Response, Result, Url};
+use crate::{Endpoint, HeaderMap, Response, Result, Url};
 
 /// `pretend` client
 ///
@@ -37,6 +45,26 @@ pub trait Client {
         headers: HeaderMap,
         body: Option<Bytes>,
     ) -> Result<Response<Bytes>>;
+
+    /// Execute a request for an endpoint
+    ///
+    /// This method is called by `pretend` with the endpoint being called.
+    /// The default implementation ignores the endpoint.
+    ///
+    /// As for any default `async fn` with `async_trait`, the default
+    /// implementation requires `Self: Sync`. `pretend` requires `Sync`
+    /// clients for this reason.
+    async fn execute_with_endpoint(
+        &self,
+        endpoint: &Endpoint,
+        method: Method,
+        url: Url,
+        headers: HeaderMap,
+        body: Option<Bytes>,
+    ) -> Result<Response<Bytes>> {
+        let _ = endpoint;
+        self.execute(method, url, headers, body).await
+    }
 }
 
 /// `pretend` local client
@@ -52,6 +80,22 @@ pub trait LocalClient {
         headers: HeaderMap,
         body: Option<Bytes>,
     ) -> Result<Response<Bytes>>;
+
+    /// Execute a request for an endpoint
+    ///
+    /// This method is called by `pretend` with the endpoint being called.
+    /// The default implementation ignores the endpoint.
+    async fn execute_with_endpoint(
+        &self,
+        endpoint: &Endpoint,
+        method: Method,
+        url: Url,
+        headers: HeaderMap,
+        body: Option<Bytes>,
+    ) -> Result<Response<Bytes>> {
+        let _ = endpoint;
+        self.execute(method, url, headers, body).await
+    }
 }
 
 /// `pretend` blocking client
@@ -66,6 +110,22 @@ pub trait BlockingClient {
         headers: HeaderMap,
         body: Option<Bytes>,
     ) -> Result<Response<Bytes>>;
+
+    /// Execute a request for an endpoint
+    ///
+    /// This method is called by `pretend` with the endpoint being called.
+    /// The default implementation ignores the endpoint.
+    fn execute_with_endpoint(
+        &self,
+        endpoint: &Endpoint,
+        method: Method,
+        url: Url,
+        headers: HeaderMap,
+        body: Option<Bytes>,
+    ) -> Result<Response<Bytes>> {
+        let _ = endpoint;
+        self.execute(method, url, headers, body)
+    }
 }
 
 /// `pretend` local client
@@ -74,7 +134,7 @@ pub trait BlockingClient {
 #[async_trait(?Send)]
 impl<C> LocalClient for C
 where
-    C: Client,
+    C: Client + Sync,
 {
     async fn execute(
         &self,
@@ -85,4 +145,15 @@ where
     ) -> Result<Response<Bytes>> {
         Client::execute(self, method, url, headers, body).await
     }
+
+    async fn execute_with_endpoint(
+        &self,
+        endpoint: &Endpoint,
+        method: Method,
+        url: Url,
+        headers: HeaderMap,
+        body: Option<Bytes>,
+    ) -> Result<Response<Bytes>> {
+        Client::execute_with_endpoint(self, endpoint, method, url, headers, body).await
+    }
 }
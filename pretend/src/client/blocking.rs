@@ -0,0 +1,184 @@
+//! Blocking adapter for async clients
+//!
+//! [`BlockingAdapter`] wraps an async client, either a [`Client`](super::Client) or a
+//! [`LocalClient`], and implements [`BlockingClient`] by driving requests
+//! to completion on a runtime. This allows the same configured async client
+//! to be used with async and blocking `pretend` traits.
+//!
+//! Runtimes implement [`BlockOn`]. By default, [`CurrentThread`] runs the
+//! requests on the calling thread, which works for clients that do not need
+//! a specific runtime, like `isahc`. Clients based on `tokio`, like `reqwest`
+//! or `hyper`, need a `tokio` runtime to be supplied. When the `tokio` feature
+//! is enabled, `BlockOn` is implemented for `tokio` runtimes.
+//!
+//! ```rust
+//! use pretend::client::blocking::BlockingAdapter;
+//! use pretend::{pretend, Pretend, Result, Url};
+//! use pretend_isahc::Client;
+//!
+//! #[pretend]
+//! trait HttpBin {
+//!     #[request(method = "GET", path = "/get")]
+//!     fn get(&self) -> Result<String>;
+//! }
+//!
+//! let client = BlockingAdapter::new(Client::new().unwrap());
+//! let url = Url::parse("https://httpbin.org").unwrap();
+//! let pretend = Pretend::for_client(client).with_url(url);
+//! ```
+//!
+//! Blocking on a runtime from a task running on that runtime is
+//! not supported, and usually panics or deadlocks.
+
+use super::{BlockingClient, Bytes, LocalClient, Method};
+use crate::{Endpoint, HeaderMap, Response, Result, Url};
+use std::future::Future;
+use std::sync::Arc;
+
+/// Describe a runtime that can block on futures
+///
+/// See module level documentation for more information.
+pub trait BlockOn {
+    /// Run a future to completion, blocking the current thread
+    fn block_on<F>(&self, future: F) -> F::Output
+    where
+        F: Future;
+}
+
+impl<B> BlockOn for &B
+where
+    B: BlockOn + ?Sized,
+{
+    fn block_on<F>(&self, future: F) -> F::Output
+    where
+        F: Future,
+    {
+        (**self).block_on(future)
+    }
+}
+
+impl<B> BlockOn for Arc<B>
+where
+    B: BlockOn + ?Sized,
+{
+    fn block_on<F>(&self, future: F) -> F::Output
+    where
+        F: Future,
+    {
+        (**self).block_on(future)
+    }
+}
+
+/// Runtime running futures on the calling thread
+///
+/// This runtime does not provide any reactor or timer.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CurrentThread;
+
+impl BlockOn for CurrentThread {
+    fn block_on<F>(&self, future: F) -> F::Output
+    where
+        F: Future,
+    {
+        futures_executor::block_on(future)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl BlockOn for tokio::runtime::Runtime {
+    fn block_on<F>(&self, future: F) -> F::Output
+    where
+        F: Future,
+    {
+        tokio::runtime::Runtime::block_on(self, future)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl BlockOn for tokio::runtime::Handle {
+    fn block_on<F>(&self, future: F) -> F::Output
+    where
+        F: Future,
+    {
+        tokio::runtime::Handle::block_on(self, future)
+    }
+}
+
+/// Blocking client wrapping an async client
+///
+/// See module level documentation for more information.
+#[derive(Clone, Debug)]
+pub struct BlockingAdapter<C, B = CurrentThread> {
+    client: C,
+    runtime: B,
+}
+
+impl<C> BlockingAdapter<C> {
+    /// Constructor
+    ///
+    /// Requests are executed on the calling thread.
+    pub fn new(client: C) -> Self {
+        BlockingAdapter::with_runtime(client, CurrentThread)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<C> BlockingAdapter<C, tokio::runtime::Runtime> {
+    /// Constructor with a dedicated `tokio` runtime
+    ///
+    /// Requests are executed on a new single-threaded `tokio` runtime.
+    pub fn with_tokio_runtime(client: C) -> std::io::Result<Self> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        Ok(BlockingAdapter::with_runtime(client, runtime))
+    }
+}
+
+impl<C, B> BlockingAdapter<C, B> {
+    /// Constructor with a supplied runtime
+    pub fn with_runtime(client: C, runtime: B) -> Self {
+        BlockingAdapter { client, runtime }
+    }
+
+    /// Wrapped client
+    pub fn client(&self) -> &C {
+        &self.client
+    }
+
+    /// Runtime used to execute requests
+    pub fn runtime(&self) -> &B {
+        &self.runtime
+    }
+}
+
+impl<C, B> BlockingClient for BlockingAdapter<C, B>
+where
+    C: LocalClient,
+    B: BlockOn,
+{
+    fn execute(
+        &self,
+        method: Method,
+        url: Url,
+        headers: HeaderMap,
+        body: Option<Bytes>,
+    ) -> Result<Response<Bytes>> {
+        let future = self.client.execute(method, url, headers, body);
+        self.runtime.block_on(future)
+    }
+
+    fn execute_with_endpoint(
+        &self,
+        endpoint: &Endpoint,
+        method: Method,
+        url: Url,
+        headers: HeaderMap,
+        body: Option<Bytes>,
+    ) -> Result<Response<Bytes>> {
+        let future = self
+            .client
+            .execute_with_endpoint(endpoint, method, url, headers, body);
+        self.runtime.block_on(future)
+    }
+}
@@ -0,0 +1,284 @@
+//! Client configuration
+//!
+//! This module contains [`ClientConfig`], a configuration that is independent of
+//! the HTTP client being used. Client implementations can build their underlying
+//! client from this configuration, so switching clients do not require rewriting
+//! the configuration code.
+//!
+//! ```rust
+//! use pretend::config::{ClientConfig, Proxy, RedirectPolicy};
+//! use pretend::Url;
+//! use std::time::Duration;
+//!
+//! let proxy = Url::parse("http://proxy.local:3128").unwrap();
+//! let config = ClientConfig::new()
+//!     .with_connect_timeout(Duration::from_secs(5))
+//!     .with_timeout(Duration::from_secs(30))
+//!     .with_proxy(Proxy::new(proxy).with_basic_auth("user", "password"))
+//!     .with_redirect_policy(RedirectPolicy::Limit(5))
+//!     .with_user_agent("my-service/1.0")
+//!     .with_pool_size(8);
+//! ```
+//!
+//! Not all clients support every option. Clients should fail with
+//! a [`Error::Client`](crate::Error::Client) error wrapping [`UnsupportedOption`]
+//! when an option that is set cannot be applied.
+
+use crate::Url;
+use std::borrow::Cow;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
+use thiserror::Error;
+
+const REDACTED: &str = "<redacted>";
+
+/// Client configuration
+///
+/// See module level documentation for more information.
+#[derive(Clone, Debug, Default)]
+pub struct ClientConfig {
+    connect_timeout: Option<Duration>,
+    timeout: Option<Duration>,
+    proxy: Option<Proxy>,
+    root_certificates: Vec<Certificate>,
+    identity: Option<Identity>,
+    redirect_policy: Option<RedirectPolicy>,
+    user_agent: Option<String>,
+    pool_size: Option<usize>,
+}
+
+impl ClientConfig {
+    /// Constructor
+    ///
+    /// Options that are not set use the defaults of the client.
+    pub fn new() -> Self {
+        ClientConfig::default()
+    }
+
+    /// Set the timeout for connecting to a server
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Set the timeout for a whole request
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Set the proxy used for all requests
+    pub fn with_proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Add a trusted root certificate
+    pub fn with_root_certificate(mut self, certificate: Certificate) -> Self {
+        self.root_certificates.push(certificate);
+        self
+    }
+
+    /// Set the identity used for client authentication
+    pub fn with_identity(mut self, identity: Identity) -> Self {
+        self.identity = Some(identity);
+        self
+    }
+
+    /// Set the redirect policy
+    pub fn with_redirect_policy(mut self, policy: RedirectPolicy) -> Self {
+        self.redirect_policy = Some(policy);
+        self
+    }
+
+    /// Set the user agent
+    pub fn with_user_agent<S>(mut self, user_agent: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Set the maximum number of connections kept per host
+    pub fn with_pool_size(mut self, size: usize) -> Self {
+        self.pool_size = Some(size);
+        self
+    }
+
+    /// Timeout for connecting to a server
+    pub fn connect_timeout(&self) -> Option<Duration> {
+        self.connect_timeout
+    }
+
+    /// Timeout for a whole request
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Proxy used for all requests
+    pub fn proxy(&self) -> Option<&Proxy> {
+        self.proxy.as_ref()
+    }
+
+    /// Trusted root certificates
+    pub fn root_certificates(&self) -> &[Certificate] {
+        &self.root_certificates
+    }
+
+    /// Identity used for client authentication
+    pub fn identity(&self) -> Option<&Identity> {
+        self.identity.as_ref()
+    }
+
+    /// Redirect policy
+    pub fn redirect_policy(&self) -> Option<RedirectPolicy> {
+        self.redirect_policy
+    }
+
+    /// User agent
+    pub fn user_agent(&self) -> Option<&str> {
+        self.user_agent.as_deref()
+    }
+
+    /// Maximum number of connections kept per host
+    pub fn pool_size(&self) -> Option<usize> {
+        self.pool_size
+    }
+}
+
+/// Proxy configuration
+///
+/// The password is redacted from the `Debug` output.
+#[derive(Clone)]
+pub struct Proxy {
+    url: Url,
+    credentials: Option<(String, String)>,
+}
+
+impl Proxy {
+    /// Constructor
+    pub fn new(url: Url) -> Self {
+        Proxy {
+            url,
+            credentials: None,
+        }
+    }
+
+    /// Set the credentials used for basic authentication
+    pub fn with_basic_auth<U, P>(mut self, username: U, password: P) -> Self
+    where
+        U: Into<String>,
+        P: Into<String>,
+    {
+        self.credentials = Some((username.into(), password.into()));
+        self
+    }
+
+    /// Proxy URL
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// Credentials used for basic authentication
+    ///
+    /// The credentials are returned as a username and a password.
+    pub fn basic_auth(&self) -> Option<(&str, &str)> {
+        let credentials = self.credentials.as_ref();
+        credentials.map(|(username, password)| (username.as_str(), password.as_str()))
+    }
+}
+
+impl fmt::Debug for Proxy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let credentials = self.credentials.as_ref();
+        let credentials = credentials.map(|(username, _)| (username, REDACTED));
+        f.debug_struct("Proxy")
+            .field("url", &self.url)
+            .field("credentials", &credentials)
+            .finish()
+    }
+}
+
+/// Root certificate
+#[non_exhaustive]
+#[derive(Clone, Debug)]
+pub enum Certificate {
+    /// PEM encoded certificate
+    Pem(Vec<u8>),
+    /// Path to a PEM encoded certificate
+    PemFile(PathBuf),
+}
+
+impl Certificate {
+    /// PEM encoded certificate
+    ///
+    /// The certificate is read when it is stored in a file.
+    pub fn pem(&self) -> io::Result<Cow<'_, [u8]>> {
+        match self {
+            Certificate::Pem(pem) => Ok(Cow::Borrowed(pem)),
+            Certificate::PemFile(path) => fs::read(path).map(Cow::Owned),
+        }
+    }
+}
+
+/// Client identity
+///
+/// The private key and the password are redacted from the `Debug` output.
+#[non_exhaustive]
+#[derive(Clone)]
+pub enum Identity {
+    /// PEM encoded certificate chain and private key
+    Pem(Vec<u8>),
+    /// DER encoded PKCS #12 archive and its password
+    Pkcs12 {
+        /// DER encoded archive
+        der: Vec<u8>,
+        /// Password of the archive
+        password: String,
+    },
+}
+
+impl fmt::Debug for Identity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Identity::Pem(_) => f.debug_tuple("Pem").field(&REDACTED).finish(),
+            Identity::Pkcs12 { .. } => f
+                .debug_struct("Pkcs12")
+                .field("der", &REDACTED)
+                .field("password", &REDACTED)
+                .finish(),
+        }
+    }
+}
+
+/// Redirect policy
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RedirectPolicy {
+    /// Do not follow redirects
+    None,
+    /// Follow at most the given number of redirects
+    Limit(usize),
+}
+
+/// Error for options that are not supported by a client
+#[derive(Clone, Debug, Error)]
+#[error("Option `{option}` is not supported by this client")]
+pub struct UnsupportedOption {
+    option: &'static str,
+}
+
+impl UnsupportedOption {
+    /// Constructor
+    pub fn new(option: &'static str) -> Self {
+        UnsupportedOption { option }
+    }
+
+    /// Name of the option
+    pub fn option(&self) -> &'static str {
+        self.option
+    }
+}
@@ -0,0 +1,39 @@
+/// Endpoint metadata
+///
+/// This struct describes a method of a `pretend`-annotated trait. It is
+/// generated by `pretend` and contains the name of the trait, the name of
+/// the method and the path declared in `request`, before templating.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct Endpoint {
+    trait_name: &'static str,
+    name: &'static str,
+    path: &'static str,
+}
+
+impl Endpoint {
+    /// Constructor
+    pub const fn new(trait_name: &'static str, name: &'static str, path: &'static str) -> Self {
+        Endpoint {
+            trait_name,
+            name,
+            path,
+        }
+    }
+
+    /// Name of the trait declaring this endpoint
+    pub fn trait_name(&self) -> &'static str {
+        self.trait_name
+    }
+
+    /// Name of the method
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Path template
+    ///
+    /// This is the path declared in `request`, like `/users/{id}`.
+    pub fn path(&self) -> &'static str {
+        self.path
+    }
+}
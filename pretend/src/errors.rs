@@ -29,6 +29,12 @@ macro_rules! impl_error {
             /// returns bodies.
             #[error("HTTP {0}")]
             Status(StatusCode),
+            /// Circuit breaker error
+            ///
+            /// This error is returned when a circuit breaker is
+            /// open, and rejects the request without executing it.
+            #[error("Circuit breaker is open")]
+            CircuitOpen,
         }
 
         impl Error {
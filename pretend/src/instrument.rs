@@ -0,0 +1,182 @@
+//! Request instrumentation
+//!
+//! When the `tracing` feature is enabled, each request opens a span, recording
+//! the endpoint, the HTTP method and URL, the response status and the latency.
+//! The span is opened before the request is serialized and intercepted, so
+//! failures in these steps are also recorded. The URL is recorded without its
+//! query, fragment and credentials, as they might contain secrets.
+//! When the `opentelemetry` feature is enabled, the context of this span is also
+//! injected in the request headers, using the W3C `traceparent` and `tracestate`
+//! headers.
+//!
+//! Without these features, these helpers do nothing.
+
+use crate::client::{Bytes, Method};
+use crate::{Endpoint, Error, HeaderMap, Response, Result, Url};
+use std::future::Future;
+
+#[cfg(feature = "tracing")]
+use std::time::Instant;
+#[cfg(feature = "tracing")]
+use tracing::field::{display, Empty};
+#[cfg(feature = "tracing")]
+use tracing::{Instrument, Span};
+
+/// Span around the execution of a request
+pub(crate) struct RequestSpan {
+    #[cfg(feature = "tracing")]
+    span: Span,
+    #[cfg(feature = "tracing")]
+    start: Instant,
+}
+
+#[cfg(feature = "tracing")]
+impl RequestSpan {
+    pub(crate) fn new(endpoint: &Endpoint, method: &Method, url: &Url) -> Self {
+        let span = tracing::info_span!(
+            "pretend.request",
+            otel.name = %format_args!("{} {}", method, endpoint.path()),
+            otel.kind = "client",
+            otel.status_code = Empty,
+            pretend.trait_name = endpoint.trait_name(),
+            pretend.method = endpoint.name(),
+            http.request.method = %method,
+            http.route = endpoint.path(),
+            url.full = %redact(url),
+            http.response.status_code = Empty,
+            latency_ms = Empty,
+            error = Empty,
+        );
+        RequestSpan {
+            span,
+            start: Instant::now(),
+        }
+    }
+
+    pub(crate) fn record_request(&self, method: &Method, url: &Url) {
+        self.span.record("http.request.method", display(method));
+        self.span.record("url.full", display(redact(url)));
+    }
+
+    pub(crate) fn inject(&self, headers: &mut HeaderMap) {
+        #[cfg(feature = "opentelemetry")]
+        propagation::inject(&self.span, headers);
+        #[cfg(not(feature = "opentelemetry"))]
+        let _ = headers;
+    }
+
+    pub(crate) async fn instrument<F>(&self, future: F) -> F::Output
+    where
+        F: Future,
+    {
+        future.instrument(self.span.clone()).await
+    }
+
+    pub(crate) fn in_scope<F, T>(&self, f: F) -> T
+    where
+        F: FnOnce() -> T,
+    {
+        self.span.in_scope(f)
+    }
+
+    pub(crate) fn record(&self, result: &Result<Response<Bytes>>) {
+        let latency = self.start.elapsed().as_secs_f64() * 1000.;
+        self.span.record("latency_ms", latency);
+        match result {
+            Ok(response) => {
+                let status = response.status();
+                self.span
+                    .record("http.response.status_code", status.as_u16());
+                if status.is_server_error() {
+                    self.span.record("otel.status_code", "ERROR");
+                }
+            }
+            Err(err) => self.record_error(err),
+        }
+    }
+
+    pub(crate) fn record_error(&self, err: &Error) {
+        self.span.record("otel.status_code", "ERROR");
+        self.span.record("error", display(err));
+    }
+}
+
+#[cfg(feature = "tracing")]
+fn redact(url: &Url) -> Url {
+    let mut url = url.clone();
+    url.set_query(None);
+    url.set_fragment(None);
+    // Only fails for URLs that cannot have credentials
+    let _ = url.set_username("");
+    let _ = url.set_password(None);
+    url
+}
+
+#[cfg(not(feature = "tracing"))]
+impl RequestSpan {
+    pub(crate) fn new(_: &Endpoint, _: &Method, _: &Url) -> Self {
+        RequestSpan {}
+    }
+
+    pub(crate) fn record_request(&self, _: &Method, _: &Url) {}
+
+    pub(crate) fn inject(&self, _: &mut HeaderMap) {}
+
+    pub(crate) async fn instrument<F>(&self, future: F) -> F::Output
+    where
+        F: Future,
+    {
+        future.await
+    }
+
+    pub(crate) fn in_scope<F, T>(&self, f: F) -> T
+    where
+        F: FnOnce() -> T,
+    {
+        f()
+    }
+
+    pub(crate) fn record(&self, _: &Result<Response<Bytes>>) {}
+
+    pub(crate) fn record_error(&self, _: &Error) {}
+}
+
+#[cfg(feature = "opentelemetry")]
+mod propagation {
+    use crate::HeaderMap;
+    use http::HeaderValue;
+    use otel::trace::TraceContextExt;
+    use tracing::Span;
+    use tracing_otel::OpenTelemetrySpanExt;
+
+    const TRACEPARENT: &str = "traceparent";
+    const TRACESTATE: &str = "tracestate";
+    const SUPPORTED_VERSION: u8 = 0;
+
+    pub(super) fn inject(span: &Span, headers: &mut HeaderMap) {
+        let context = span.context();
+        let span = context.span();
+        let span_context = span.span_context();
+        if !span_context.is_valid() {
+            return;
+        }
+
+        let traceparent = format!(
+            "{:02x}-{}-{}-{:02x}",
+            SUPPORTED_VERSION,
+            span_context.trace_id(),
+            span_context.span_id(),
+            span_context.trace_flags().to_u8()
+        );
+        if let Ok(traceparent) = HeaderValue::from_str(&traceparent) {
+            headers.insert(TRACEPARENT, traceparent);
+        }
+
+        let tracestate = span_context.trace_state().header();
+        if !tracestate.is_empty() {
+            if let Ok(tracestate) = HeaderValue::from_str(&tracestate) {
+                headers.insert(TRACESTATE, tracestate);
+            }
+        }
+    }
+}
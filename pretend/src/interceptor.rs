@@ -12,6 +12,9 @@
 //!
 //! Custom interceptors are defined by implementing [`InterceptRequest`].
 //!
+//! Requests generated by `pretend` carry the [`Endpoint`] being called. It
+//! can be used to customize requests per endpoint.
+//!
 //! # Error handling
 //!
 //! Request interceptors are allowed to fail. They return a pretend
@@ -19,7 +22,7 @@
 //! `Error::Request` when reporting an error.
 
 pub use crate::client::Method;
-pub use crate::{HeaderMap, Result, Url};
+pub use crate::{Endpoint, HeaderMap, Result, Url};
 
 /// An HTTP request
 #[non_exhaustive]
@@ -30,6 +33,8 @@ pub struct Request {
     pub url: Url,
     /// Request headers
     pub headers: HeaderMap,
+    /// Endpoint being called
+    pub endpoint: Option<Endpoint>,
 }
 
 impl Request {
@@ -43,8 +48,15 @@ impl Request {
             method,
             url,
             headers,
+            endpoint: None,
         }
     }
+
+    /// Set the endpoint being called
+    pub fn with_endpoint(mut self, endpoint: Endpoint) -> Self {
+        self.endpoint = Some(endpoint);
+        self
+    }
 }
 
 /// Describe a request interceptor
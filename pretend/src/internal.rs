@@ -3,14 +3,19 @@
 //! Internal module used by the code generator
 
 use crate::client::{BlockingClient, Bytes, Client, LocalClient, Method};
+use crate::instrument::RequestSpan;
 use crate::interceptor::{InterceptRequest, Request};
-use crate::resolver::ResolveUrl;
-use crate::{Error, HeaderMap, Json, JsonResult, Pretend, Response, Result};
+use crate::metrics::{NoopMetricsRecorder, RecordMetrics, RequestMetrics};
+use crate::resolver::{AsyncResolveUrl, ResolveContext, ResolveUrl};
+use crate::server::{match_template, Reply};
+use crate::{Endpoint, Error, HeaderMap, Json, JsonResult, Pretend, Response, Result, StatusCode};
 use http::header::{HeaderName, CONTENT_TYPE};
 use http::HeaderValue;
+use percent_encoding::percent_decode_str;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::str::FromStr;
+use std::time::Instant;
 use url::Url;
 
 /// Request body
@@ -29,78 +34,161 @@ where
 }
 
 /// Helper for pretend code generator
-pub struct MacroSupport<'p, C, R, I>
+pub struct MacroSupport<'p, C, R, I, M = NoopMetricsRecorder>
 where
-    R: ResolveUrl,
     I: InterceptRequest,
 {
-    pretend: &'p Pretend<C, R, I>,
+    pretend: &'p Pretend<C, R, I, M>,
+    endpoint: Endpoint,
 }
 
-impl<'p, C, R, I> MacroSupport<'p, C, R, I>
+impl<'p, C, R, I, M> MacroSupport<'p, C, R, I, M>
 where
-    R: ResolveUrl,
     I: InterceptRequest,
 {
     /// Constructor
     ///
-    /// It wraps a `Pretend` instance
-    pub fn new(pretend: &'p Pretend<C, R, I>) -> Self {
-        MacroSupport { pretend }
+    /// It wraps a `Pretend` instance, and the endpoint being called
+    pub fn new(pretend: &'p Pretend<C, R, I, M>, endpoint: Endpoint) -> Self {
+        MacroSupport { pretend, endpoint }
     }
 
     /// Create an url from the resolver and a path
-    pub fn create_url(&self, path: &str) -> Result<Url> {
+    pub fn create_url(&self, method: &Method, path: &str) -> Result<Url>
+    where
+        R: ResolveUrl,
+    {
         let resolver = &self.pretend.resolver;
-        let result = resolver.resolve_url(path);
+        let context = ResolveContext::new(method, path, &self.endpoint);
+        let result = resolver.resolve_url_with_context(&context);
         result.map_err(Error::request)
     }
 
+    /// Create an url from the async resolver and a path
+    pub async fn create_url_async(&self, method: &Method, path: &str) -> Result<Url>
+    where
+        R: AsyncResolveUrl,
+    {
+        let resolver = &self.pretend.resolver;
+        let context = ResolveContext::new(method, path, &self.endpoint);
+        resolver.resolve_url_async(&context).await
+    }
+
     /// Execute a request
     ///
     /// Execute a request from request components.
-    /// Serialize the body if needed.
-    pub async fn request<'a, T>(
+    /// Serialize the body if needed, and convert the response.
+    pub async fn request<'a, T, O>(
         &'a self,
         method: Method,
         url: Url,
         headers: HeaderMap,
         body: Body<'a, T>,
-    ) -> Result<Response<Bytes>>
+    ) -> Result<O>
     where
-        C: Client,
+        C: Client + Sync,
+        M: RecordMetrics,
         T: Serialize,
+        Response<Bytes>: IntoResponse<O>,
     {
-        let client = &self.pretend.client;
-        let (method, url, headers, body) = self.prepare_request(method, url, headers, body)?;
-        client.execute(method, url, headers, body).await
+        let start = Instant::now();
+        let result = self.execute(method.clone(), url, headers, body).await;
+        self.convert(&method, start, result)
     }
 
     /// Execute a request on a local client
     ///
     /// Execute a request from request components.
-    /// Serialize the body if needed.
-    pub async fn request_local<'a, T>(
+    /// Serialize the body if needed, and convert the response.
+    pub async fn request_local<'a, T, O>(
         &'a self,
         method: Method,
         url: Url,
         headers: HeaderMap,
         body: Body<'a, T>,
-    ) -> Result<Response<Bytes>>
+    ) -> Result<O>
     where
         C: LocalClient,
+        M: RecordMetrics,
         T: Serialize,
+        Response<Bytes>: IntoResponse<O>,
     {
-        let client = &self.pretend.client;
-        let (method, url, headers, body) = self.prepare_request(method, url, headers, body)?;
-        client.execute(method, url, headers, body).await
+        let start = Instant::now();
+        let result = self.execute_local(method.clone(), url, headers, body).await;
+        self.convert(&method, start, result)
     }
 
     /// Execute a blocking request
     ///
     /// Execute a request from request components.
-    /// Serialize the body if needed.
-    pub fn request_blocking<'a, T>(
+    /// Serialize the body if needed, and convert the response.
+    pub fn request_blocking<'a, T, O>(
+        &'a self,
+        method: Method,
+        url: Url,
+        headers: HeaderMap,
+        body: Body<'a, T>,
+    ) -> Result<O>
+    where
+        C: BlockingClient,
+        M: RecordMetrics,
+        T: Serialize,
+        Response<Bytes>: IntoResponse<O>,
+    {
+        let start = Instant::now();
+        let result = self.execute_blocking(method.clone(), url, headers, body);
+        self.convert(&method, start, result)
+    }
+
+    async fn execute<'a, T>(
+        &'a self,
+        method: Method,
+        url: Url,
+        headers: HeaderMap,
+        body: Body<'a, T>,
+    ) -> Result<Response<Bytes>>
+    where
+        C: Client + Sync,
+        T: Serialize,
+    {
+        let client = &self.pretend.client;
+        let span = RequestSpan::new(&self.endpoint, &method, &url);
+        let request = self.prepare_request(&span, method, url, headers, body)?;
+        let (method, url, mut headers, body) = request;
+        span.inject(&mut headers);
+
+        let endpoint = &self.endpoint;
+        let future = client.execute_with_endpoint(endpoint, method, url, headers, body);
+        let result = span.instrument(future).await;
+        span.record(&result);
+        result
+    }
+
+    async fn execute_local<'a, T>(
+        &'a self,
+        method: Method,
+        url: Url,
+        headers: HeaderMap,
+        body: Body<'a, T>,
+    ) -> Result<Response<Bytes>>
+    where
+        C: LocalClient,
+        T: Serialize,
+    {
+        let client = &self.pretend.client;
+        let span = RequestSpan::new(&self.endpoint, &method, &url);
+        let request = self.prepare_request(&span, method, url, headers, body)?;
+        let (method, url, mut headers, body) = request;
+        span.inject(&mut headers);
+
+        let endpoint = &self.endpoint;
+        let future = client.execute_with_endpoint(endpoint, method, url, headers, body);
+        let result = span.instrument(future).await;
+        span.record(&result);
+        result
+    }
+
+    fn execute_blocking<'a, T>(
         &'a self,
         method: Method,
         url: Url,
@@ -112,11 +200,77 @@ where
         T: Serialize,
     {
         let client = &self.pretend.client;
-        let (method, url, headers, body) = self.prepare_request(method, url, headers, body)?;
-        client.execute(method, url, headers, body)
+        let span = RequestSpan::new(&self.endpoint, &method, &url);
+        let request = self.prepare_request(&span, method, url, headers, body)?;
+        let (method, url, mut headers, body) = request;
+        span.inject(&mut headers);
+
+        let endpoint = &self.endpoint;
+        let result =
+            span.in_scope(|| client.execute_with_endpoint(endpoint, method, url, headers, body));
+        span.record(&result);
+        result
+    }
+
+    // Responses are converted before metrics are recorded, so that conversion
+    // errors, like unsuccessful statuses or invalid JSON, are recorded as errors
+    fn convert<O>(
+        &self,
+        method: &Method,
+        start: Instant,
+        result: Result<Response<Bytes>>,
+    ) -> Result<O>
+    where
+        M: RecordMetrics,
+        Response<Bytes>: IntoResponse<O>,
+    {
+        let result = result.and_then(|response| {
+            let output = response.clone().into_response()?;
+            Ok((response, output))
+        });
+        match result {
+            Ok((response, output)) => {
+                self.record(method, start, &Ok(response));
+                Ok(output)
+            }
+            Err(err) => {
+                let result = Err(err);
+                self.record(method, start, &result);
+                // Returns the error as it is
+                result.and_then(IntoResponse::into_response)
+            }
+        }
+    }
+
+    fn record(&self, method: &Method, start: Instant, result: &Result<Response<Bytes>>)
+    where
+        M: RecordMetrics,
+    {
+        let latency = start.elapsed();
+        let metrics = RequestMetrics::new(&self.endpoint, method, latency, result);
+        self.pretend.metrics.record(&metrics);
     }
 
     fn prepare_request<'a, T>(
+        &'a self,
+        span: &RequestSpan,
+        method: Method,
+        url: Url,
+        headers: HeaderMap,
+        body: Body<'a, T>,
+    ) -> Result<(Method, Url, HeaderMap, Option<Bytes>)>
+    where
+        T: Serialize,
+    {
+        let result = span.in_scope(|| self.build_request(method, url, headers, body));
+        match &result {
+            Ok((method, url, _, _)) => span.record_request(method, url),
+            Err(err) => span.record_error(err),
+        }
+        result
+    }
+
+    fn build_request<'a, T>(
         &'a self,
         method: Method,
         url: Url,
@@ -151,7 +305,7 @@ where
             }
         };
 
-        let request = Request::new(method, url, headers);
+        let request = Request::new(method, url, headers).with_endpoint(self.endpoint);
         let request = self.pretend.interceptor.intercept(request)?;
         Ok((request.method, request.url, request.headers, body))
     }
@@ -179,6 +333,182 @@ pub fn build_header(headers: &mut HeaderMap, name: &str, value: &str) -> Result<
     Ok(())
 }
 
+/// Set a default header, if the header is not already set
+pub fn build_default_header(headers: &mut HeaderMap, name: &str, value: &str) -> Result<()> {
+    let name = HeaderName::from_str(name).map_err(Error::request)?;
+    if !headers.contains_key(&name) {
+        let value = HeaderValue::from_str(value).map_err(Error::request)?;
+        headers.insert(name, value);
+    }
+    Ok(())
+}
+
+/// Append default query parameters, if they are not already set
+pub fn build_default_query(mut url: Url, query: &[(&str, &str)]) -> Url {
+    let missing = query
+        .iter()
+        .filter(|(name, _)| !url.query_pairs().any(|(key, _)| key == *name))
+        .collect::<Vec<_>>();
+
+    if !missing.is_empty() {
+        url.query_pairs_mut().extend_pairs(missing);
+    }
+    url
+}
+
+/// Helper for stub servers generated by pretend
+pub struct ServerSupport {
+    method: Method,
+    url: Url,
+    headers: HeaderMap,
+    body: Option<Bytes>,
+}
+
+impl ServerSupport {
+    /// Constructor
+    ///
+    /// It wraps the request received by the stub server
+    pub fn new(method: Method, url: Url, headers: HeaderMap, body: Option<Bytes>) -> Self {
+        ServerSupport {
+            method,
+            url,
+            headers,
+            body,
+        }
+    }
+
+    /// Match the request against a method and a path
+    ///
+    /// The path is matched against the end of the request path.
+    /// Its query part, if any, is matched against the request query.
+    pub fn route(&self, method: &Method, path: &str) -> Option<Route<'_>> {
+        if self.method != method {
+            return None;
+        }
+
+        let mut split = path.splitn(2, '?');
+        let path = split.next().unwrap_or_default();
+        let mut params = self.match_path(path)?;
+        if let Some(query) = split.next() {
+            params.extend(self.match_query(query)?);
+        }
+
+        Some(Route {
+            support: self,
+            params,
+        })
+    }
+
+    /// `404 Not Found` reply
+    pub fn not_found(&self) -> Reply {
+        Reply::new(StatusCode::NOT_FOUND)
+    }
+
+    fn match_path(&self, template: &str) -> Option<Vec<(String, String)>> {
+        let path = self.url.path();
+        let starts = path.match_indices('/').map(|(index, _)| index);
+        let mut starts = std::iter::once(0).chain(starts);
+        let params =
+            starts.find_map(|start| match_template(template, &path[start..], Some('/')))?;
+
+        let params = params.into_iter().map(|(name, value)| {
+            let value = percent_decode_str(&value).decode_utf8_lossy();
+            (name.to_string(), value.to_string())
+        });
+        Some(params.collect())
+    }
+
+    fn match_query(&self, template: &str) -> Option<Vec<(String, String)>> {
+        let mut params = Vec::new();
+        for pair in template.split('&').filter(|pair| !pair.is_empty()) {
+            let mut split = pair.splitn(2, '=');
+            let name = split.next().unwrap_or_default();
+            let template = split.next().unwrap_or_default();
+
+            let mut values = self.url.query_pairs().filter(|(key, _)| key == name);
+            let matched = values.find_map(|(_, value)| match_template(template, &value, None))?;
+            let matched = matched.into_iter();
+            params.extend(matched.map(|(name, value)| (name.to_string(), value)));
+        }
+        Some(params)
+    }
+}
+
+/// Request matched by a stub server
+pub struct Route<'s> {
+    support: &'s ServerSupport,
+    params: Vec<(String, String)>,
+}
+
+// Replies are returned directly to the client, they are not propagated
+#[allow(clippy::result_large_err)]
+impl<'s> Route<'s> {
+    /// Parse a parameter from the path
+    pub fn param<T>(&self, name: &str) -> std::result::Result<T, Reply>
+    where
+        T: FromStr,
+    {
+        let param = self.params.iter().find(|(param, _)| param == name);
+        let (_, value) = param.ok_or_else(bad_request)?;
+        value.parse().map_err(|_| bad_request())
+    }
+
+    /// Parse a parameter from a header value
+    pub fn header<T>(&self, name: &str, value: &str, param: &str) -> std::result::Result<T, Reply>
+    where
+        T: FromStr,
+    {
+        let headers = self.support.headers.get_all(name).iter();
+        let mut headers = headers.filter_map(|header| header.to_str().ok());
+        let params = headers.find_map(|header| match_template(value, header, None));
+        let params = params.ok_or_else(bad_request)?;
+
+        let value = params.iter().find(|(name, _)| *name == param);
+        let (_, value) = value.ok_or_else(bad_request)?;
+        value.parse().map_err(|_| bad_request())
+    }
+
+    /// Deserialize the query
+    pub fn query<T>(&self) -> std::result::Result<T, Reply>
+    where
+        T: DeserializeOwned,
+    {
+        let query = self.support.url.query().unwrap_or_default();
+        serde_urlencoded::from_str(query).map_err(|_| bad_request())
+    }
+
+    /// Raw query
+    pub fn raw_query(&self) -> String {
+        let query = self.support.url.query().unwrap_or_default();
+        query.to_string()
+    }
+
+    /// Deserialize a form body
+    pub fn form<T>(&self) -> std::result::Result<T, Reply>
+    where
+        T: DeserializeOwned,
+    {
+        serde_urlencoded::from_bytes(&self.body()).map_err(|_| bad_request())
+    }
+
+    /// Deserialize a JSON body
+    pub fn json<T>(&self) -> std::result::Result<T, Reply>
+    where
+        T: DeserializeOwned,
+    {
+        serde_json::from_slice(&self.body()).map_err(|_| bad_request())
+    }
+
+    /// Raw body
+    pub fn body(&self) -> Bytes {
+        self.support.body.clone().unwrap_or_default()
+    }
+}
+
+fn bad_request() -> Reply {
+    Reply::new(StatusCode::BAD_REQUEST)
+}
+
 /// Trait to convert into a response
 ///
 /// This trait is responsible to convert a raw body
@@ -188,6 +518,13 @@ pub trait IntoResponse<T> {
     fn into_response(self) -> Result<T>;
 }
 
+// Raw responses, forwarded as they are
+impl IntoResponse<Response<Bytes>> for Response<Bytes> {
+    fn into_response(self) -> Result<Response<Bytes>> {
+        Ok(self)
+    }
+}
+
 impl IntoResponse<()> for Response<Bytes> {
     fn into_response(self) -> Result<()> {
         if self.status.is_success() {
@@ -200,8 +537,7 @@ impl IntoResponse<()> for Response<Bytes> {
 
 impl IntoResponse<Response<()>> for Response<Bytes> {
     fn into_response(self) -> Result<Response<()>> {
-        let (status, headers, _) = self.into_parts();
-        Ok(Response::new(status, headers, ()))
+        Ok(self.with_body(()))
     }
 }
 
@@ -218,7 +554,7 @@ impl IntoResponse<String> for Response<Bytes> {
 impl IntoResponse<Response<String>> for Response<Bytes> {
     fn into_response(self) -> Result<Response<String>> {
         let body = parse_string_body(&self);
-        Ok(Response::new(self.status, self.headers, body))
+        Ok(self.with_body(body))
     }
 }
 
@@ -252,7 +588,8 @@ impl IntoResponse<Vec<u8>> for Response<Bytes> {
 
 impl IntoResponse<Response<Vec<u8>>> for Response<Bytes> {
     fn into_response(self) -> Result<Response<Vec<u8>>> {
-        Ok(Response::new(self.status, self.headers, self.body.to_vec()))
+        let body = self.body.to_vec();
+        Ok(self.with_body(body))
     }
 }
 
@@ -275,9 +612,9 @@ where
     T: DeserializeOwned,
 {
     fn into_response(self) -> Result<Response<Json<T>>> {
-        let value = parse_json(self.body)?;
+        let value = parse_json(self.body.clone())?;
         let body = Json { value };
-        Ok(Response::new(self.status, self.headers, body))
+        Ok(self.with_body(body))
     }
 }
 
@@ -304,19 +641,11 @@ where
 {
     fn into_response(self) -> Result<Response<JsonResult<T, E>>> {
         if self.status.is_success() {
-            let value = parse_json(self.body)?;
-            Ok(Response::new(
-                self.status,
-                self.headers,
-                JsonResult::Ok(value),
-            ))
+            let value = parse_json(self.body.clone())?;
+            Ok(self.with_body(JsonResult::Ok(value)))
         } else {
-            let value = parse_json(self.body)?;
-            Ok(Response::new(
-                self.status,
-                self.headers,
-                JsonResult::Err(value),
-            ))
+            let value = parse_json(self.body.clone())?;
+            Ok(self.with_body(JsonResult::Err(value)))
         }
     }
 }
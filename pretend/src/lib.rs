@@ -184,17 +184,118 @@
 //! # }
 //! ```
 //!
+//! # Trait-level defaults
+//!
+//! Settings shared by all the methods of a trait can be passed to the `pretend` attribute:
+//!
+//! - `path` is a prefix added to the path of every request,
+//! - `header(name = "...", value = "...")` is a default header, and can be repeated,
+//! - `query(name = "...", value = "...")` is a default query parameter, and can be repeated,
+//! - `accept` is the default value of the `Accept` header.
+//!
+//! Default headers and query parameters are only sent if the method does not set them
+//! already, either with `header`, in the request path or with `query`.
+//!
+//! ```rust
+//! use pretend::{pretend, Result};
+//!
+//! #[pretend(
+//!     path = "/api/v1",
+//!     header(name = "X-Client", value = "pretend"),
+//!     query(name = "format", value = "full"),
+//!     accept = "application/json"
+//! )]
+//! trait HttpBin {
+//!     #[request(method = "GET", path = "/users")]
+//!     async fn get_users(&self) -> Result<String>;
+//!
+//!     #[request(method = "GET", path = "/users?format={format}")]
+//!     #[header(name = "Accept", value = "text/plain")]
+//!     async fn get_users_as_text(&self, format: &str) -> Result<String>;
+//! }
+//! ```
+//!
+//! # Generic methods
+//!
+//! Methods can be generic over their parameters and their return type. This allows an
+//! endpoint to accept several body types, or to return a response type chosen by the caller.
+//!
+//! ```rust
+//! use pretend::{pretend, Json, Result};
+//! use serde::de::DeserializeOwned;
+//! use serde::Serialize;
+//!
+//! #[pretend]
+//! trait HttpBin {
+//!     #[request(method = "POST", path = "/anything")]
+//!     async fn post_json<T, R>(&self, json: &T) -> Result<Json<R>>
+//!     where
+//!         T: Serialize + Sync,
+//!         R: DeserializeOwned;
+//!
+//!     #[request(method = "GET", path = "/anything")]
+//!     async fn get_with_query(&self, query: &(impl Serialize + Sync)) -> Result<String>;
+//! }
+//! ```
+//!
+//! Since futures returned by `Send` traits must be `Send`, generic types that are borrowed
+//! should be `Sync`, and generic types that are passed by value should be `Send`. Const
+//! generics are not supported.
+//!
+//! # Default methods
+//!
+//! Methods with a default implementation, and without the `request` attribute, are kept as
+//! they are. They can be used to compose several requests. Associated constants with a
+//! default value are also supported.
+//!
+//! ```rust
+//! use pretend::{pretend, Result};
+//!
+//! #[pretend]
+//! trait HttpBin {
+//!     const DEFAULT_STATUS: u16 = 200;
+//!
+//!     #[request(method = "GET", path = "/status/{status}")]
+//!     async fn get_status(&self, status: u16) -> Result<()>;
+//!
+//!     async fn get_default_status(&self) -> Result<()> {
+//!         self.get_status(Self::DEFAULT_STATUS).await
+//!     }
+//! }
+//! ```
+//!
 //! # URL resolvers
 //!
 //! `pretend` uses URL resolvers to resolve a full URL from the path in `request`. By default
-//! the URL resolver will simply append the path to a base URL. More advanced resolvers can
+//! the URL resolver will simply join the path to a base URL. More advanced resolvers can
 //! be implemented with the [resolver] module.
 //!
+//! When the base URL contains a path, like an API prefix, [`resolver::BasePathUrlResolver`]
+//! can be used to append the path in `request` to the path of the base URL.
+//!
+//! Resolvers have access to the HTTP method and to the [`Endpoint`] being called. Async
+//! implementations can also resolve URLs asynchronously, for example by querying a service
+//! discovery mechanism.
+//!
+//! ```rust
+//! use pretend::resolver::BasePathUrlResolver;
+//! use pretend::{Pretend, Url};
+//! use pretend_reqwest::Client;
+//!
+//! let url = Url::parse("https://httpbin.org/api/v2").unwrap();
+//! let resolver = BasePathUrlResolver::new(url);
+//! let pretend = Pretend::for_client(Client::default()).with_url_resolver(resolver);
+//! ```
+//!
 //! # Request interceptors
 //!
 //! `pretend` uses request interceptors to customize auto-generated requests. They can be useful
 //! when dealing with authentication. They can be implemented with the [interceptor] module.
 //!
+//! Interceptors receive the [`Endpoint`] being called, containing the trait name, the method
+//! name and the path template. Clients receive it too, via `execute_with_endpoint`. It can be
+//! used to key logs or metrics on endpoints rather than on full URLs.
+//!
 //! ```rust
 //! use pretend::http::header::AUTHORIZATION;
 //! use pretend::http::HeaderValue;
@@ -249,6 +350,88 @@
 //! # }
 //! ```
 //!
+//! # Client middlewares
+//!
+//! `pretend` uses client middlewares to customize how requests are executed, independently
+//! of the HTTP client implementation. A client can be wrapped with a middleware, like a rate
+//! limiter. They can be implemented with the [middleware] module.
+//!
+//! ```rust
+//! use pretend::middleware::ratelimit::{Quota, RateLimiter};
+//! use pretend::middleware::MiddlewareClient;
+//! use pretend::{Pretend, Url};
+//! use pretend_reqwest::Client;
+//!
+//! let limiter = RateLimiter::new().with_quota(Quota::per_second(10));
+//! let client = MiddlewareClient::new(Client::default(), limiter);
+//! let url = Url::parse("https://httpbin.org").unwrap();
+//! let pretend = Pretend::for_client(client).with_url(url);
+//! ```
+//!
+//! # Tracing
+//!
+//! When the `tracing` feature is enabled, `pretend` opens a [`tracing`] span named
+//! `pretend.request` for each request. This span records the endpoint, the HTTP method,
+//! the templated route and the URL. Once the request is executed, the response status and
+//! the latency are recorded too.
+//!
+//! When the `opentelemetry` feature is enabled, the OpenTelemetry context of this span is
+//! injected in the request headers, using the W3C `traceparent` and `tracestate` headers.
+//! This feature relies on [`tracing-opentelemetry`] to attach OpenTelemetry contexts to
+//! `tracing` spans.
+//!
+//! Since spans are opened by the generated code, they are available with every client
+//! implementation.
+//!
+//! [`tracing`]: https://docs.rs/tracing/latest/tracing/
+//! [`tracing-opentelemetry`]: https://docs.rs/tracing-opentelemetry/latest/tracing_opentelemetry/
+//!
+//! # Metrics
+//!
+//! `pretend` calls a metrics recorder after each request, with the endpoint, the latency
+//! and the result of the request. Recorders are set with [`Pretend::with_metrics_recorder`]
+//! and are implemented with the [metrics] module.
+//!
+//! When the `metrics` feature is enabled, [`metrics::MetricsRecorder`] records request
+//! counts, error counts, status classes and latency histograms with the [`metrics`] crate.
+//!
+//! ```rust
+//! # #[cfg(feature = "metrics")]
+//! # {
+//! use pretend::metrics::MetricsRecorder;
+//! use pretend::{Pretend, Url};
+//! use pretend_reqwest::Client;
+//!
+//! let url = Url::parse("https://httpbin.org").unwrap();
+//! let pretend = Pretend::for_client(Client::default())
+//!     .with_url(url)
+//!     .with_metrics_recorder(MetricsRecorder);
+//! # }
+//! ```
+//!
+//! [`metrics`]: https://docs.rs/metrics/latest/metrics/
+//!
+//! # OpenAPI
+//!
+//! When the `openapi` feature is enabled, traits annotated with `#[pretend(openapi)]` can be
+//! described as OpenAPI 3 path items. This allows comparing the contract implemented by a
+//! client against the specification of the provider. See the `openapi` module for more
+//! information.
+//!
+//! # Stub servers
+//!
+//! Traits annotated with `#[pretend(server)]` also get a handler trait and a stub server.
+//! The stub server is a client implementation that dispatches requests to the handlers,
+//! so that tests can run the generated client without a real HTTP server. See the
+//! [server] module for more information.
+//!
+//! # Tower
+//!
+//! When the `tower` feature is enabled, `pretend` can execute requests with a tower `Service`,
+//! so that tower middlewares can be used under `pretend`, or dispatch requests to an in-process
+//! application, without binding a socket. `Pretend` can also be used as a tower `Service`. See
+//! the `tower` module for more information.
+//!
 //! # Examples
 //!
 //! More examples are available in the [examples folder].
@@ -285,6 +468,24 @@
 //!
 //! [`reqwest`]: https://crates.io/crates/pretend-reqwest
 //!
+//! An async trait can also get a blocking variant, with the `blocking_variant` option. The
+//! variant is a second trait, with the same requests, that is implemented for blocking clients.
+//! Async default methods are not part of the variant.
+//!
+//! ```rust
+//! use pretend::{pretend, Result};
+//!
+//! #[pretend(blocking_variant = "HttpBinBlocking")]
+//! trait HttpBin {
+//!     #[request(method = "POST", path = "/anything")]
+//!     async fn post_anything(&self, body: &'static str) -> Result<String>;
+//! }
+//! ```
+//!
+//! Any async client can also be used as a blocking client, by wrapping it in
+//! [`client::blocking::BlockingAdapter`]. Requests are then executed on a runtime that is
+//! either supplied, or runs on the calling thread.
+//!
 //! # Non-Send implementation
 //!
 //! Today, Rust does not support futures in traits. `pretend` uses `async_trait` to workaround
@@ -315,6 +516,8 @@
 //!
 //! - [`reqwest`](https://crates.io/crates/pretend-reqwest) (async and blocking)
 //! - [`isahc`](https://crates.io/crates/pretend-isahc) (async)
+//! - [`hyper`](https://crates.io/crates/pretend-hyper) (async)
+//! - [`curl`](https://crates.io/crates/pretend-curl) (blocking)
 //! - [`awc`](https://crates.io/crates/pretend-awc) (local async)
 //! - [`ureq`](https://crates.io/crates/pretend-ureq) (blocking)
 //!
@@ -346,6 +549,30 @@
 //! reqwest = { version = "*", default-features = false, features = ["rustls-tls"] }
 //! ```
 //!
+//! Enabling the `rustls-tls` feature of `pretend-reqwest` instead allows root certificates and
+//! identities from the client configuration to be used with rustls.
+//!
+//! # Client configuration
+//!
+//! Timeouts, proxy, root certificates, client identity, redirect policy, user agent and pool
+//! size can be described with [`config::ClientConfig`], independently of the HTTP client. Client
+//! implementations provide a `from_config` constructor that builds the underlying HTTP client
+//! from this configuration, so switching clients does not require rewriting configuration code.
+//!
+//! ```rust
+//! use pretend::config::ClientConfig;
+//! use pretend::{Pretend, Url};
+//! use pretend_reqwest::Client;
+//! use std::time::Duration;
+//!
+//! let config = ClientConfig::new()
+//!     .with_timeout(Duration::from_secs(30))
+//!     .with_user_agent("my-service/1.0");
+//!
+//! let url = Url::parse("https://httpbin.org").unwrap();
+//! let pretend = Pretend::for_client(Client::from_config(&config).unwrap()).with_url(url);
+//! ```
+//!
 //! # Implementing a `pretend` HTTP client
 //!
 //! `pretend` clients wraps HTTP clients from other crates. They allow [`Pretend`] to execute
@@ -366,12 +593,23 @@
 #![forbid(unsafe_code)]
 
 pub mod client;
+pub mod config;
 pub mod interceptor;
 pub mod internal;
+pub mod metrics;
+pub mod middleware;
+#[cfg(feature = "openapi")]
+pub mod openapi;
 pub mod resolver;
+pub mod server;
+#[cfg(feature = "tower")]
+pub mod tower;
 
+mod endpoint;
 mod errors;
+mod instrument;
 
+pub use self::endpoint::Endpoint;
 pub use self::errors::{Error, Result};
 pub use http;
 pub use http::{HeaderMap, StatusCode};
@@ -381,16 +619,22 @@ pub use url;
 pub use url::Url;
 
 use crate::interceptor::{InterceptRequest, NoopRequestInterceptor};
-use crate::resolver::{InvalidUrlResolver, ResolveUrl, UrlResolver};
+use crate::metrics::{NoopMetricsRecorder, RecordMetrics};
+use crate::resolver::{InvalidUrlResolver, UrlResolver};
 use serde::de::DeserializeOwned;
 use std::ops::{Deref, DerefMut};
 
 /// Response type
-#[derive(Clone, Debug, Eq, PartialEq)]
+///
+/// Responses are compared by status, headers and body. The final URL
+/// is not taken into account.
+#[derive(Clone, Debug)]
 pub struct Response<T> {
     status: StatusCode,
     headers: HeaderMap,
     body: T,
+    // Boxed to keep responses small, as the URL is rarely used
+    url: Option<Box<Url>>,
 }
 
 impl<T> Response<T> {
@@ -400,9 +644,25 @@ impl<T> Response<T> {
             status,
             headers,
             body,
+            url: None,
         }
     }
 
+    /// Set the final URL of this response
+    pub fn with_url(mut self, url: Url) -> Self {
+        self.url = Some(Box::new(url));
+        self
+    }
+
+    /// Final URL of this response
+    ///
+    /// This is the URL of the last request, after following redirects.
+    /// It is only available when it is reported by the client implementation
+    /// or by a middleware, like [`middleware::redirect::Redirect`].
+    pub fn url(&self) -> Option<&Url> {
+        self.url.as_deref()
+    }
+
     /// HTTP status
     pub fn status(&self) -> &StatusCode {
         &self.status
@@ -413,6 +673,11 @@ impl<T> Response<T> {
         &self.headers
     }
 
+    /// Mutable response headers
+    pub fn headers_mut(&mut self) -> &mut HeaderMap {
+        &mut self.headers
+    }
+
     /// Response body
     pub fn body(&self) -> &T {
         &self.body
@@ -427,8 +692,31 @@ impl<T> Response<T> {
     pub fn into_parts(self) -> (StatusCode, HeaderMap, T) {
         (self.status, self.headers, self.body)
     }
+
+    /// Replace the body
+    ///
+    /// The status, headers and final URL are kept.
+    pub fn with_body<U>(self, body: U) -> Response<U> {
+        Response {
+            status: self.status,
+            headers: self.headers,
+            body,
+            url: self.url,
+        }
+    }
+}
+
+impl<T> PartialEq for Response<T>
+where
+    T: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.status == other.status && self.headers == other.headers && self.body == other.body
+    }
 }
 
+impl<T> Eq for Response<T> where T: Eq {}
+
 /// The pretend HTTP client
 ///
 /// This struct is the entry point for `pretend` clients. It can be constructed with
@@ -437,19 +725,18 @@ impl<T> Response<T> {
 ///
 /// See crate level documentation for more information
 #[derive(Clone, Debug)]
-pub struct Pretend<C, R, I>
+pub struct Pretend<C, R, I, M = NoopMetricsRecorder>
 where
-    R: ResolveUrl,
     I: InterceptRequest,
 {
     client: C,
     resolver: R,
     interceptor: I,
+    metrics: M,
 }
 
 impl<C, R, I> Pretend<C, R, I>
 where
-    R: ResolveUrl,
     I: InterceptRequest,
 {
     /// Constructor
@@ -461,34 +748,64 @@ where
             client,
             resolver,
             interceptor,
+            metrics: NoopMetricsRecorder,
         }
     }
+}
 
+impl<C, R, I, M> Pretend<C, R, I, M>
+where
+    I: InterceptRequest,
+{
     /// Set the base URL
     ///
     /// Set the base URL for this client.
-    pub fn with_url(self, url: Url) -> Pretend<C, UrlResolver, I> {
+    pub fn with_url(self, url: Url) -> Pretend<C, UrlResolver, I, M> {
         self.with_url_resolver(UrlResolver::new(url))
     }
 
     /// Set the request interceptor
     ///
     /// Set the request interceptor for this client.
-    pub fn with_request_interceptor<II>(self, interceptor: II) -> Pretend<C, R, II>
+    pub fn with_request_interceptor<II>(self, interceptor: II) -> Pretend<C, R, II, M>
     where
         II: InterceptRequest,
     {
-        Pretend::new(self.client, self.resolver, interceptor)
+        Pretend {
+            client: self.client,
+            resolver: self.resolver,
+            interceptor,
+            metrics: self.metrics,
+        }
     }
 
     /// Set the URL resolver
     ///
-    /// Set the URL resolver for this client.
-    pub fn with_url_resolver<RR>(self, resolver: RR) -> Pretend<C, RR, I>
+    /// Set the URL resolver for this client. Both [`resolver::ResolveUrl`]
+    /// and [`resolver::AsyncResolveUrl`] implementations are supported.
+    pub fn with_url_resolver<RR>(self, resolver: RR) -> Pretend<C, RR, I, M> {
+        Pretend {
+            client: self.client,
+            resolver,
+            interceptor: self.interceptor,
+            metrics: self.metrics,
+        }
+    }
+
+    /// Set the metrics recorder
+    ///
+    /// Set the metrics recorder for this client.
+    /// See [`metrics`] for more information.
+    pub fn with_metrics_recorder<MM>(self, recorder: MM) -> Pretend<C, R, I, MM>
     where
-        RR: ResolveUrl,
+        MM: RecordMetrics,
     {
-        Pretend::new(self.client, resolver, self.interceptor)
+        Pretend {
+            client: self.client,
+            resolver: self.resolver,
+            interceptor: self.interceptor,
+            metrics: recorder,
+        }
     }
 }
 
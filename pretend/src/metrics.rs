@@ -0,0 +1,166 @@
+//! Request metrics
+//!
+//! `pretend` supports metrics recorders. They are called after each
+//! request, with the endpoint being called, the HTTP method, the latency
+//! and the result of the request.
+//!
+//! Recorders are called once the response is converted to the return type
+//! of the method. Conversion errors, like an unsuccessful status or a JSON body
+//! that can't be parsed, are recorded as errors.
+//!
+//! Since recorders are called by the generated code, the metrics are
+//! consistent, whatever the client implementation being used.
+//!
+//! By default a [`NoopMetricsRecorder`] is used, and will
+//! not record anything.
+//!
+//! Custom recorders are defined by implementing [`RecordMetrics`].
+//!
+//! # `metrics` integration
+//!
+//! When the `metrics` feature is enabled, [`MetricsRecorder`] records
+//! metrics with the [`metrics`] crate. The following metrics are recorded:
+//!
+//! - `pretend_requests_total`, a counter of requests, with the
+//!   `trait`, `method`, `route` and `status_class` labels.
+//! - `pretend_errors_total`, a counter of errors, with the
+//!   `trait`, `method`, `route` and `error` labels.
+//! - `pretend_request_duration_seconds`, an histogram of latencies, with
+//!   the `trait`, `method`, `route` and `status_class` labels.
+//!
+//! When a request fails without a status, `status_class` is set to `error`.
+//!
+//! [`metrics`]: https://docs.rs/metrics/latest/metrics/
+
+pub use crate::client::{Bytes, Method};
+pub use crate::{Endpoint, Error, Response, Result};
+
+use std::time::Duration;
+
+/// Metrics of a request
+#[non_exhaustive]
+pub struct RequestMetrics<'a> {
+    /// Endpoint being called
+    pub endpoint: &'a Endpoint,
+    /// Request method
+    pub method: &'a Method,
+    /// Time spent executing the request
+    pub latency: Duration,
+    /// Result of the request, including conversion errors
+    pub result: &'a Result<Response<Bytes>>,
+}
+
+impl<'a> RequestMetrics<'a> {
+    /// Constructor
+    pub fn new(
+        endpoint: &'a Endpoint,
+        method: &'a Method,
+        latency: Duration,
+        result: &'a Result<Response<Bytes>>,
+    ) -> Self {
+        RequestMetrics {
+            endpoint,
+            method,
+            latency,
+            result,
+        }
+    }
+
+    /// Class of the response status
+    ///
+    /// Returns `1xx`, `2xx`, `3xx`, `4xx` or `5xx`, `other` for
+    /// non-standard statuses, or `None` if the request failed
+    /// without a status.
+    pub fn status_class(&self) -> Option<&'static str> {
+        let status = match self.result {
+            Ok(response) => response.status(),
+            Err(Error::Status(status)) => status,
+            Err(_) => return None,
+        };
+        let class = match status.as_u16() / 100 {
+            1 => "1xx",
+            2 => "2xx",
+            3 => "3xx",
+            4 => "4xx",
+            5 => "5xx",
+            _ => "other",
+        };
+        Some(class)
+    }
+
+    /// Kind of error
+    ///
+    /// Returns the name of the [`Error`] variant in snake case, or
+    /// `None` if the request succeeded.
+    pub fn error_kind(&self) -> Option<&'static str> {
+        let kind = match self.result.as_ref().err()? {
+            Error::Client(_) => "client",
+            Error::Request(_) => "request",
+            Error::Response(_) => "response",
+            Error::Body(_) => "body",
+            Error::Status(_) => "status",
+            Error::CircuitOpen => "circuit_open",
+        };
+        Some(kind)
+    }
+}
+
+/// Describe a metrics recorder
+///
+/// See module level documentation for more information.
+pub trait RecordMetrics {
+    /// Record the metrics of a request
+    fn record(&self, metrics: &RequestMetrics);
+}
+
+/// Default metrics recorder
+///
+/// This metrics recorder will not
+/// record anything.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoopMetricsRecorder;
+
+impl RecordMetrics for NoopMetricsRecorder {
+    fn record(&self, _: &RequestMetrics) {}
+}
+
+#[cfg(feature = "metrics")]
+pub use self::recorder::MetricsRecorder;
+
+#[cfg(feature = "metrics")]
+mod recorder {
+    use super::{RecordMetrics, RequestMetrics};
+
+    const REQUESTS_TOTAL: &str = "pretend_requests_total";
+    const ERRORS_TOTAL: &str = "pretend_errors_total";
+    const REQUEST_DURATION_SECONDS: &str = "pretend_request_duration_seconds";
+
+    /// Metrics recorder using the `metrics` crate
+    ///
+    /// See module level documentation for more information.
+    #[derive(Clone, Copy, Debug, Default)]
+    pub struct MetricsRecorder;
+
+    impl RecordMetrics for MetricsRecorder {
+        fn record(&self, metrics: &RequestMetrics) {
+            let endpoint = metrics.endpoint;
+            let labels = |name, value| {
+                [
+                    ("trait", endpoint.trait_name()),
+                    ("method", endpoint.name()),
+                    ("route", endpoint.path()),
+                    (name, value),
+                ]
+            };
+
+            let status_class = labels("status_class", metrics.status_class().unwrap_or("error"));
+            ::metrics::counter!(REQUESTS_TOTAL, &status_class).increment(1);
+            ::metrics::histogram!(REQUEST_DURATION_SECONDS, &status_class)
+                .record(metrics.latency.as_secs_f64());
+
+            if let Some(error) = metrics.error_kind() {
+                ::metrics::counter!(ERRORS_TOTAL, &labels("error", error)).increment(1);
+            }
+        }
+    }
+}
@@ -0,0 +1,404 @@
+//! Client middlewares
+//!
+//! `pretend` supports client middlewares. They wrap a client
+//! implementation to customize how requests are executed,
+//! independently of the HTTP client being used.
+//!
+//! Middlewares are defined by implementing [`Middleware`].
+//! A middleware is called before a request is executed, and
+//! after a response is received:
+//!
+//! - Before executing the request, it can modify the request, return
+//!   a response directly, or ask to wait before being called again.
+//! - After receiving the response, it can modify the response, or
+//!   ask to execute another request.
+//!
+//! A middleware is attached to a client with [`MiddlewareClient`], that
+//! supports both [`Client`] and [`BlockingClient`], and [`LocalMiddlewareClient`],
+//! that supports [`LocalClient`]. Since these wrappers are clients, they can
+//! be nested to use several middlewares.
+//!
+//! The following middlewares are provided:
+//!
+//! - [`ratelimit::RateLimiter`] limits the rate of requests.
+//! - [`circuitbreaker::CircuitBreaker`] rejects requests when a server keeps failing.
+//! - [`cache::Cache`] caches responses.
+//! - [`compression::Compression`] compresses requests and decodes responses.
+//! - [`redirect::Redirect`] follows redirects.
+//! - [`cookie::CookieJar`] stores cookies and sends them back.
+//!
+//! # Error handling
+//!
+//! Middlewares are allowed to fail. They return a pretend
+//! `Result` that will be returned as is to the caller.
+
+pub mod cache;
+pub mod circuitbreaker;
+pub mod compression;
+pub mod cookie;
+pub mod ratelimit;
+pub mod redirect;
+
+pub use crate::client::{Bytes, Method};
+pub use crate::{Endpoint, HeaderMap, Response, Result, Url};
+
+use crate::client::{async_trait, BlockingClient, Client, LocalClient};
+use futures_timer::Delay;
+use std::future::Future;
+use std::thread;
+use std::time::Duration;
+
+/// An HTTP request
+#[non_exhaustive]
+#[derive(Clone, Debug)]
+pub struct Request {
+    /// Request method
+    pub method: Method,
+    /// Full request URL
+    pub url: Url,
+    /// Request headers
+    pub headers: HeaderMap,
+    /// Request body
+    pub body: Option<Bytes>,
+    /// Endpoint being called
+    ///
+    /// It is only available when the request is executed by `pretend`.
+    pub endpoint: Option<Endpoint>,
+}
+
+impl Request {
+    /// Constructor
+    pub fn new(method: Method, url: Url, headers: HeaderMap, body: Option<Bytes>) -> Self {
+        Request {
+            method,
+            url,
+            headers,
+            body,
+            endpoint: None,
+        }
+    }
+
+    /// Set the endpoint being called
+    pub fn with_endpoint(mut self, endpoint: Endpoint) -> Self {
+        self.endpoint = Some(endpoint);
+        self
+    }
+}
+
+/// Outcome of [`Middleware::before`]
+pub enum Before<S> {
+    /// Execute the request
+    ///
+    /// The state will be passed to [`Middleware::after`].
+    Execute(S),
+    /// Wait before calling [`Middleware::before`] again
+    Wait(Duration),
+    /// Return this response without executing the request
+    Respond(Response<Bytes>),
+}
+
+/// Outcome of [`Middleware::after`]
+pub enum After {
+    /// Return this response
+    Respond(Response<Bytes>),
+    /// Execute another request
+    ///
+    /// [`Middleware::after`] will be called again with
+    /// the response of this request.
+    Execute(Request),
+}
+
+/// Describe a client middleware
+///
+/// See module level documentation for more information.
+pub trait Middleware {
+    /// State kept between [`Middleware::before`] and [`Middleware::after`]
+    type State;
+
+    /// Called before executing a request
+    fn before(&self, request: &mut Request) -> Result<Before<Self::State>>;
+
+    /// Called after executing a request
+    ///
+    /// The response is the result of executing `request`.
+    fn after(
+        &self,
+        state: &mut Self::State,
+        request: &Request,
+        response: Result<Response<Bytes>>,
+    ) -> Result<After>;
+}
+
+/// Client with a middleware
+///
+/// This client wraps a [`Client`] or a [`BlockingClient`]
+/// and uses a middleware when executing requests.
+#[derive(Clone, Debug)]
+pub struct MiddlewareClient<C, M> {
+    client: C,
+    middleware: M,
+}
+
+impl<C, M> MiddlewareClient<C, M> {
+    /// Constructor
+    pub fn new(client: C, middleware: M) -> Self {
+        MiddlewareClient { client, middleware }
+    }
+
+    /// Wrapped client
+    pub fn client(&self) -> &C {
+        &self.client
+    }
+
+    /// Middleware
+    pub fn middleware(&self) -> &M {
+        &self.middleware
+    }
+}
+
+#[async_trait]
+impl<C, M> Client for MiddlewareClient<C, M>
+where
+    C: Client + Sync,
+    M: Middleware + Sync,
+    M::State: Send,
+{
+    async fn execute(
+        &self,
+        method: Method,
+        url: Url,
+        headers: HeaderMap,
+        body: Option<Bytes>,
+    ) -> Result<Response<Bytes>> {
+        let request = Request::new(method, url, headers, body);
+        self.execute_async(request).await
+    }
+
+    async fn execute_with_endpoint(
+        &self,
+        endpoint: &Endpoint,
+        method: Method,
+        url: Url,
+        headers: HeaderMap,
+        body: Option<Bytes>,
+    ) -> Result<Response<Bytes>> {
+        let request = Request::new(method, url, headers, body).with_endpoint(*endpoint);
+        self.execute_async(request).await
+    }
+}
+
+impl<C, M> BlockingClient for MiddlewareClient<C, M>
+where
+    C: BlockingClient,
+    M: Middleware,
+{
+    fn execute(
+        &self,
+        method: Method,
+        url: Url,
+        headers: HeaderMap,
+        body: Option<Bytes>,
+    ) -> Result<Response<Bytes>> {
+        let request = Request::new(method, url, headers, body);
+        self.execute_blocking(request)
+    }
+
+    fn execute_with_endpoint(
+        &self,
+        endpoint: &Endpoint,
+        method: Method,
+        url: Url,
+        headers: HeaderMap,
+        body: Option<Bytes>,
+    ) -> Result<Response<Bytes>> {
+        let request = Request::new(method, url, headers, body).with_endpoint(*endpoint);
+        self.execute_blocking(request)
+    }
+}
+
+impl<C, M> MiddlewareClient<C, M>
+where
+    M: Middleware,
+{
+    async fn execute_async(&self, request: Request) -> Result<Response<Bytes>>
+    where
+        C: Client + Sync,
+    {
+        let client = &self.client;
+        execute(&self.middleware, request, |request| async move {
+            let Request {
+                method,
+                url,
+                headers,
+                body,
+                endpoint,
+            } = request;
+            match endpoint {
+                Some(endpoint) => {
+                    let future = Client::execute_with_endpoint(
+                        client, &endpoint, method, url, headers, body,
+                    );
+                    future.await
+                }
+                None => Client::execute(client, method, url, headers, body).await,
+            }
+        })
+        .await
+    }
+
+    fn execute_blocking(&self, request: Request) -> Result<Response<Bytes>>
+    where
+        C: BlockingClient,
+    {
+        let client = &self.client;
+        execute_blocking(&self.middleware, request, |request| {
+            let Request {
+                method,
+                url,
+                headers,
+                body,
+                endpoint,
+            } = request;
+            match endpoint {
+                Some(endpoint) => {
+                    client.execute_with_endpoint(&endpoint, method, url, headers, body)
+                }
+                None => client.execute(method, url, headers, body),
+            }
+        })
+    }
+}
+
+/// Local client with a middleware
+///
+/// This client wraps a [`LocalClient`] and uses
+/// a middleware when executing requests.
+#[derive(Clone, Debug)]
+pub struct LocalMiddlewareClient<C, M> {
+    client: C,
+    middleware: M,
+}
+
+impl<C, M> LocalMiddlewareClient<C, M> {
+    /// Constructor
+    pub fn new(client: C, middleware: M) -> Self {
+        LocalMiddlewareClient { client, middleware }
+    }
+
+    /// Wrapped client
+    pub fn client(&self) -> &C {
+        &self.client
+    }
+
+    /// Middleware
+    pub fn middleware(&self) -> &M {
+        &self.middleware
+    }
+}
+
+#[async_trait(?Send)]
+impl<C, M> LocalClient for LocalMiddlewareClient<C, M>
+where
+    C: LocalClient,
+    M: Middleware,
+{
+    async fn execute(
+        &self,
+        method: Method,
+        url: Url,
+        headers: HeaderMap,
+        body: Option<Bytes>,
+    ) -> Result<Response<Bytes>> {
+        let request = Request::new(method, url, headers, body);
+        self.execute_local(request).await
+    }
+
+    async fn execute_with_endpoint(
+        &self,
+        endpoint: &Endpoint,
+        method: Method,
+        url: Url,
+        headers: HeaderMap,
+        body: Option<Bytes>,
+    ) -> Result<Response<Bytes>> {
+        let request = Request::new(method, url, headers, body).with_endpoint(*endpoint);
+        self.execute_local(request).await
+    }
+}
+
+impl<C, M> LocalMiddlewareClient<C, M>
+where
+    C: LocalClient,
+    M: Middleware,
+{
+    async fn execute_local(&self, request: Request) -> Result<Response<Bytes>> {
+        let client = &self.client;
+        execute(&self.middleware, request, |request| async move {
+            let Request {
+                method,
+                url,
+                headers,
+                body,
+                endpoint,
+            } = request;
+            match endpoint {
+                Some(endpoint) => {
+                    let future =
+                        client.execute_with_endpoint(&endpoint, method, url, headers, body);
+                    future.await
+                }
+                None => client.execute(method, url, headers, body).await,
+            }
+        })
+        .await
+    }
+}
+
+async fn execute<M, F, Fut>(middleware: &M, mut request: Request, f: F) -> Result<Response<Bytes>>
+where
+    M: Middleware,
+    F: Fn(Request) -> Fut,
+    Fut: Future<Output = Result<Response<Bytes>>>,
+{
+    let mut state = loop {
+        let before = middleware.before(&mut request)?;
+        match before {
+            Before::Execute(state) => break state,
+            Before::Wait(duration) => Delay::new(duration).await,
+            Before::Respond(response) => return Ok(response),
+        }
+    };
+
+    loop {
+        let response = f(request.clone()).await;
+        let after = middleware.after(&mut state, &request, response)?;
+        match after {
+            After::Respond(response) => return Ok(response),
+            After::Execute(next) => request = next,
+        }
+    }
+}
+
+fn execute_blocking<M, F>(middleware: &M, mut request: Request, f: F) -> Result<Response<Bytes>>
+where
+    M: Middleware,
+    F: Fn(Request) -> Result<Response<Bytes>>,
+{
+    let mut state = loop {
+        let before = middleware.before(&mut request)?;
+        match before {
+            Before::Execute(state) => break state,
+            Before::Wait(duration) => thread::sleep(duration),
+            Before::Respond(response) => return Ok(response),
+        }
+    };
+
+    loop {
+        let response = f(request.clone());
+        let after = middleware.after(&mut state, &request, response)?;
+        match after {
+            After::Respond(response) => return Ok(response),
+            After::Execute(next) => request = next,
+        }
+    }
+}
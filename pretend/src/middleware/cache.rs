@@ -0,0 +1,438 @@
+//! HTTP caching middleware
+//!
+//! [`Cache`] is a private HTTP cache, following the rules of RFC 9111.
+//! It stores responses to `GET` requests in a [`CacheStore`], and serves
+//! them again while they are fresh.
+//!
+//! The freshness of a response is computed from the `Cache-Control` and
+//! `Expires` headers. When these headers are missing, a heuristic based on
+//! `Last-Modified` is used. Responses with `Cache-Control: no-store` or
+//! `Vary: *` are not stored.
+//!
+//! When a stored response is stale, the request is sent with `If-None-Match`
+//! and `If-Modified-Since` headers, built from the `ETag` and `Last-Modified`
+//! headers of the stored response. If the server responds with
+//! `304 Not Modified`, the stored response is updated and returned instead.
+//!
+//! `Cache-Control` directives sent with requests are supported. `no-store`
+//! bypasses the cache, `no-cache` forces revalidation, `max-age` limits the
+//! age of the returned response and `only-if-cached` responds with
+//! `504 Gateway Timeout` instead of sending the request.
+//!
+//! Successful responses to unsafe requests, like `POST` or `DELETE`,
+//! invalidate the stored response for the same URL.
+//!
+//! # Stores
+//!
+//! Stores are implemented with [`CacheStore`]. The following stores are provided:
+//!
+//! - [`MemoryStore`] keeps responses in memory, evicting the least recently used ones.
+//! - [`DiskStore`] keeps responses in files in a directory.
+//!
+//! ```rust
+//! use pretend::middleware::cache::{Cache, MemoryStore};
+//! use pretend::middleware::MiddlewareClient;
+//! use pretend_reqwest::Client;
+//!
+//! let cache = Cache::new(MemoryStore::new(100));
+//! let client = MiddlewareClient::new(Client::default(), cache);
+//! ```
+
+mod disk;
+mod memory;
+
+pub use self::disk::DiskStore;
+pub use self::memory::MemoryStore;
+
+use super::{After, Before, Middleware, Request};
+use crate::client::{Bytes, Method};
+use crate::{HeaderMap, Response, Result, StatusCode, Url};
+use http::header::{
+    HeaderName, AGE, CACHE_CONTROL, CONTENT_LENGTH, DATE, ETAG, EXPIRES, IF_MATCH,
+    IF_MODIFIED_SINCE, IF_NONE_MATCH, IF_UNMODIFIED_SINCE, LAST_MODIFIED, VARY,
+};
+use http::HeaderValue;
+use std::mem;
+use std::time::{Duration, SystemTime};
+
+// Heuristic freshness is 10% of the time since the last modification
+const HEURISTIC_FRACTION: u32 = 10;
+// Heuristic freshness is capped to one day
+const HEURISTIC_MAX: Duration = Duration::from_secs(24 * 60 * 60);
+// Delta-seconds values and ages are capped to 2^31 seconds (RFC 9111, section 1.2.2)
+const MAX_DELTA_SECONDS: u64 = 2_147_483_648;
+
+/// A stored response
+#[derive(Clone, Debug)]
+pub struct CacheEntry {
+    response: Response<Bytes>,
+    vary_headers: HeaderMap,
+    stored_at: SystemTime,
+}
+
+impl CacheEntry {
+    /// Constructor
+    ///
+    /// `vary_headers` contains the request headers listed in
+    /// the `Vary` header of the response, and `stored_at` is
+    /// the time when the response was received.
+    pub fn new(response: Response<Bytes>, vary_headers: HeaderMap, stored_at: SystemTime) -> Self {
+        CacheEntry {
+            response,
+            vary_headers,
+            stored_at,
+        }
+    }
+
+    /// Stored response
+    pub fn response(&self) -> &Response<Bytes> {
+        &self.response
+    }
+
+    /// Request headers listed in the `Vary` header of the response
+    pub fn vary_headers(&self) -> &HeaderMap {
+        &self.vary_headers
+    }
+
+    /// Time when the response was received
+    pub fn stored_at(&self) -> SystemTime {
+        self.stored_at
+    }
+
+    fn matches(&self, headers: &HeaderMap) -> bool {
+        vary_names(self.response.headers()).all(|name| {
+            let stored = self.vary_headers.get_all(&name).iter();
+            let sent = headers.get_all(&name).iter();
+            stored.eq(sent)
+        })
+    }
+
+    fn age(&self, now: SystemTime) -> Duration {
+        let headers = self.response.headers();
+        let date = parse_date(headers, DATE).unwrap_or(self.stored_at);
+        let apparent_age = self.stored_at.duration_since(date).unwrap_or_default();
+        let age = parse_seconds(headers, AGE).unwrap_or_default();
+        let resident_time = now.duration_since(self.stored_at).unwrap_or_default();
+        let age = apparent_age.max(age).checked_add(resident_time);
+        age.unwrap_or_else(|| Duration::from_secs(MAX_DELTA_SECONDS))
+    }
+
+    fn freshness_lifetime(&self) -> Duration {
+        let headers = self.response.headers();
+        let directives = Directives::parse(headers);
+        if directives.no_cache {
+            return Duration::default();
+        }
+        if let Some(max_age) = directives.max_age {
+            return max_age;
+        }
+
+        let date = parse_date(headers, DATE).unwrap_or(self.stored_at);
+        if headers.contains_key(EXPIRES) {
+            // Invalid dates represent a time in the past
+            let expires = parse_date(headers, EXPIRES).unwrap_or(date);
+            return expires.duration_since(date).unwrap_or_default();
+        }
+        match parse_date(headers, LAST_MODIFIED) {
+            Some(last_modified) if is_heuristically_cacheable(self.response.status()) => {
+                let age = date.duration_since(last_modified).unwrap_or_default();
+                (age / HEURISTIC_FRACTION).min(HEURISTIC_MAX)
+            }
+            _ => Duration::default(),
+        }
+    }
+
+    fn is_fresh(&self, directives: &Directives, now: SystemTime) -> bool {
+        if directives.no_cache {
+            return false;
+        }
+        let age = self.age(now);
+        let lifetime = self.freshness_lifetime();
+        let lifetime = directives.max_age.map_or(lifetime, |max| lifetime.min(max));
+        age < lifetime
+    }
+
+    fn validate(&self, headers: &mut HeaderMap) -> bool {
+        let stored = self.response.headers();
+        let etag = stored.get(ETAG);
+        let last_modified = stored.get(LAST_MODIFIED);
+        if let Some(etag) = etag {
+            headers.insert(IF_NONE_MATCH, etag.clone());
+        }
+        if let Some(last_modified) = last_modified {
+            headers.insert(IF_MODIFIED_SINCE, last_modified.clone());
+        }
+        etag.is_some() || last_modified.is_some()
+    }
+
+    fn refresh(self, headers: &HeaderMap, now: SystemTime) -> Self {
+        let mut response = self.response;
+        let stored = response.headers_mut();
+        for name in headers.keys() {
+            if name == CONTENT_LENGTH {
+                continue;
+            }
+            stored.remove(name);
+            for value in headers.get_all(name) {
+                stored.append(name.clone(), value.clone());
+            }
+        }
+        CacheEntry::new(response, self.vary_headers, now)
+    }
+
+    // The URL of the request is used when the stored response has no URL
+    fn to_response(&self, url: &Url, now: SystemTime) -> Response<Bytes> {
+        let mut response = self.response.clone();
+        let age = HeaderValue::from(self.age(now).as_secs());
+        response.headers_mut().insert(AGE, age);
+        match response.url() {
+            Some(_) => response,
+            None => response.with_url(url.clone()),
+        }
+    }
+}
+
+/// Describe a cache store
+///
+/// Stores are keyed by request URL. They are used behind a shared
+/// reference and should use interior mutability.
+///
+/// Caching is best effort: stores should not fail, and should
+/// ignore the errors they encounter instead.
+pub trait CacheStore {
+    /// Get a stored response
+    fn get(&self, key: &str) -> Option<CacheEntry>;
+
+    /// Store a response
+    fn put(&self, key: &str, entry: CacheEntry);
+
+    /// Remove a stored response
+    fn remove(&self, key: &str);
+}
+
+/// HTTP caching middleware
+///
+/// See module level documentation for more information.
+#[derive(Clone, Debug)]
+pub struct Cache<S> {
+    store: S,
+}
+
+impl<S> Cache<S> {
+    /// Constructor
+    pub fn new(store: S) -> Self {
+        Cache { store }
+    }
+
+    /// Cache store
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+}
+
+impl<S> Middleware for Cache<S>
+where
+    S: CacheStore,
+{
+    type State = CacheState;
+
+    fn before(&self, request: &mut Request) -> Result<Before<CacheState>> {
+        if request.method != Method::GET {
+            let state = if is_unsafe(&request.method) {
+                CacheState::Invalidate
+            } else {
+                CacheState::Bypass
+            };
+            return Ok(Before::Execute(state));
+        }
+
+        let directives = Directives::parse(&request.headers);
+        if directives.no_store {
+            return Ok(Before::Execute(CacheState::Bypass));
+        }
+
+        let now = SystemTime::now();
+        let entry = self.store.get(request.url.as_str());
+        let entry = entry.filter(|entry| entry.matches(&request.headers));
+        match entry {
+            Some(entry) if entry.is_fresh(&directives, now) => {
+                Ok(Before::Respond(entry.to_response(&request.url, now)))
+            }
+            _ if directives.only_if_cached => {
+                let response =
+                    Response::new(StatusCode::GATEWAY_TIMEOUT, HeaderMap::new(), Bytes::new());
+                Ok(Before::Respond(response))
+            }
+            Some(entry) if !is_conditional(&request.headers) => {
+                if entry.validate(&mut request.headers) {
+                    Ok(Before::Execute(CacheState::Revalidate(Box::new(entry))))
+                } else {
+                    Ok(Before::Execute(CacheState::Store))
+                }
+            }
+            _ => Ok(Before::Execute(CacheState::Store)),
+        }
+    }
+
+    fn after(
+        &self,
+        state: &mut CacheState,
+        request: &Request,
+        response: Result<Response<Bytes>>,
+    ) -> Result<After> {
+        let response = response?;
+        let key = request.url.as_str();
+        let now = SystemTime::now();
+
+        match mem::replace(state, CacheState::Bypass) {
+            CacheState::Bypass => {}
+            CacheState::Invalidate => {
+                let status = response.status();
+                if status.is_success() || status.is_redirection() {
+                    self.store.remove(key);
+                }
+            }
+            CacheState::Revalidate(entry) if *response.status() == StatusCode::NOT_MODIFIED => {
+                let entry = entry.refresh(response.headers(), now);
+                let response = entry.to_response(&request.url, now);
+                self.store.put(key, entry);
+                return Ok(After::Respond(response));
+            }
+            CacheState::Store | CacheState::Revalidate(_) => {
+                if let Some(entry) = store_entry(request, &response, now) {
+                    self.store.put(key, entry);
+                }
+            }
+        }
+        Ok(After::Respond(response))
+    }
+}
+
+/// State of a request going through a cache
+#[derive(Debug)]
+pub enum CacheState {
+    /// The request does not use the cache
+    Bypass,
+    /// The response will be stored
+    Store,
+    /// The stored response is being revalidated
+    Revalidate(Box<CacheEntry>),
+    /// The stored response will be removed
+    Invalidate,
+}
+
+#[derive(Debug, Default)]
+struct Directives {
+    no_store: bool,
+    no_cache: bool,
+    max_age: Option<Duration>,
+    only_if_cached: bool,
+}
+
+impl Directives {
+    fn parse(headers: &HeaderMap) -> Self {
+        let mut directives = Directives::default();
+        let values = headers.get_all(CACHE_CONTROL).iter();
+        let values = values.filter_map(|value| value.to_str().ok());
+        for directive in values.flat_map(|value| value.split(',')) {
+            let mut split = directive.splitn(2, '=');
+            let name = split.next().unwrap_or_default().trim().to_ascii_lowercase();
+            let value = split.next().map(|value| value.trim().trim_matches('"'));
+            match name.as_str() {
+                "no-store" => directives.no_store = true,
+                "no-cache" => directives.no_cache = true,
+                "only-if-cached" => directives.only_if_cached = true,
+                "max-age" => {
+                    let max_age = value.and_then(parse_delta_seconds);
+                    // Invalid max-age represent a stale response
+                    directives.max_age = Some(max_age.unwrap_or_default());
+                }
+                _ => {}
+            }
+        }
+        directives
+    }
+}
+
+fn store_entry(
+    request: &Request,
+    response: &Response<Bytes>,
+    now: SystemTime,
+) -> Option<CacheEntry> {
+    let headers = response.headers();
+    let directives = Directives::parse(headers);
+    if directives.no_store {
+        return None;
+    }
+
+    let mut vary_headers = HeaderMap::new();
+    for name in vary_names(headers) {
+        if name == "*" {
+            return None;
+        }
+        for value in request.headers.get_all(&name) {
+            vary_headers.append(name.clone(), value.clone());
+        }
+    }
+
+    let explicit = directives.max_age.is_some() || headers.contains_key(EXPIRES);
+    if !explicit && !is_heuristically_cacheable(response.status()) {
+        return None;
+    }
+
+    let entry = CacheEntry::new(response.clone(), vary_headers, now);
+    let validators = headers.contains_key(ETAG) || headers.contains_key(LAST_MODIFIED);
+    if validators || entry.freshness_lifetime() > Duration::default() {
+        Some(entry)
+    } else {
+        None
+    }
+}
+
+fn vary_names(headers: &HeaderMap) -> impl Iterator<Item = HeaderName> + '_ {
+    let values = headers.get_all(VARY).iter();
+    let values = values.filter_map(|value| value.to_str().ok());
+    values
+        .flat_map(|value| value.split(','))
+        .filter_map(|name| HeaderName::from_bytes(name.trim().as_bytes()).ok())
+}
+
+fn is_heuristically_cacheable(status: &StatusCode) -> bool {
+    matches!(
+        status.as_u16(),
+        200 | 203 | 204 | 206 | 300 | 301 | 308 | 404 | 405 | 410 | 414 | 501
+    )
+}
+
+fn is_unsafe(method: &Method) -> bool {
+    !matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE
+    )
+}
+
+fn is_conditional(headers: &HeaderMap) -> bool {
+    headers.contains_key(IF_NONE_MATCH)
+        || headers.contains_key(IF_MODIFIED_SINCE)
+        || headers.contains_key(IF_MATCH)
+        || headers.contains_key(IF_UNMODIFIED_SINCE)
+}
+
+fn parse_date(headers: &HeaderMap, name: HeaderName) -> Option<SystemTime> {
+    let value = headers.get(name)?.to_str().ok()?;
+    httpdate::parse_http_date(value.trim()).ok()
+}
+
+fn parse_seconds(headers: &HeaderMap, name: HeaderName) -> Option<Duration> {
+    let value = headers.get(name)?.to_str().ok()?;
+    parse_delta_seconds(value.trim())
+}
+
+fn parse_delta_seconds(value: &str) -> Option<Duration> {
+    if value.is_empty() || !value.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    // Only digits are left, parsing can only fail on overflow
+    let seconds = value.parse().unwrap_or(MAX_DELTA_SECONDS);
+    Some(Duration::from_secs(seconds.min(MAX_DELTA_SECONDS)))
+}
@@ -0,0 +1,143 @@
+use super::{CacheEntry, CacheStore};
+use crate::client::Bytes;
+use crate::{HeaderMap, Response, StatusCode, Url};
+use http::header::HeaderName;
+use http::HeaderValue;
+use serde_json::{json, Value};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+/// On-disk cache store
+///
+/// This store keeps each response in a file in a directory. The directory
+/// is created when the first response is stored. Responses are kept until
+/// they are replaced or invalidated, and the directory is never pruned.
+///
+/// Header values that are not valid strings are not stored.
+#[derive(Clone, Debug)]
+pub struct DiskStore {
+    path: PathBuf,
+}
+
+impl DiskStore {
+    /// Constructor
+    pub fn new<P>(path: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        DiskStore { path: path.into() }
+    }
+
+    /// Directory containing the stored responses
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // File names are a stable hash of the key, and the key is stored in
+    // the file to detect collisions
+    fn file(&self, key: &str) -> PathBuf {
+        let hash = key.bytes().fold(FNV_OFFSET_BASIS, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+        });
+        self.path.join(format!("{:016x}", hash))
+    }
+
+    fn read(&self, key: &str) -> Option<CacheEntry> {
+        let content = fs::read(self.file(key)).ok()?;
+        let split = content.iter().position(|byte| *byte == b'\n')?;
+        let metadata: Value = serde_json::from_slice(&content[..split]).ok()?;
+        if metadata["key"].as_str()? != key {
+            return None;
+        }
+
+        let status = metadata["status"].as_u64()?;
+        let status = StatusCode::from_u16(status as u16).ok()?;
+        let headers = headers_from_json(&metadata["headers"])?;
+        let vary_headers = headers_from_json(&metadata["vary_headers"])?;
+        let stored_at = Duration::from_millis(metadata["stored_at"].as_u64()?);
+        let body = Bytes::copy_from_slice(&content[split + 1..]);
+
+        let mut response = Response::new(status, headers, body);
+        if let Some(url) = metadata["url"].as_str() {
+            response = response.with_url(Url::parse(url).ok()?);
+        }
+        Some(CacheEntry::new(
+            response,
+            vary_headers,
+            UNIX_EPOCH + stored_at,
+        ))
+    }
+
+    fn write(&self, key: &str, entry: &CacheEntry) -> io::Result<()> {
+        let stored_at = entry.stored_at.duration_since(UNIX_EPOCH);
+        let stored_at = stored_at.unwrap_or_default().as_millis() as u64;
+        let metadata = json!({
+            "key": key,
+            "status": entry.response.status().as_u16(),
+            "headers": headers_to_json(entry.response.headers()),
+            "vary_headers": headers_to_json(&entry.vary_headers),
+            "stored_at": stored_at,
+            "url": entry.response.url().map(Url::as_str),
+        });
+
+        let mut content = serde_json::to_vec(&metadata)?;
+        content.push(b'\n');
+        content.extend_from_slice(entry.response.body());
+
+        // Write to a temporary file first, to never read a partial response
+        fs::create_dir_all(&self.path)?;
+        let file = self.file(key);
+        let temporary = file.with_extension(format!("{}-{}.tmp", process::id(), timestamp()));
+        fs::write(&temporary, content)?;
+        let result = fs::rename(&temporary, file);
+        if result.is_err() {
+            let _ = fs::remove_file(&temporary);
+        }
+        result
+    }
+}
+
+impl CacheStore for DiskStore {
+    fn get(&self, key: &str) -> Option<CacheEntry> {
+        self.read(key)
+    }
+
+    fn put(&self, key: &str, entry: CacheEntry) {
+        let _ = self.write(key, &entry);
+    }
+
+    fn remove(&self, key: &str) {
+        if self.read(key).is_some() {
+            let _ = fs::remove_file(self.file(key));
+        }
+    }
+}
+
+fn headers_to_json(headers: &HeaderMap) -> Value {
+    let headers = headers.iter().filter_map(|(name, value)| {
+        let value = value.to_str().ok()?;
+        Some(json!([name.as_str(), value]))
+    });
+    Value::Array(headers.collect())
+}
+
+fn headers_from_json(value: &Value) -> Option<HeaderMap> {
+    let mut headers = HeaderMap::new();
+    for header in value.as_array()? {
+        let name = HeaderName::from_bytes(header[0].as_str()?.as_bytes()).ok()?;
+        let value = HeaderValue::from_str(header[1].as_str()?).ok()?;
+        headers.append(name, value);
+    }
+    Some(headers)
+}
+
+fn timestamp() -> u128 {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH);
+    now.unwrap_or_default().as_nanos()
+}
@@ -0,0 +1,113 @@
+use super::{CacheEntry, CacheStore};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+/// In-memory cache store
+///
+/// This store keeps up to `capacity` responses in memory. When it is full,
+/// the least recently used response is evicted.
+///
+/// Memory stores are cheap to clone. Clones share the same responses.
+#[derive(Clone, Debug)]
+pub struct MemoryStore {
+    capacity: usize,
+    entries: Arc<Mutex<Entries>>,
+}
+
+impl MemoryStore {
+    /// Constructor
+    ///
+    /// # Panics
+    ///
+    /// This constructor panics if `capacity` is zero.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "Capacity must not be zero");
+        MemoryStore {
+            capacity,
+            entries: Arc::new(Mutex::new(Entries::default())),
+        }
+    }
+
+    /// Maximum number of stored responses
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of stored responses
+    pub fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    /// Check if there are no stored responses
+    pub fn is_empty(&self) -> bool {
+        self.lock().entries.is_empty()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Entries> {
+        self.entries.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl CacheStore for MemoryStore {
+    fn get(&self, key: &str) -> Option<CacheEntry> {
+        let mut entries = self.lock();
+        entries.touch(key)?;
+        entries.entries.get(key).map(|(entry, _)| entry.clone())
+    }
+
+    fn put(&self, key: &str, entry: CacheEntry) {
+        let mut entries = self.lock();
+        entries.remove(key);
+        while entries.entries.len() >= self.capacity {
+            entries.evict();
+        }
+        entries.insert(key, entry);
+    }
+
+    fn remove(&self, key: &str) {
+        self.lock().remove(key);
+    }
+}
+
+// Entries are ordered by last use with a counter
+#[derive(Debug, Default)]
+struct Entries {
+    entries: HashMap<String, (CacheEntry, u64)>,
+    usage: BTreeMap<u64, String>,
+    counter: u64,
+}
+
+impl Entries {
+    fn next(&mut self) -> u64 {
+        self.counter += 1;
+        self.counter
+    }
+
+    fn touch(&mut self, key: &str) -> Option<()> {
+        let used = self.next();
+        let (_, last_used) = self.entries.get_mut(key)?;
+        let key = self.usage.remove(last_used)?;
+        *last_used = used;
+        self.usage.insert(used, key);
+        Some(())
+    }
+
+    fn insert(&mut self, key: &str, entry: CacheEntry) {
+        let used = self.next();
+        self.entries.insert(key.to_string(), (entry, used));
+        self.usage.insert(used, key.to_string());
+    }
+
+    fn remove(&mut self, key: &str) {
+        if let Some((_, used)) = self.entries.remove(key) {
+            self.usage.remove(&used);
+        }
+    }
+
+    fn evict(&mut self) {
+        let oldest = self.usage.keys().next().copied();
+        if let Some(key) = oldest.and_then(|used| self.usage.remove(&used)) {
+            self.entries.remove(&key);
+        }
+    }
+}
@@ -0,0 +1,336 @@
+//! Circuit breaker middleware
+//!
+//! [`CircuitBreaker`] stops sending requests to a server that keeps failing.
+//! It prevents a failing dependency from exhausting connections and threads.
+//!
+//! A circuit breaker has three states:
+//!
+//! - When closed, requests are executed, and their outcomes are recorded
+//!   in a sliding window. When the ratio of failures in this window reaches
+//!   the configured ratio, the circuit breaker opens.
+//! - When open, requests are rejected immediately with `Error::CircuitOpen`.
+//!   After a configured duration, the circuit breaker becomes half-open.
+//! - When half-open, a limited number of probe requests are executed. If they
+//!   all succeed, the circuit breaker closes. If one of them fails, the circuit
+//!   breaker opens again.
+//!
+//! A request is considered to be failed when the client returns an `Error::Response`,
+//! or when the server responds with a `5xx` status. Other errors are ignored.
+//!
+//! Circuit breakers are cheap to clone. Clones share the same state, allowing
+//! a circuit breaker to be shared by several clients.
+//!
+//! ```rust
+//! use pretend::middleware::circuitbreaker::CircuitBreaker;
+//! use pretend::middleware::MiddlewareClient;
+//! use pretend_reqwest::Client;
+//! use std::time::Duration;
+//!
+//! let breaker = CircuitBreaker::new()
+//!     .with_failure_ratio(0.5)
+//!     .with_window(20)
+//!     .with_open_duration(Duration::from_secs(10));
+//! let client = MiddlewareClient::new(Client::default(), breaker);
+//! ```
+
+use super::{After, Before, Middleware, Request};
+use crate::client::Bytes;
+use crate::{Error, Response, Result};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+/// State of a circuit breaker
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum CircuitState {
+    /// Requests are executed
+    Closed,
+    /// Requests are rejected
+    Open,
+    /// Probe requests are executed
+    HalfOpen,
+}
+
+/// Circuit breaker middleware
+///
+/// See module level documentation for more information.
+#[derive(Clone, Debug)]
+pub struct CircuitBreaker {
+    failure_ratio: f64,
+    window: usize,
+    minimum_requests: usize,
+    open_duration: Duration,
+    half_open_requests: u32,
+    circuit: Arc<Mutex<Circuit>>,
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        CircuitBreaker {
+            failure_ratio: 0.5,
+            window: 20,
+            minimum_requests: 10,
+            open_duration: Duration::from_secs(30),
+            half_open_requests: 1,
+            circuit: Arc::new(Mutex::new(Circuit::default())),
+        }
+    }
+}
+
+impl CircuitBreaker {
+    /// Constructor
+    ///
+    /// This constructor creates a circuit breaker that opens when half
+    /// of the last 20 requests failed, with at least 10 requests. It stays
+    /// open for 30 seconds, and closes after 1 successful probe request.
+    pub fn new() -> Self {
+        CircuitBreaker::default()
+    }
+
+    /// Set the ratio of failed requests that opens the circuit breaker
+    ///
+    /// The circuit breaker opens when the ratio of failures is greater
+    /// than or equal to this ratio.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `failure_ratio` is not in `(0, 1]`.
+    pub fn with_failure_ratio(mut self, failure_ratio: f64) -> Self {
+        assert!(
+            failure_ratio > 0. && failure_ratio <= 1.,
+            "Failure ratio must be in (0, 1]"
+        );
+        self.failure_ratio = failure_ratio;
+        self
+    }
+
+    /// Set the number of recent requests used to compute the failure ratio
+    ///
+    /// The minimum number of requests is capped to this value.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `window` is zero.
+    pub fn with_window(mut self, window: usize) -> Self {
+        assert!(window > 0, "Window must not be zero");
+        self.window = window;
+        self
+    }
+
+    /// Set the minimum number of requests before the circuit breaker can open
+    pub fn with_minimum_requests(mut self, minimum_requests: usize) -> Self {
+        self.minimum_requests = minimum_requests;
+        self
+    }
+
+    /// Set how long the circuit breaker stays open before probing
+    pub fn with_open_duration(mut self, open_duration: Duration) -> Self {
+        self.open_duration = open_duration;
+        self
+    }
+
+    /// Set the number of successful probe requests needed to close the circuit breaker
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `half_open_requests` is zero.
+    pub fn with_half_open_requests(mut self, half_open_requests: u32) -> Self {
+        assert!(
+            half_open_requests > 0,
+            "Half-open requests must not be zero"
+        );
+        self.half_open_requests = half_open_requests;
+        self
+    }
+
+    /// Current state of the circuit breaker
+    pub fn state(&self) -> CircuitState {
+        match self.lock().state {
+            State::Closed => CircuitState::Closed,
+            State::Open { .. } => CircuitState::Open,
+            State::HalfOpen { .. } => CircuitState::HalfOpen,
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Circuit> {
+        lock(&self.circuit)
+    }
+
+    fn acquire(&self, now: Instant) -> Option<Permit> {
+        let mut guard = self.lock();
+        let circuit = &mut *guard;
+        if let State::Open { until } = circuit.state {
+            if now < until {
+                return None;
+            }
+            circuit.half_open();
+        }
+
+        let probe = match &mut circuit.state {
+            State::HalfOpen {
+                in_flight,
+                successes,
+            } => {
+                if *in_flight + *successes >= self.half_open_requests {
+                    return None;
+                }
+                *in_flight += 1;
+                Some(circuit.generation)
+            }
+            _ => None,
+        };
+        Some(Permit {
+            circuit: self.circuit.clone(),
+            probe,
+        })
+    }
+
+    fn record(&self, permit: &mut Permit, failed: Option<bool>, now: Instant) {
+        let mut guard = self.lock();
+        let circuit = &mut *guard;
+        match permit.probe.take() {
+            Some(generation) if generation == circuit.generation => {
+                let close = match &mut circuit.state {
+                    State::HalfOpen {
+                        in_flight,
+                        successes,
+                    } => {
+                        *in_flight -= 1;
+                        if failed == Some(false) {
+                            *successes += 1;
+                        }
+                        *successes >= self.half_open_requests
+                    }
+                    _ => return,
+                };
+                if failed == Some(true) {
+                    circuit.open(now + self.open_duration);
+                } else if close {
+                    circuit.close();
+                }
+            }
+            Some(_) => {}
+            None => {
+                if let (State::Closed, Some(failed)) = (&circuit.state, failed) {
+                    circuit.outcomes.push_back(failed);
+                    if circuit.outcomes.len() > self.window {
+                        circuit.outcomes.pop_front();
+                    }
+                    if self.should_open(&circuit.outcomes) {
+                        circuit.open(now + self.open_duration);
+                    }
+                }
+            }
+        }
+    }
+
+    fn should_open(&self, outcomes: &VecDeque<bool>) -> bool {
+        let minimum_requests = self.minimum_requests.min(self.window);
+        if outcomes.len() < minimum_requests || outcomes.is_empty() {
+            return false;
+        }
+        let failures = outcomes.iter().filter(|failed| **failed).count();
+        failures as f64 / outcomes.len() as f64 >= self.failure_ratio
+    }
+}
+
+impl Middleware for CircuitBreaker {
+    type State = Permit;
+
+    fn before(&self, _: &mut Request) -> Result<Before<Permit>> {
+        match self.acquire(Instant::now()) {
+            Some(permit) => Ok(Before::Execute(permit)),
+            None => Err(Error::CircuitOpen),
+        }
+    }
+
+    fn after(
+        &self,
+        permit: &mut Permit,
+        _: &Request,
+        response: Result<Response<Bytes>>,
+    ) -> Result<After> {
+        let failed = match &response {
+            Ok(response) => Some(response.status().is_server_error()),
+            Err(Error::Response(_)) => Some(true),
+            Err(_) => None,
+        };
+        self.record(permit, failed, Instant::now());
+        response.map(After::Respond)
+    }
+}
+
+/// Permit to execute a request through a circuit breaker
+///
+/// A permit for a probe request is released when
+/// it is dropped without being used.
+#[derive(Debug)]
+pub struct Permit {
+    circuit: Arc<Mutex<Circuit>>,
+    probe: Option<u64>,
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        if let Some(generation) = self.probe {
+            let mut guard = lock(&self.circuit);
+            let circuit = &mut *guard;
+            if generation != circuit.generation {
+                return;
+            }
+            if let State::HalfOpen { in_flight, .. } = &mut circuit.state {
+                *in_flight -= 1;
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+enum State {
+    Closed,
+    Open { until: Instant },
+    HalfOpen { in_flight: u32, successes: u32 },
+}
+
+#[derive(Debug)]
+struct Circuit {
+    state: State,
+    outcomes: VecDeque<bool>,
+    // Incremented each time the circuit becomes half-open,
+    // to ignore probe requests from previous half-open states
+    generation: u64,
+}
+
+impl Default for Circuit {
+    fn default() -> Self {
+        Circuit {
+            state: State::Closed,
+            outcomes: VecDeque::new(),
+            generation: 0,
+        }
+    }
+}
+
+impl Circuit {
+    fn open(&mut self, until: Instant) {
+        self.state = State::Open { until };
+        self.outcomes.clear();
+    }
+
+    fn half_open(&mut self) {
+        self.state = State::HalfOpen {
+            in_flight: 0,
+            successes: 0,
+        };
+        self.generation += 1;
+    }
+
+    fn close(&mut self) {
+        self.state = State::Closed;
+        self.outcomes.clear();
+    }
+}
+
+fn lock(circuit: &Mutex<Circuit>) -> MutexGuard<'_, Circuit> {
+    circuit.lock().unwrap_or_else(PoisonError::into_inner)
+}
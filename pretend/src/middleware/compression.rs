@@ -0,0 +1,435 @@
+//! Compression middleware
+//!
+//! [`Compression`] handles compression independently of the HTTP client
+//! being used. It advertises the supported encodings with `Accept-Encoding`,
+//! and decodes response bodies according to their `Content-Encoding`.
+//! Decoded responses no longer have the `Content-Encoding` and `Content-Length`
+//! headers.
+//!
+//! It can also compress request bodies, with [`RequestCompression`]. Only bodies
+//! larger than a minimum size are compressed. Request compression can be enabled
+//! for all requests, and configured for some traits or some endpoints. Traits and
+//! endpoints are only known when requests are executed by `pretend`.
+//!
+//! Supported encodings depend on the following features:
+//!
+//! - `gzip` enables `gzip` and `deflate`.
+//! - `brotli` enables `br`.
+//! - `zstd` enables `zstd`.
+//!
+//! When `Accept-Encoding` is already set, it is kept as is. Responses with
+//! unsupported encodings are returned as is. If the HTTP client also decodes
+//! responses, its own decompression should be disabled.
+//!
+//! To protect against decompression bombs, decoded bodies are limited to
+//! 64 MiB by default. When several encodings are applied, this limit applies
+//! to each decoding step. Larger bodies fail with a [`DecodedBodyTooLarge`]
+//! error, returned as an `Error::Body`.
+//!
+//! ```rust
+//! # #[cfg(feature = "gzip")]
+//! # {
+//! use pretend::middleware::compression::{Compression, Encoding, RequestCompression};
+//! use pretend::middleware::MiddlewareClient;
+//! use pretend_reqwest::Client;
+//!
+//! let compression = RequestCompression::new(Encoding::Gzip).with_min_size(4096);
+//! let compression = Compression::new()
+//!     .with_trait_request_compression("UploadApi", Some(compression));
+//! let client = MiddlewareClient::new(Client::default(), compression);
+//! # }
+//! ```
+
+use super::{After, Before, Middleware, Request};
+use crate::client::Bytes;
+use crate::{Endpoint, Error, HeaderMap, Response, Result};
+use http::header::{ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_LENGTH};
+use http::HeaderValue;
+use std::collections::HashMap;
+use std::io;
+use thiserror::Error;
+
+// Default maximum size of decoded bodies
+const MAX_DECODED_SIZE: usize = 64 * 1024 * 1024;
+
+/// Content encoding
+///
+/// Available encodings depend on the enabled features.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Encoding {
+    /// `gzip`
+    #[cfg(feature = "gzip")]
+    Gzip,
+    /// `deflate`, using the zlib format
+    #[cfg(feature = "gzip")]
+    Deflate,
+    /// `br`
+    #[cfg(feature = "brotli")]
+    Brotli,
+    /// `zstd`
+    #[cfg(feature = "zstd")]
+    Zstd,
+}
+
+impl Encoding {
+    const ALL: &'static [Encoding] = &[
+        #[cfg(feature = "gzip")]
+        Encoding::Gzip,
+        #[cfg(feature = "gzip")]
+        Encoding::Deflate,
+        #[cfg(feature = "brotli")]
+        Encoding::Brotli,
+        #[cfg(feature = "zstd")]
+        Encoding::Zstd,
+    ];
+
+    /// Name of this encoding, as used in HTTP headers
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            #[cfg(feature = "gzip")]
+            Encoding::Gzip => "gzip",
+            #[cfg(feature = "gzip")]
+            Encoding::Deflate => "deflate",
+            #[cfg(feature = "brotli")]
+            Encoding::Brotli => "br",
+            #[cfg(feature = "zstd")]
+            Encoding::Zstd => "zstd",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Encoding> {
+        let value = value.trim();
+        if value.eq_ignore_ascii_case("x-gzip") {
+            return Encoding::parse("gzip");
+        }
+        let mut encodings = Encoding::ALL.iter();
+        let encoding = encodings.find(|encoding| value.eq_ignore_ascii_case(encoding.as_str()));
+        encoding.copied()
+    }
+
+    fn encode(&self, data: &[u8]) -> io::Result<Vec<u8>> {
+        codecs::encode(*self, data)
+    }
+
+    fn decode(&self, data: &[u8], max_size: usize) -> Result<Vec<u8>> {
+        // Read one more byte to detect bodies that are too large
+        let limit = (max_size as u64).saturating_add(1);
+        let output = codecs::decode(*self, data, limit).map_err(Error::body)?;
+        if output.len() > max_size {
+            return Err(Error::body(DecodedBodyTooLarge { max_size }));
+        }
+        Ok(output)
+    }
+}
+
+/// Request compression
+///
+/// Request bodies are compressed with `encoding` when they are at least
+/// `min_size` bytes long. The default minimum size is 1 KiB.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct RequestCompression {
+    encoding: Encoding,
+    min_size: usize,
+}
+
+impl RequestCompression {
+    /// Constructor
+    pub fn new(encoding: Encoding) -> Self {
+        RequestCompression {
+            encoding,
+            min_size: 1024,
+        }
+    }
+
+    /// Set the minimum size of compressed bodies
+    pub fn with_min_size(mut self, min_size: usize) -> Self {
+        self.min_size = min_size;
+        self
+    }
+
+    /// Encoding
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// Minimum size of compressed bodies
+    pub fn min_size(&self) -> usize {
+        self.min_size
+    }
+}
+
+/// Decoded body too large error
+///
+/// This error is returned when a decoded response
+/// body is larger than the configured maximum size.
+#[derive(Clone, Copy, Debug, Error)]
+#[error("Decoded body is larger than {max_size} bytes")]
+pub struct DecodedBodyTooLarge {
+    max_size: usize,
+}
+
+impl DecodedBodyTooLarge {
+    /// Maximum size of decoded bodies
+    pub fn max_size(&self) -> usize {
+        self.max_size
+    }
+}
+
+/// Compression middleware
+///
+/// See module level documentation for more information.
+#[derive(Clone, Debug)]
+pub struct Compression {
+    request_compression: Option<RequestCompression>,
+    trait_request_compressions: HashMap<&'static str, Option<RequestCompression>>,
+    endpoint_request_compressions: HashMap<Endpoint, Option<RequestCompression>>,
+    max_decoded_size: usize,
+}
+
+impl Default for Compression {
+    fn default() -> Self {
+        Compression {
+            request_compression: None,
+            trait_request_compressions: HashMap::new(),
+            endpoint_request_compressions: HashMap::new(),
+            max_decoded_size: MAX_DECODED_SIZE,
+        }
+    }
+}
+
+impl Compression {
+    /// Constructor
+    ///
+    /// This constructor creates a middleware that decodes
+    /// responses, but does not compress requests.
+    pub fn new() -> Self {
+        Compression::default()
+    }
+
+    /// Set the default request compression
+    ///
+    /// This compression is used for traits and endpoints
+    /// without a specific compression.
+    pub fn with_request_compression(mut self, compression: RequestCompression) -> Self {
+        self.request_compression = Some(compression);
+        self
+    }
+
+    /// Set the request compression for a trait
+    ///
+    /// `None` disables request compression for this trait.
+    pub fn with_trait_request_compression(
+        mut self,
+        trait_name: &'static str,
+        compression: Option<RequestCompression>,
+    ) -> Self {
+        self.trait_request_compressions
+            .insert(trait_name, compression);
+        self
+    }
+
+    /// Set the request compression for an endpoint
+    ///
+    /// `None` disables request compression for this endpoint.
+    pub fn with_endpoint_request_compression(
+        mut self,
+        endpoint: Endpoint,
+        compression: Option<RequestCompression>,
+    ) -> Self {
+        self.endpoint_request_compressions
+            .insert(endpoint, compression);
+        self
+    }
+
+    /// Set the maximum size of decoded response bodies
+    pub fn with_max_decoded_size(mut self, max_decoded_size: usize) -> Self {
+        self.max_decoded_size = max_decoded_size;
+        self
+    }
+
+    fn request_compression(&self, request: &Request) -> Option<RequestCompression> {
+        if let Some(endpoint) = request.endpoint.as_ref() {
+            if let Some(compression) = self.endpoint_request_compressions.get(endpoint) {
+                return *compression;
+            }
+            let trait_name = endpoint.trait_name();
+            if let Some(compression) = self.trait_request_compressions.get(trait_name) {
+                return *compression;
+            }
+        }
+        self.request_compression
+    }
+
+    fn compress(&self, request: &mut Request) -> Result<()> {
+        let compression = self.request_compression(request);
+        let body = request.body.as_ref();
+        let (compression, body) = match (compression, body) {
+            (Some(compression), Some(body)) if body.len() >= compression.min_size => {
+                (compression, body)
+            }
+            _ => return Ok(()),
+        };
+        if request.headers.contains_key(CONTENT_ENCODING) {
+            return Ok(());
+        }
+
+        let encoding = compression.encoding;
+        let body = encoding.encode(body).map_err(Error::request)?;
+        let value = HeaderValue::from_static(encoding.as_str());
+        request.headers.insert(CONTENT_ENCODING, value);
+        request.headers.remove(CONTENT_LENGTH);
+        request.body = Some(Bytes::from(body));
+        Ok(())
+    }
+}
+
+impl Middleware for Compression {
+    type State = ();
+
+    fn before(&self, request: &mut Request) -> Result<Before<()>> {
+        if !Encoding::ALL.is_empty() && !request.headers.contains_key(ACCEPT_ENCODING) {
+            let encodings = Encoding::ALL.iter().map(Encoding::as_str);
+            let value = encodings.collect::<Vec<_>>().join(", ");
+            let value = HeaderValue::from_str(&value).map_err(Error::request)?;
+            request.headers.insert(ACCEPT_ENCODING, value);
+        }
+        self.compress(request)?;
+        Ok(Before::Execute(()))
+    }
+
+    fn after(&self, _: &mut (), _: &Request, response: Result<Response<Bytes>>) -> Result<After> {
+        let response = decode(response?, self.max_decoded_size)?;
+        Ok(After::Respond(response))
+    }
+}
+
+fn decode(response: Response<Bytes>, max_size: usize) -> Result<Response<Bytes>> {
+    let encodings = match content_encodings(response.headers()) {
+        Some(encodings) if !encodings.is_empty() => encodings,
+        _ => return Ok(response),
+    };
+    if response.body().is_empty() {
+        return Ok(response);
+    }
+
+    let mut body = response.body().to_vec();
+    // Encodings are listed in the order they were applied
+    for encoding in encodings.iter().rev() {
+        body = encoding.decode(&body, max_size)?;
+    }
+
+    let mut response = response.with_body(Bytes::from(body));
+    response.headers.remove(CONTENT_ENCODING);
+    response.headers.remove(CONTENT_LENGTH);
+    Ok(response)
+}
+
+/// Parse `Content-Encoding`
+///
+/// Returns `None` if an encoding is not supported.
+fn content_encodings(headers: &HeaderMap) -> Option<Vec<Encoding>> {
+    let mut encodings = Vec::new();
+    for value in headers.get_all(CONTENT_ENCODING) {
+        let value = value.to_str().ok()?;
+        for encoding in value.split(',') {
+            if encoding.trim().eq_ignore_ascii_case("identity") {
+                continue;
+            }
+            encodings.push(Encoding::parse(encoding)?);
+        }
+    }
+    Some(encodings)
+}
+
+#[cfg(any(feature = "gzip", feature = "brotli", feature = "zstd"))]
+mod codecs {
+    use super::Encoding;
+    use std::io;
+
+    // Parameters used by brotli encoders
+    #[cfg(feature = "brotli")]
+    const BROTLI_QUALITY: u32 = 5;
+    #[cfg(feature = "brotli")]
+    const BROTLI_LG_WINDOW_SIZE: u32 = 22;
+    #[cfg(feature = "brotli")]
+    const BROTLI_BUFFER_SIZE: usize = 4096;
+    #[cfg(feature = "zstd")]
+    const ZSTD_LEVEL: i32 = 3;
+
+    pub(super) fn encode(encoding: Encoding, data: &[u8]) -> io::Result<Vec<u8>> {
+        match encoding {
+            #[cfg(feature = "gzip")]
+            Encoding::Gzip => {
+                use std::io::Write;
+                let level = flate2::Compression::default();
+                let mut encoder = flate2::write::GzEncoder::new(Vec::new(), level);
+                encoder.write_all(data)?;
+                encoder.finish()
+            }
+            #[cfg(feature = "gzip")]
+            Encoding::Deflate => {
+                use std::io::Write;
+                let level = flate2::Compression::default();
+                let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), level);
+                encoder.write_all(data)?;
+                encoder.finish()
+            }
+            #[cfg(feature = "brotli")]
+            Encoding::Brotli => {
+                use std::io::Write;
+                let mut output = Vec::new();
+                {
+                    let mut encoder = brotli::CompressorWriter::new(
+                        &mut output,
+                        BROTLI_BUFFER_SIZE,
+                        BROTLI_QUALITY,
+                        BROTLI_LG_WINDOW_SIZE,
+                    );
+                    encoder.write_all(data)?;
+                }
+                Ok(output)
+            }
+            #[cfg(feature = "zstd")]
+            Encoding::Zstd => zstd::stream::encode_all(data, ZSTD_LEVEL),
+        }
+    }
+
+    // Decode at most limit bytes
+    pub(super) fn decode(encoding: Encoding, data: &[u8], limit: u64) -> io::Result<Vec<u8>> {
+        match encoding {
+            #[cfg(feature = "gzip")]
+            Encoding::Gzip => read(flate2::read::MultiGzDecoder::new(data), limit),
+            #[cfg(feature = "gzip")]
+            Encoding::Deflate => read(flate2::read::ZlibDecoder::new(data), limit),
+            #[cfg(feature = "brotli")]
+            Encoding::Brotli => read(brotli::Decompressor::new(data, BROTLI_BUFFER_SIZE), limit),
+            #[cfg(feature = "zstd")]
+            Encoding::Zstd => read(zstd::stream::read::Decoder::new(data)?, limit),
+        }
+    }
+
+    fn read<R>(reader: R, limit: u64) -> io::Result<Vec<u8>>
+    where
+        R: io::Read,
+    {
+        use std::io::Read;
+        let mut output = Vec::new();
+        reader.take(limit).read_to_end(&mut output)?;
+        Ok(output)
+    }
+}
+
+#[cfg(not(any(feature = "gzip", feature = "brotli", feature = "zstd")))]
+mod codecs {
+    use super::Encoding;
+    use std::io;
+
+    pub(super) fn encode(encoding: Encoding, _: &[u8]) -> io::Result<Vec<u8>> {
+        match encoding {}
+    }
+
+    pub(super) fn decode(encoding: Encoding, _: &[u8], _: u64) -> io::Result<Vec<u8>> {
+        match encoding {}
+    }
+}
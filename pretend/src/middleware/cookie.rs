@@ -0,0 +1,449 @@
+//! Cookie middleware
+//!
+//! [`CookieJar`] stores the cookies set by servers with `Set-Cookie`, and sends
+//! them back with the `Cookie` header, following the rules of RFC 6265. Services
+//! relying on session cookies can then be used independently of the HTTP client.
+//!
+//! Cookies are only sent to the domain and the path they were set for. A cookie
+//! without a `Domain` attribute is only sent to the host that set it. Cookies with
+//! the `Secure` attribute are only accepted and sent over `https`. Expired cookies
+//! are removed, and servers can remove a cookie by setting it again with an expiry
+//! in the past. A public suffix list is not used, so cookies can be set for any
+//! parent domain containing a dot.
+//!
+//! Cookie jars are cheap to clone. Clones share the same cookies, allowing a jar
+//! to be shared by several clients, and then by several `Pretend` instances.
+//!
+//! A jar can be saved to a file with [`CookieJar::save`] and loaded again with
+//! [`CookieJar::load`]. Session cookies, that do not have an expiry, are saved too.
+//!
+//! ```rust
+//! use pretend::middleware::cookie::CookieJar;
+//! use pretend::middleware::MiddlewareClient;
+//! use pretend::{Pretend, Url};
+//! use pretend_reqwest::Client;
+//! use std::env;
+//!
+//! let path = env::temp_dir().join("cookies.json");
+//! let jar = CookieJar::load(&path).unwrap_or_default();
+//!
+//! // Both instances use the same cookies
+//! let url = Url::parse("https://httpbin.org").unwrap();
+//! let client = MiddlewareClient::new(Client::default(), jar.clone());
+//! let first = Pretend::for_client(client).with_url(url.clone());
+//! let client = MiddlewareClient::new(Client::default(), jar.clone());
+//! let second = Pretend::for_client(client).with_url(url);
+//!
+//! jar.save(&path).unwrap();
+//! ```
+//!
+//! When used with [`Redirect`](super::redirect::Redirect), the cookie jar should be
+//! wrapped by the redirect middleware, so that cookies are stored and sent for every
+//! redirect.
+
+use super::{After, Before, Middleware, Request};
+use crate::client::Bytes;
+use crate::{Error, Response, Result, Url};
+use http::header::{COOKIE, SET_COOKIE};
+use http::HeaderValue;
+use serde_json::{json, Value};
+use std::cmp::Reverse;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use url::Host;
+
+/// A cookie stored in a [`CookieJar`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Cookie {
+    name: String,
+    value: String,
+    domain: String,
+    host_only: bool,
+    path: String,
+    secure: bool,
+    http_only: bool,
+    expires: Option<SystemTime>,
+}
+
+impl Cookie {
+    /// Name of the cookie
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Value of the cookie
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Domain the cookie is sent to
+    pub fn domain(&self) -> &str {
+        &self.domain
+    }
+
+    /// Check if the cookie is only sent to its domain, and not to its subdomains
+    pub fn is_host_only(&self) -> bool {
+        self.host_only
+    }
+
+    /// Path the cookie is sent to
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Check if the cookie is only sent over `https`
+    pub fn is_secure(&self) -> bool {
+        self.secure
+    }
+
+    /// Check if the cookie has the `HttpOnly` attribute
+    pub fn is_http_only(&self) -> bool {
+        self.http_only
+    }
+
+    /// Expiry of the cookie
+    ///
+    /// Session cookies do not have an expiry.
+    pub fn expires(&self) -> Option<SystemTime> {
+        self.expires
+    }
+
+    fn is_expired(&self, now: SystemTime) -> bool {
+        matches!(self.expires, Some(expires) if expires <= now)
+    }
+
+    fn matches(&self, url: &Url, now: SystemTime) -> bool {
+        let domain_matches = match host(url) {
+            Some((host, _)) if self.host_only => host == self.domain,
+            Some((host, is_ip)) => domain_matches(&host, is_ip, &self.domain),
+            None => false,
+        };
+        domain_matches
+            && path_matches(url.path(), &self.path)
+            && (!self.secure || url.scheme() == "https")
+            && !self.is_expired(now)
+    }
+
+    fn to_json(&self) -> Value {
+        let expires = self.expires.map(|expires| {
+            let expires = expires.duration_since(UNIX_EPOCH).unwrap_or_default();
+            expires.as_millis() as u64
+        });
+        json!({
+            "name": self.name,
+            "value": self.value,
+            "domain": self.domain,
+            "host_only": self.host_only,
+            "path": self.path,
+            "secure": self.secure,
+            "http_only": self.http_only,
+            "expires": expires,
+        })
+    }
+
+    fn from_json(value: &Value) -> Option<Self> {
+        let expires = match &value["expires"] {
+            Value::Null => None,
+            expires => Some(UNIX_EPOCH + Duration::from_millis(expires.as_u64()?)),
+        };
+        Some(Cookie {
+            name: value["name"].as_str()?.to_string(),
+            value: value["value"].as_str()?.to_string(),
+            domain: value["domain"].as_str()?.to_string(),
+            host_only: value["host_only"].as_bool()?,
+            path: value["path"].as_str()?.to_string(),
+            secure: value["secure"].as_bool()?,
+            http_only: value["http_only"].as_bool()?,
+            expires,
+        })
+    }
+}
+
+/// Cookie middleware
+///
+/// See module level documentation for more information.
+#[derive(Clone, Debug, Default)]
+pub struct CookieJar {
+    cookies: Arc<Mutex<Vec<Cookie>>>,
+}
+
+impl CookieJar {
+    /// Constructor
+    ///
+    /// The jar is empty.
+    pub fn new() -> Self {
+        CookieJar::default()
+    }
+
+    /// Load a jar from a file
+    ///
+    /// The file should have been written by [`CookieJar::save`].
+    /// Expired cookies are not loaded.
+    pub fn load<P>(path: P) -> io::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let content = fs::read(path)?;
+        let value: Value = serde_json::from_slice(&content)?;
+        let values = value.as_array().ok_or_else(invalid_data)?;
+
+        let now = SystemTime::now();
+        let mut cookies = Vec::new();
+        for value in values {
+            let cookie = Cookie::from_json(value).ok_or_else(invalid_data)?;
+            if !cookie.is_expired(now) {
+                cookies.push(cookie);
+            }
+        }
+        Ok(CookieJar {
+            cookies: Arc::new(Mutex::new(cookies)),
+        })
+    }
+
+    /// Save this jar to a file
+    ///
+    /// The file is replaced if it exists.
+    pub fn save<P>(&self, path: P) -> io::Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        let cookies = self.cookies().iter().map(Cookie::to_json).collect();
+        let content = serde_json::to_vec_pretty(&Value::Array(cookies))?;
+
+        // Write to a temporary file first, to never load a partial jar
+        let path = path.as_ref();
+        let temporary = path.with_extension(format!("{}-{}.tmp", process::id(), timestamp()));
+        fs::write(&temporary, content)?;
+        let result = fs::rename(&temporary, path);
+        if result.is_err() {
+            let _ = fs::remove_file(&temporary);
+        }
+        result
+    }
+
+    /// Stored cookies
+    ///
+    /// Expired cookies are not returned.
+    pub fn cookies(&self) -> Vec<Cookie> {
+        let mut cookies = self.lock();
+        remove_expired(&mut cookies, SystemTime::now());
+        cookies.clone()
+    }
+
+    /// Store a cookie
+    ///
+    /// The cookie is parsed from the value of a `Set-Cookie` header,
+    /// received in a response to a request to `url`. Invalid cookies,
+    /// or cookies that `url` is not allowed to set, are ignored.
+    pub fn set_cookie(&self, url: &Url, set_cookie: &str) {
+        let now = SystemTime::now();
+        let cookie = match parse_set_cookie(url, set_cookie, now) {
+            Some(cookie) => cookie,
+            None => return,
+        };
+
+        let mut cookies = self.lock();
+        remove_expired(&mut cookies, now);
+        let existing = cookies.iter().position(|stored| {
+            stored.name == cookie.name
+                && stored.domain == cookie.domain
+                && stored.path == cookie.path
+        });
+        // Expired cookies are used to remove stored cookies
+        match existing {
+            Some(index) if cookie.is_expired(now) => {
+                cookies.remove(index);
+            }
+            Some(index) => cookies[index] = cookie,
+            None if cookie.is_expired(now) => {}
+            None => cookies.push(cookie),
+        }
+    }
+
+    /// Remove all cookies
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    // Cookies are sorted by path length, and then by
+    // creation time, as recommended by RFC 6265
+    fn header(&self, url: &Url) -> Option<String> {
+        let now = SystemTime::now();
+        let cookies = self.lock();
+        let mut cookies = cookies
+            .iter()
+            .filter(|cookie| cookie.matches(url, now))
+            .collect::<Vec<_>>();
+        if cookies.is_empty() {
+            return None;
+        }
+
+        cookies.sort_by_key(|cookie| Reverse(cookie.path.len()));
+        let cookies = cookies
+            .iter()
+            .map(|cookie| format!("{}={}", cookie.name, cookie.value))
+            .collect::<Vec<_>>();
+        Some(cookies.join("; "))
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<Cookie>> {
+        self.cookies.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Middleware for CookieJar {
+    type State = ();
+
+    fn before(&self, request: &mut Request) -> Result<Before<()>> {
+        if let Some(header) = self.header(&request.url) {
+            // Cookies that are already set are kept
+            let header = match request.headers.get(COOKIE) {
+                Some(cookie) => {
+                    let cookie = cookie.to_str().map_err(Error::request)?;
+                    format!("{}; {}", cookie, header)
+                }
+                None => header,
+            };
+            let header = HeaderValue::from_str(&header).map_err(Error::request)?;
+            request.headers.insert(COOKIE, header);
+        }
+        Ok(Before::Execute(()))
+    }
+
+    fn after(
+        &self,
+        _: &mut (),
+        request: &Request,
+        response: Result<Response<Bytes>>,
+    ) -> Result<After> {
+        let response = response?;
+        let values = response.headers().get_all(SET_COOKIE).iter();
+        for value in values.filter_map(|value| value.to_str().ok()) {
+            self.set_cookie(&request.url, value);
+        }
+        Ok(After::Respond(response))
+    }
+}
+
+fn parse_set_cookie(url: &Url, set_cookie: &str, now: SystemTime) -> Option<Cookie> {
+    let (host, is_ip) = host(url)?;
+    let mut parts = set_cookie.split(';');
+    let pair = parts.next()?;
+    let index = pair.find('=')?;
+    let name = pair[..index].trim();
+    if name.is_empty() {
+        return None;
+    }
+
+    let mut cookie = Cookie {
+        name: name.to_string(),
+        value: pair[index + 1..].trim().to_string(),
+        domain: host.clone(),
+        host_only: true,
+        path: default_path(url),
+        secure: false,
+        http_only: false,
+        expires: None,
+    };
+    let mut domain = None;
+    let mut expires = None;
+    let mut max_age = None;
+
+    for attribute in parts {
+        let (name, value) = match attribute.find('=') {
+            Some(index) => (&attribute[..index], attribute[index + 1..].trim()),
+            None => (attribute, ""),
+        };
+        match name.trim().to_ascii_lowercase().as_str() {
+            "expires" => expires = parse_date(value).or(expires),
+            "max-age" => max_age = value.parse::<i64>().ok().or(max_age),
+            "domain" => {
+                let value = value.trim_start_matches('.').to_ascii_lowercase();
+                if !value.is_empty() {
+                    domain = Some(value);
+                }
+            }
+            "path" if value.starts_with('/') => cookie.path = value.to_string(),
+            "secure" => cookie.secure = true,
+            "httponly" => cookie.http_only = true,
+            _ => {}
+        }
+    }
+
+    // Max-Age takes precedence over Expires
+    cookie.expires = match max_age {
+        Some(max_age) if max_age <= 0 => Some(UNIX_EPOCH),
+        Some(max_age) => now.checked_add(Duration::from_secs(max_age as u64)),
+        None => expires,
+    };
+
+    if let Some(domain) = domain {
+        let allowed = domain.contains('.') || domain == host;
+        if !allowed || !domain_matches(&host, is_ip, &domain) {
+            return None;
+        }
+        cookie.domain = domain;
+        cookie.host_only = false;
+    }
+
+    if cookie.secure && url.scheme() != "https" {
+        return None;
+    }
+    Some(cookie)
+}
+
+// Host of an URL, and whether it is an IP address
+fn host(url: &Url) -> Option<(String, bool)> {
+    let host = url.host()?;
+    let is_ip = !matches!(host, Host::Domain(_));
+    Some((host.to_string().to_ascii_lowercase(), is_ip))
+}
+
+fn domain_matches(host: &str, is_ip: bool, domain: &str) -> bool {
+    if host == domain {
+        return true;
+    }
+    !is_ip && host.ends_with(domain) && host.as_bytes()[host.len() - domain.len() - 1] == b'.'
+}
+
+fn path_matches(path: &str, cookie_path: &str) -> bool {
+    if path == cookie_path {
+        return true;
+    }
+    path.starts_with(cookie_path)
+        && (cookie_path.ends_with('/') || path[cookie_path.len()..].starts_with('/'))
+}
+
+// Directory of the request path
+fn default_path(url: &Url) -> String {
+    let path = url.path();
+    match path.rfind('/') {
+        Some(index) if index > 0 && path.starts_with('/') => path[..index].to_string(),
+        _ => "/".to_string(),
+    }
+}
+
+// Cookie dates are usually HTTP dates, but may also use dashes
+// to separate the day, the month and the year
+fn parse_date(value: &str) -> Option<SystemTime> {
+    let date = httpdate::parse_http_date(value);
+    let date = date.or_else(|_| httpdate::parse_http_date(&value.replace('-', " ")));
+    date.ok()
+}
+
+fn remove_expired(cookies: &mut Vec<Cookie>, now: SystemTime) {
+    cookies.retain(|cookie| !cookie.is_expired(now));
+}
+
+fn invalid_data() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "Invalid cookie jar")
+}
+
+fn timestamp() -> u128 {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH);
+    now.unwrap_or_default().as_nanos()
+}
@@ -0,0 +1,367 @@
+//! Rate limiting middleware
+//!
+//! [`RateLimiter`] limits the rate of requests sent to each host using
+//! token buckets. Each host has its own bucket, configured with a [`Quota`].
+//! A default quota can be used for all hosts, and specific quotas can be
+//! configured for some hosts.
+//!
+//! Quotas can also be configured for some endpoints. Requests to these
+//! endpoints use a bucket for the endpoint instead of the bucket for the host.
+//! Endpoints are only known when requests are executed by `pretend`.
+//!
+//! When the rate limit is exceeded, the rate limiter waits until a token is
+//! available. It can also be configured to fail fast. In this case, a
+//! [`RateLimitError`] is returned as an `Error::Request`.
+//!
+//! Rate limiters are cheap to clone. Clones share the same buckets, allowing
+//! a rate limiter to be shared by several clients.
+//!
+//! # Adaptive rate limiting
+//!
+//! A rate limiter can adapt to the rate limiting headers sent by servers.
+//! When enabled, the following headers are used:
+//!
+//! - `X-RateLimit-Remaining` reduces the number of available tokens.
+//!   When it reaches 0, requests are delayed until `X-RateLimit-Reset`.
+//! - `Retry-After`, sent with `429 Too Many Requests` or `503 Service Unavailable`,
+//!   delays requests until the indicated time.
+//!
+//! `X-RateLimit-Reset` is either a number of seconds, or a Unix timestamp.
+//! Delays requested by servers are capped to one day.
+//!
+//! ```rust
+//! use pretend::middleware::ratelimit::{Quota, RateLimiter};
+//! use pretend::middleware::MiddlewareClient;
+//! use pretend_reqwest::Client;
+//!
+//! let limiter = RateLimiter::new()
+//!     .with_quota(Quota::per_second(10))
+//!     .with_host_quota("api.github.com", Quota::per_minute(60))
+//!     .with_adaptive(true);
+//! let client = MiddlewareClient::new(Client::default(), limiter);
+//! ```
+
+use super::{After, Before, Middleware, Request};
+use crate::client::Bytes;
+use crate::{Endpoint, Error, HeaderMap, Response, Result, StatusCode};
+use http::header::RETRY_AFTER;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+const RATE_LIMIT_REMAINING: &str = "x-ratelimit-remaining";
+const RATE_LIMIT_RESET: &str = "x-ratelimit-reset";
+// Values of X-RateLimit-Reset above this threshold are Unix timestamps
+const RATE_LIMIT_RESET_TIMESTAMP: u64 = 1_000_000_000;
+// Longest delay that a server can request
+const MAX_BLOCK: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Token bucket quota
+///
+/// A quota allows `capacity` requests every `period`. Tokens are
+/// refilled continuously, and up to `capacity` requests can be sent
+/// at once.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct Quota {
+    capacity: u32,
+    period: Duration,
+}
+
+impl Quota {
+    /// Constructor
+    ///
+    /// # Panics
+    ///
+    /// This constructor panics if `capacity` or `period` is zero.
+    pub fn new(capacity: u32, period: Duration) -> Self {
+        assert!(capacity > 0, "Quota capacity must not be zero");
+        assert!(
+            period > Duration::from_secs(0),
+            "Quota period must not be zero"
+        );
+        Quota { capacity, period }
+    }
+
+    /// Quota allowing `capacity` requests per second
+    pub fn per_second(capacity: u32) -> Self {
+        Quota::new(capacity, Duration::from_secs(1))
+    }
+
+    /// Quota allowing `capacity` requests per minute
+    pub fn per_minute(capacity: u32) -> Self {
+        Quota::new(capacity, Duration::from_secs(60))
+    }
+
+    /// Number of requests allowed per period
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    /// Period
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+
+    fn tokens_per_sec(&self) -> f64 {
+        f64::from(self.capacity) / self.period.as_secs_f64()
+    }
+}
+
+/// Rate limit error
+///
+/// This error is returned when the rate limit
+/// is exceeded and the rate limiter fails fast.
+#[derive(Clone, Copy, Debug, Error)]
+#[error("Rate limit exceeded, retry after {retry_after:?}")]
+pub struct RateLimitError {
+    retry_after: Duration,
+}
+
+impl RateLimitError {
+    /// Duration to wait before the next request is allowed
+    pub fn retry_after(&self) -> Duration {
+        self.retry_after
+    }
+}
+
+/// Rate limiting middleware
+///
+/// See module level documentation for more information.
+#[derive(Clone, Debug, Default)]
+pub struct RateLimiter {
+    quota: Option<Quota>,
+    host_quotas: HashMap<String, Quota>,
+    endpoint_quotas: HashMap<Endpoint, Quota>,
+    fail_fast: bool,
+    adaptive: bool,
+    buckets: Arc<Mutex<HashMap<BucketKey, Bucket>>>,
+}
+
+impl RateLimiter {
+    /// Constructor
+    ///
+    /// This constructor creates a rate limiter without any quota.
+    pub fn new() -> Self {
+        RateLimiter::default()
+    }
+
+    /// Set the default quota
+    ///
+    /// This quota is used for hosts without a specific quota.
+    pub fn with_quota(mut self, quota: Quota) -> Self {
+        self.quota = Some(quota);
+        self
+    }
+
+    /// Set the quota for a host
+    pub fn with_host_quota<H>(mut self, host: H, quota: Quota) -> Self
+    where
+        H: Into<String>,
+    {
+        self.host_quotas.insert(host.into(), quota);
+        self
+    }
+
+    /// Set the quota for an endpoint
+    pub fn with_endpoint_quota(mut self, endpoint: Endpoint, quota: Quota) -> Self {
+        self.endpoint_quotas.insert(endpoint, quota);
+        self
+    }
+
+    /// Fail fast instead of waiting when the rate limit is exceeded
+    pub fn with_fail_fast(mut self, fail_fast: bool) -> Self {
+        self.fail_fast = fail_fast;
+        self
+    }
+
+    /// Adapt to rate limiting headers sent by servers
+    pub fn with_adaptive(mut self, adaptive: bool) -> Self {
+        self.adaptive = adaptive;
+        self
+    }
+
+    fn bucket(&self, request: &Request) -> Option<(BucketKey, Option<Quota>)> {
+        let endpoint = request.endpoint.as_ref();
+        let endpoint_quota = endpoint.and_then(|endpoint| self.endpoint_quotas.get(endpoint));
+        if let (Some(endpoint), Some(quota)) = (endpoint, endpoint_quota) {
+            return Some((BucketKey::Endpoint(*endpoint), Some(*quota)));
+        }
+
+        let host = request.url.host_str()?;
+        let quota = self.host_quotas.get(host).or(self.quota.as_ref());
+        Some((BucketKey::Host(host.to_string()), quota.copied()))
+    }
+
+    fn with_bucket<F, T>(&self, key: BucketKey, quota: Option<Quota>, f: F) -> T
+    where
+        F: FnOnce(&mut Bucket) -> T,
+    {
+        let mut buckets = self.buckets.lock().unwrap_or_else(PoisonError::into_inner);
+        let bucket = buckets
+            .entry(key)
+            .or_insert_with(|| Bucket::new(quota.as_ref()));
+        f(bucket)
+    }
+
+    fn adapt(&self, request: &Request, status: &StatusCode, headers: &HeaderMap) {
+        let (key, quota) = match self.bucket(request) {
+            Some(bucket) => bucket,
+            None => return,
+        };
+
+        let now = Instant::now();
+        let remaining = parse_header::<u32>(headers, RATE_LIMIT_REMAINING);
+        let reset = parse_header::<u64>(headers, RATE_LIMIT_RESET).map(parse_reset);
+        let retry_after = match *status {
+            StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE => {
+                parse_retry_after(headers)
+            }
+            _ => None,
+        };
+
+        self.with_bucket(key, quota, |bucket| {
+            if let Some(remaining) = remaining {
+                bucket.limit(remaining);
+                if let (0, Some(reset)) = (remaining, reset) {
+                    bucket.block(block_until(now, reset));
+                }
+            }
+            if let Some(retry_after) = retry_after {
+                bucket.block(block_until(now, retry_after));
+            }
+        })
+    }
+}
+
+impl Middleware for RateLimiter {
+    type State = ();
+
+    fn before(&self, request: &mut Request) -> Result<Before<()>> {
+        let (key, quota) = match self.bucket(request) {
+            Some(bucket) => bucket,
+            None => return Ok(Before::Execute(())),
+        };
+
+        let now = Instant::now();
+        let wait = self.with_bucket(key, quota, |bucket| bucket.acquire(quota.as_ref(), now));
+        match wait {
+            None => Ok(Before::Execute(())),
+            Some(retry_after) if self.fail_fast => {
+                Err(Error::request(RateLimitError { retry_after }))
+            }
+            Some(retry_after) => Ok(Before::Wait(retry_after)),
+        }
+    }
+
+    fn after(
+        &self,
+        _: &mut (),
+        request: &Request,
+        response: Result<Response<Bytes>>,
+    ) -> Result<After> {
+        let response = response?;
+        if self.adaptive {
+            self.adapt(request, response.status(), response.headers());
+        }
+        Ok(After::Respond(response))
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+enum BucketKey {
+    Host(String),
+    Endpoint(Endpoint),
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+    blocked_until: Option<Instant>,
+}
+
+impl Bucket {
+    fn new(quota: Option<&Quota>) -> Self {
+        let tokens = quota.map(|quota| f64::from(quota.capacity)).unwrap_or(0.);
+        Bucket {
+            tokens,
+            updated: Instant::now(),
+            blocked_until: None,
+        }
+    }
+
+    // Returns the duration to wait if no token is available
+    fn acquire(&mut self, quota: Option<&Quota>, now: Instant) -> Option<Duration> {
+        if let Some(blocked_until) = self.blocked_until {
+            if now < blocked_until {
+                return Some(blocked_until - now);
+            }
+            self.blocked_until = None;
+        }
+
+        let quota = quota?;
+        self.refill(quota, now);
+        if self.tokens >= 1. {
+            self.tokens -= 1.;
+            None
+        } else {
+            let missing = 1. - self.tokens;
+            Some(Duration::from_secs_f64(missing / quota.tokens_per_sec()))
+        }
+    }
+
+    fn refill(&mut self, quota: &Quota, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated);
+        let tokens = self.tokens + elapsed.as_secs_f64() * quota.tokens_per_sec();
+        self.tokens = tokens.min(f64::from(quota.capacity));
+        self.updated = now;
+    }
+
+    fn limit(&mut self, remaining: u32) {
+        self.tokens = self.tokens.min(f64::from(remaining));
+    }
+
+    fn block(&mut self, until: Instant) {
+        let blocked_until = self
+            .blocked_until
+            .map_or(until, |current| current.max(until));
+        self.blocked_until = Some(blocked_until);
+    }
+}
+
+fn parse_header<T>(headers: &HeaderMap, name: &str) -> Option<T>
+where
+    T: std::str::FromStr,
+{
+    let value = headers.get(name)?.to_str().ok()?;
+    value.trim().parse().ok()
+}
+
+fn block_until(now: Instant, duration: Duration) -> Instant {
+    now + duration.min(MAX_BLOCK)
+}
+
+fn parse_reset(reset: u64) -> Duration {
+    if reset >= RATE_LIMIT_RESET_TIMESTAMP {
+        let reset = match UNIX_EPOCH.checked_add(Duration::from_secs(reset)) {
+            Some(reset) => reset,
+            None => return MAX_BLOCK,
+        };
+        let now = SystemTime::now();
+        reset.duration_since(now).unwrap_or_default()
+    } else {
+        Duration::from_secs(reset)
+    }
+}
+
+fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse() {
+        Some(Duration::from_secs(seconds))
+    } else {
+        let date = httpdate::parse_http_date(value).ok()?;
+        let now = SystemTime::now();
+        Some(date.duration_since(now).unwrap_or_default())
+    }
+}
@@ -0,0 +1,173 @@
+//! Redirect middleware
+//!
+//! [`Redirect`] follows redirects independently of the HTTP client being used.
+//! Responses with a `301`, `302`, `303`, `307` or `308` status and a `Location`
+//! header are followed, until a configured number of redirects is reached. Once
+//! this limit is exceeded, requests fail with an `Error::Response` wrapping
+//! [`TooManyRedirects`]. Only `http` and `https` locations are followed: redirects
+//! to other schemes, like `file`, are returned as is.
+//!
+//! The method of the request is changed as browsers do:
+//!
+//! - After a `303`, requests are sent with `GET`, without a body. `HEAD` requests are kept.
+//! - After a `301` or a `302`, `POST` requests are sent with `GET`, without a body.
+//! - After a `307` or a `308`, the method and the body are kept.
+//!
+//! When the body is dropped, the `Content-Type`, `Content-Length` and `Content-Encoding`
+//! headers are removed. When a redirect points to another origin, the `Authorization`,
+//! `Cookie` and `Proxy-Authorization` headers are removed, so that credentials do not
+//! leak to another server.
+//!
+//! The URL of the last request is available with [`Response::url`]. If the HTTP client
+//! also follows redirects, its own redirect policy should be disabled.
+//!
+//! ```rust
+//! use pretend::middleware::redirect::Redirect;
+//! use pretend::middleware::MiddlewareClient;
+//! use pretend::config::{ClientConfig, RedirectPolicy};
+//! use pretend_reqwest::Client;
+//!
+//! let config = ClientConfig::new().with_redirect_policy(RedirectPolicy::None);
+//! let client = Client::from_config(&config).unwrap();
+//! let client = MiddlewareClient::new(client, Redirect::new().with_limit(5));
+//! ```
+
+use super::{After, Before, Middleware, Request};
+use crate::client::{Bytes, Method};
+use crate::{Error, Response, Result, StatusCode, Url};
+use http::header::{
+    AUTHORIZATION, CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_TYPE, COOKIE, LOCATION,
+    PROXY_AUTHORIZATION,
+};
+use thiserror::Error;
+
+/// Redirect middleware
+///
+/// See module level documentation for more information.
+#[derive(Clone, Debug)]
+pub struct Redirect {
+    limit: usize,
+}
+
+impl Default for Redirect {
+    fn default() -> Self {
+        Redirect { limit: 10 }
+    }
+}
+
+impl Redirect {
+    /// Constructor
+    ///
+    /// At most 10 redirects are followed by default.
+    pub fn new() -> Self {
+        Redirect::default()
+    }
+
+    /// Set the maximum number of redirects to follow
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Maximum number of redirects to follow
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+}
+
+impl Middleware for Redirect {
+    // Number of redirects that were followed
+    type State = usize;
+
+    fn before(&self, _: &mut Request) -> Result<Before<usize>> {
+        Ok(Before::Execute(0))
+    }
+
+    fn after(
+        &self,
+        redirects: &mut usize,
+        request: &Request,
+        response: Result<Response<Bytes>>,
+    ) -> Result<After> {
+        let response = response?;
+        let url = match location(&request.url, &response) {
+            Some(url) => url,
+            None if response.url().is_some() => return Ok(After::Respond(response)),
+            None => return Ok(After::Respond(response.with_url(request.url.clone()))),
+        };
+
+        if *redirects >= self.limit {
+            return Err(Error::response(TooManyRedirects::new(self.limit)));
+        }
+        *redirects += 1;
+
+        let next = redirect_request(request, *response.status(), url);
+        Ok(After::Execute(next))
+    }
+}
+
+// URL to redirect to, if the response is a redirect
+fn location(base: &Url, response: &Response<Bytes>) -> Option<Url> {
+    match *response.status() {
+        StatusCode::MOVED_PERMANENTLY
+        | StatusCode::FOUND
+        | StatusCode::SEE_OTHER
+        | StatusCode::TEMPORARY_REDIRECT
+        | StatusCode::PERMANENT_REDIRECT => {}
+        _ => return None,
+    }
+
+    let location = response.headers().get(LOCATION)?;
+    let location = location.to_str().ok()?;
+    let url = base.join(location).ok()?;
+    match url.scheme() {
+        "http" | "https" => Some(url),
+        _ => None,
+    }
+}
+
+fn redirect_request(request: &Request, status: StatusCode, url: Url) -> Request {
+    let mut next = request.clone();
+
+    let method = &request.method;
+    let use_get = match status {
+        StatusCode::SEE_OTHER => method != Method::HEAD,
+        StatusCode::MOVED_PERMANENTLY | StatusCode::FOUND => method == Method::POST,
+        _ => false,
+    };
+    if use_get {
+        next.method = Method::GET;
+        next.body = None;
+        next.headers.remove(CONTENT_TYPE);
+        next.headers.remove(CONTENT_LENGTH);
+        next.headers.remove(CONTENT_ENCODING);
+    }
+
+    if url.origin() != request.url.origin() {
+        next.headers.remove(AUTHORIZATION);
+        next.headers.remove(COOKIE);
+        next.headers.remove(PROXY_AUTHORIZATION);
+    }
+
+    next.url = url;
+    next
+}
+
+/// Error when too many redirects are followed
+#[derive(Clone, Debug, Error)]
+#[error("Too many redirects, at most {limit} are followed")]
+pub struct TooManyRedirects {
+    limit: usize,
+}
+
+impl TooManyRedirects {
+    /// Constructor
+    pub fn new(limit: usize) -> Self {
+        TooManyRedirects { limit }
+    }
+
+    /// Maximum number of redirects that are followed
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+}
@@ -0,0 +1,406 @@
+//! OpenAPI documents
+//!
+//! `pretend` can describe annotated traits as OpenAPI 3 path items. This is
+//! enabled by the `openapi` feature, and by adding `openapi` to the `pretend`
+//! attribute. A marker type, named after the trait with a `Description` suffix,
+//! is then generated and implements [`DescribeApi`].
+//!
+//! ```rust
+//! use pretend::openapi::{Document, JsonSchema};
+//! use pretend::{pretend, Json, Result};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Deserialize, JsonSchema)]
+//! struct User {
+//!     id: i32,
+//!     name: String,
+//! }
+//!
+//! #[derive(Serialize, JsonSchema)]
+//! struct Filter {
+//!     name: Option<String>,
+//! }
+//!
+//! #[pretend(openapi)]
+//! trait UserApi {
+//!     #[request(method = "GET", path = "/users/{id}")]
+//!     async fn get_user(&self, id: i32) -> Result<Json<User>>;
+//!
+//!     #[request(method = "GET", path = "/users")]
+//!     async fn list_users(&self, query: &Filter) -> Result<Json<Vec<User>>>;
+//! }
+//!
+//! let document = Document::new("Users", "1.0").with_api::<UserApiDescription>();
+//! let json = serde_json::to_string_pretty(&document.to_value()).unwrap();
+//! ```
+//!
+//! Each method is described as an operation, identified by the method name and
+//! tagged with the trait name. Operations include:
+//!
+//! - path parameters, using the types of the method parameters,
+//! - query parameters, using the properties of the `query` type,
+//! - header parameters, for headers whose value is templated,
+//! - the request body, for `body`, `form` and `json`,
+//! - the responses, described by [`DescribeResponse`].
+//!
+//! Schemas are generated with [`schemars`], and types used as query parameters,
+//! bodies or JSON responses must implement [`JsonSchema`]. Named schemas are
+//! stored as components of the document.
+//!
+//! Types that depend on generic parameters of a method are chosen by the caller,
+//! and can't be described. Such path and query parameters are described as strings,
+//! while such queries, bodies and responses are omitted.
+
+pub use crate::client::Method;
+pub use crate::Endpoint;
+pub use schemars;
+pub use schemars::JsonSchema;
+
+use crate::{Json, JsonResult, Response};
+use schemars::gen::{SchemaGenerator, SchemaSettings};
+use schemars::schema::{Schema, SchemaObject};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+
+const OPENAPI_VERSION: &str = "3.0.3";
+
+/// Describe an API
+///
+/// This trait is implemented by `pretend` for the marker types
+/// generated for traits annotated with `#[pretend(openapi)]`.
+pub trait DescribeApi {
+    /// Add the operations of this API to a document
+    fn describe(document: &mut Document);
+}
+
+/// Describe responses
+///
+/// This trait is implemented for the types that can be returned by `pretend`
+/// methods. It returns a map of responses, indexed by status code.
+pub trait DescribeResponse {
+    /// Describe responses
+    fn responses(document: &mut Document) -> Map<String, Value>;
+}
+
+/// OpenAPI document
+///
+/// See module level documentation for more information.
+#[derive(Clone, Debug)]
+pub struct Document {
+    title: String,
+    version: String,
+    generator: SchemaGenerator,
+    paths: BTreeMap<String, Map<String, Value>>,
+}
+
+impl Document {
+    /// Constructor
+    ///
+    /// This constructor creates an empty document, with an API title and version.
+    pub fn new<T, V>(title: T, version: V) -> Self
+    where
+        T: Into<String>,
+        V: Into<String>,
+    {
+        Document {
+            title: title.into(),
+            version: version.into(),
+            generator: SchemaGenerator::new(SchemaSettings::openapi3()),
+            paths: BTreeMap::new(),
+        }
+    }
+
+    /// Add the operations of an API
+    pub fn with_api<T>(mut self) -> Self
+    where
+        T: DescribeApi + ?Sized,
+    {
+        T::describe(&mut self);
+        self
+    }
+
+    /// Add an operation
+    pub fn add_operation(&mut self, operation: Operation) {
+        let path = operation.path().to_string();
+        let method = operation.method.as_str().to_lowercase();
+        let path_item = self.paths.entry(path).or_default();
+        path_item.insert(method, operation.into_value());
+    }
+
+    /// Schema of a type
+    ///
+    /// Named schemas are stored as components, and a reference is returned.
+    pub fn schema<T>(&mut self) -> Value
+    where
+        T: JsonSchema + ?Sized,
+    {
+        to_value(&self.generator.subschema_for::<T>())
+    }
+
+    /// Path items
+    pub fn paths(&self) -> Value {
+        let paths = self.paths.iter();
+        let paths = paths.map(|(path, item)| (path.clone(), Value::Object(item.clone())));
+        Value::Object(paths.collect())
+    }
+
+    /// Schemas stored as components
+    pub fn schemas(&self) -> Value {
+        let definitions = self.generator.definitions().iter();
+        let schemas = definitions.map(|(name, schema)| (name.clone(), to_value(schema)));
+        Value::Object(schemas.collect())
+    }
+
+    /// Full OpenAPI document
+    pub fn to_value(&self) -> Value {
+        json!({
+            "openapi": OPENAPI_VERSION,
+            "info": {
+                "title": self.title,
+                "version": self.version,
+            },
+            "paths": self.paths(),
+            "components": {
+                "schemas": self.schemas(),
+            },
+        })
+    }
+
+    fn properties<T>(&mut self) -> Vec<(String, Value, bool)>
+    where
+        T: JsonSchema + ?Sized,
+    {
+        let schema = self.generator.subschema_for::<T>();
+        let schema = self.generator.dereference(&schema).unwrap_or(&schema);
+        let object = match schema {
+            Schema::Object(SchemaObject {
+                object: Some(object),
+                ..
+            }) => object,
+            _ => return Vec::new(),
+        };
+
+        let properties = object.properties.iter();
+        let properties = properties.map(|(name, schema)| {
+            let required = object.required.contains(name);
+            (name.clone(), to_value(schema), required)
+        });
+        properties.collect()
+    }
+}
+
+/// OpenAPI operation
+///
+/// An operation describes a method of a `pretend` annotated trait.
+#[derive(Clone, Debug)]
+pub struct Operation {
+    endpoint: Endpoint,
+    method: Method,
+    parameters: Vec<Value>,
+    request_body: Option<Value>,
+    responses: Map<String, Value>,
+}
+
+impl Operation {
+    /// Constructor
+    pub fn new(endpoint: Endpoint, method: Method) -> Self {
+        Operation {
+            endpoint,
+            method,
+            parameters: Vec::new(),
+            request_body: None,
+            responses: Map::new(),
+        }
+    }
+
+    /// Add a path parameter
+    pub fn with_path_parameter<T>(self, document: &mut Document, name: &str) -> Self
+    where
+        T: JsonSchema + ?Sized,
+    {
+        let schema = document.schema::<T>();
+        self.with_parameter(name, "path", true, schema)
+    }
+
+    /// Add a query parameter
+    pub fn with_query_parameter<T>(self, document: &mut Document, name: &str) -> Self
+    where
+        T: JsonSchema + ?Sized,
+    {
+        let schema = document.schema::<T>();
+        self.with_parameter(name, "query", true, schema)
+    }
+
+    /// Add query parameters
+    ///
+    /// Each property of the query type is added as a query parameter.
+    pub fn with_query<T>(mut self, document: &mut Document) -> Self
+    where
+        T: JsonSchema + ?Sized,
+    {
+        for (name, schema, required) in document.properties::<T>() {
+            self = self.with_parameter(&name, "query", required, schema);
+        }
+        self
+    }
+
+    /// Add a header parameter
+    pub fn with_header_parameter(self, name: &str) -> Self {
+        let schema = json!({ "type": "string" });
+        self.with_parameter(name, "header", true, schema)
+    }
+
+    /// Set a raw request body
+    pub fn with_raw_body(self) -> Self {
+        let schema = json!({ "type": "string", "format": "binary" });
+        self.with_body("application/octet-stream", schema)
+    }
+
+    /// Set a form request body
+    pub fn with_form_body<T>(self, document: &mut Document) -> Self
+    where
+        T: JsonSchema + ?Sized,
+    {
+        let schema = document.schema::<T>();
+        self.with_body("application/x-www-form-urlencoded", schema)
+    }
+
+    /// Set a JSON request body
+    pub fn with_json_body<T>(self, document: &mut Document) -> Self
+    where
+        T: JsonSchema + ?Sized,
+    {
+        let schema = document.schema::<T>();
+        self.with_body("application/json", schema)
+    }
+
+    /// Set the responses
+    pub fn with_response<T>(mut self, document: &mut Document) -> Self
+    where
+        T: DescribeResponse,
+    {
+        self.responses = T::responses(document);
+        self
+    }
+
+    fn path(&self) -> &'static str {
+        let path = self.endpoint.path();
+        path.split('?').next().unwrap_or(path)
+    }
+
+    fn with_parameter(mut self, name: &str, location: &str, required: bool, schema: Value) -> Self {
+        self.parameters.push(json!({
+            "name": name,
+            "in": location,
+            "required": required,
+            "schema": schema,
+        }));
+        self
+    }
+
+    fn with_body(mut self, content_type: &str, schema: Value) -> Self {
+        self.request_body = Some(json!({
+            "required": true,
+            "content": {
+                content_type: { "schema": schema },
+            },
+        }));
+        self
+    }
+
+    fn into_value(self) -> Value {
+        let mut operation = Map::new();
+        let tags = json!([self.endpoint.trait_name()]);
+        operation.insert("tags".to_string(), tags);
+        let operation_id = json!(self.endpoint.name());
+        operation.insert("operationId".to_string(), operation_id);
+        if !self.parameters.is_empty() {
+            let parameters = Value::Array(self.parameters);
+            operation.insert("parameters".to_string(), parameters);
+        }
+        if let Some(request_body) = self.request_body {
+            operation.insert("requestBody".to_string(), request_body);
+        }
+        let mut responses = self.responses;
+        if responses.is_empty() {
+            let response = json!({ "description": "Response" });
+            responses.insert("default".to_string(), response);
+        }
+        operation.insert("responses".to_string(), Value::Object(responses));
+        Value::Object(operation)
+    }
+}
+
+impl DescribeResponse for () {
+    fn responses(_: &mut Document) -> Map<String, Value> {
+        success(json!({ "description": "Successful response" }))
+    }
+}
+
+impl DescribeResponse for String {
+    fn responses(_: &mut Document) -> Map<String, Value> {
+        let schema = json!({ "type": "string" });
+        success(content("Successful response", "text/plain", schema))
+    }
+}
+
+impl DescribeResponse for Vec<u8> {
+    fn responses(_: &mut Document) -> Map<String, Value> {
+        let schema = json!({ "type": "string", "format": "binary" });
+        let content_type = "application/octet-stream";
+        success(content("Successful response", content_type, schema))
+    }
+}
+
+impl<T> DescribeResponse for Json<T>
+where
+    T: DeserializeOwned + JsonSchema,
+{
+    fn responses(document: &mut Document) -> Map<String, Value> {
+        let schema = document.schema::<T>();
+        success(content("Successful response", "application/json", schema))
+    }
+}
+
+impl<T, E> DescribeResponse for JsonResult<T, E>
+where
+    T: DeserializeOwned + JsonSchema,
+    E: DeserializeOwned + JsonSchema,
+{
+    fn responses(document: &mut Document) -> Map<String, Value> {
+        let mut responses = Json::<T>::responses(document);
+        let schema = document.schema::<E>();
+        let response = content("Error response", "application/json", schema);
+        responses.insert("default".to_string(), response);
+        responses
+    }
+}
+
+impl<T> DescribeResponse for Response<T>
+where
+    T: DescribeResponse,
+{
+    fn responses(document: &mut Document) -> Map<String, Value> {
+        T::responses(document)
+    }
+}
+
+fn success(response: Value) -> Map<String, Value> {
+    let mut responses = Map::new();
+    responses.insert("200".to_string(), response);
+    responses
+}
+
+fn content(description: &str, content_type: &str, schema: Value) -> Value {
+    json!({
+        "description": description,
+        "content": {
+            content_type: { "schema": schema },
+        },
+    })
+}
+
+fn to_value(schema: &Schema) -> Value {
+    serde_json::to_value(schema).unwrap_or(Value::Null)
+}
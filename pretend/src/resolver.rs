@@ -4,26 +4,107 @@
 //! create a full URL from the path specified in the
 //! `request` attribute.
 //!
-//! By default [`UrlResolver`] is used to append the path
+//! By default [`UrlResolver`] is used to join the path
 //! to a base URL. This resolver is used in `[Pretend::with_url]`.
 //!
+//! [`UrlResolver`] follows the rules of [`Url::join`]. A base URL that
+//! contains a path, like `https://host/api/v2`, will lose this path when
+//! joined with an absolute path like `/users`. [`BasePathUrlResolver`]
+//! keeps the path of the base URL and appends the request path to it.
+//!
 //! You can implement your own resolvers to suit your needs. For
 //! example, you can delegate URL resolution to a load balancer.
 //! In this case, implement [`ResolveUrl`].
+//!
+//! # Resolution context
+//!
+//! Resolvers can also use the [`ResolveContext`] of a request, that contains
+//! the HTTP method, the path and the [`Endpoint`] being called. This allows
+//! routing requests depending on the method or the endpoint. Override
+//! [`ResolveUrl::resolve_url_with_context`] to use it.
+//!
+//! # Async resolvers
+//!
+//! Resolvers that need to perform I/O, like querying a service discovery
+//! mechanism, can implement [`AsyncResolveUrl`] instead. They can only be used
+//! with asynchronous clients.
+//!
+//! Traits implemented for local clients, with `#[pretend(?Send)]`, and blocking
+//! traits still use [`ResolveUrl`]. An [`AsyncResolveUrl`] is not supported for
+//! them.
+//!
+//! Every [`ResolveUrl`] implementation that is `Sync` is also an [`AsyncResolveUrl`].
 
+pub use crate::client::Method;
+pub use crate::Endpoint;
 pub use url::{ParseError, Url};
 
+use crate::client::async_trait;
+use crate::Error;
+
+/// URL resolution context
+///
+/// This struct describes the request an URL is resolved for.
+#[non_exhaustive]
+pub struct ResolveContext<'a> {
+    /// Request method
+    pub method: &'a Method,
+    /// Request path, after templating
+    pub path: &'a str,
+    /// Endpoint being called
+    pub endpoint: &'a Endpoint,
+}
+
+impl<'a> ResolveContext<'a> {
+    /// Constructor
+    pub fn new(method: &'a Method, path: &'a str, endpoint: &'a Endpoint) -> Self {
+        ResolveContext {
+            method,
+            path,
+            endpoint,
+        }
+    }
+}
+
 /// Describe an URL resolver
 ///
 /// See module level documentation for more information.
 pub trait ResolveUrl {
     /// Resolve an URL from a path
     fn resolve_url(&self, path: &str) -> Result<Url, ParseError>;
+
+    /// Resolve an URL from a resolution context
+    ///
+    /// By default, only the path is used to resolve the URL,
+    /// by calling [`ResolveUrl::resolve_url`].
+    fn resolve_url_with_context(&self, context: &ResolveContext) -> Result<Url, ParseError> {
+        self.resolve_url(context.path)
+    }
+}
+
+/// Describe an async URL resolver
+///
+/// See module level documentation for more information.
+#[async_trait]
+pub trait AsyncResolveUrl {
+    /// Resolve an URL from a resolution context
+    async fn resolve_url_async(&self, context: &ResolveContext<'_>) -> crate::Result<Url>;
+}
+
+#[async_trait]
+impl<R> AsyncResolveUrl for R
+where
+    R: ResolveUrl + Sync,
+{
+    async fn resolve_url_async(&self, context: &ResolveContext<'_>) -> crate::Result<Url> {
+        let result = self.resolve_url_with_context(context);
+        result.map_err(Error::request)
+    }
 }
 
 /// Default URL resolver
 ///
-/// This resolver appends the path to a base URL.
+/// This resolver joins the path to a base URL, using [`Url::join`].
 #[derive(Clone, Debug)]
 pub struct UrlResolver {
     base: Url,
@@ -42,6 +123,34 @@ impl ResolveUrl for UrlResolver {
     }
 }
 
+/// Base path URL resolver
+///
+/// This resolver appends the path to the path of a base URL.
+///
+/// Unlike [`UrlResolver`], the path of the base URL is always preserved,
+/// whether it ends with a `/` or not. With a base URL `https://host/api/v2`,
+/// the path `/users` resolves to `https://host/api/v2/users`.
+#[derive(Clone, Debug)]
+pub struct BasePathUrlResolver {
+    base: Url,
+}
+
+impl BasePathUrlResolver {
+    /// Constructor
+    pub fn new(base: Url) -> Self {
+        BasePathUrlResolver { base }
+    }
+}
+
+impl ResolveUrl for BasePathUrlResolver {
+    fn resolve_url(&self, path: &str) -> Result<Url, ParseError> {
+        let base_path = self.base.path().trim_end_matches('/');
+        let path = path.trim_start_matches('/');
+        let path = format!("{}/{}", base_path, path);
+        self.base.join(&path)
+    }
+}
+
 /// Invalid URL resolver
 ///
 /// This resolver is used when calling `[Pretend::for_client]`. It
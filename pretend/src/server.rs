@@ -0,0 +1,245 @@
+//! Stub servers
+//!
+//! `pretend` can generate a stub server for an annotated trait, to test the generated
+//! client against handlers running in the same process. This is enabled by adding
+//! `server` to the `pretend` attribute.
+//!
+//! ```rust
+//! use pretend::client::Bytes;
+//! use pretend::server::Reply;
+//! use pretend::{pretend, Json, Pretend, Result, Url};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Deserialize, Serialize)]
+//! struct User {
+//!     id: i32,
+//!     name: String,
+//! }
+//!
+//! #[pretend(server)]
+//! trait UserApi {
+//!     #[request(method = "GET", path = "/users/{id}")]
+//!     fn get_user(&self, id: i32) -> Result<Json<User>>;
+//! }
+//!
+//! struct Handler;
+//!
+//! impl UserApiHandler for Handler {
+//!     fn get_user(&self, id: i32) -> Reply {
+//!         let name = "Alice".to_string();
+//!         Reply::json(&User { id, name })
+//!     }
+//! }
+//!
+//! let url = Url::parse("http://localhost").unwrap();
+//! let pretend = Pretend::for_client(UserApiServer::new(Handler)).with_url(url);
+//! let user = pretend.get_user(1).unwrap();
+//! assert_eq!(user.name, "Alice");
+//! ```
+//!
+//! For a trait named `UserApi`, `pretend` generates
+//!
+//! - an `UserApiHandler` trait, with a handler for each method of `UserApi`,
+//! - an `UserApiServer` struct, that wraps an implementation of `UserApiHandler`.
+//!
+//! `UserApiServer` is a client implementation. It is a [`Client`], a [`LocalClient`] or a
+//! [`BlockingClient`], depending on the kind of trait. It matches requests against the paths
+//! declared in `request` and calls the corresponding handler. The end of the request path is
+//! matched, so the base URL can contain a path prefix.
+//!
+//! Handlers take the parameters of the method, as owned values:
+//!
+//! - templated parameters, in the path or in header values, are parsed with [`FromStr`],
+//! - `query`, `form` and `json` are deserialized using `serde`,
+//! - `body` is passed as [`Bytes`].
+//!
+//! Parameters whose type depends on generic parameters of the method are passed
+//! undecoded: templated parameters and `query` as `String`, `form` and `json` as [`Bytes`].
+//!
+//! Handlers return a [`Reply`]. When a request does not match any method, the stub server
+//! replies with `404 Not Found`, and when parameters or bodies can't be parsed, it replies
+//! with `400 Bad Request`.
+//!
+//! [`Client`]: crate::client::Client
+//! [`LocalClient`]: crate::client::LocalClient
+//! [`BlockingClient`]: crate::client::BlockingClient
+//! [`FromStr`]: std::str::FromStr
+
+use crate::client::Bytes;
+use crate::{HeaderMap, Response, StatusCode};
+use http::header::{HeaderName, HeaderValue, CONTENT_TYPE};
+use serde::Serialize;
+
+/// Reply of a stub server handler
+#[derive(Clone, Debug)]
+pub struct Reply {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+impl Reply {
+    /// Constructor
+    ///
+    /// This constructor creates a reply with a status and an empty body.
+    pub fn new(status: StatusCode) -> Self {
+        Reply {
+            status,
+            headers: HeaderMap::new(),
+            body: Bytes::new(),
+        }
+    }
+
+    /// Empty `200 OK` reply
+    pub fn ok() -> Self {
+        Reply::new(StatusCode::OK)
+    }
+
+    /// `200 OK` reply with a JSON body
+    ///
+    /// If the value can't be serialized, the reply is
+    /// an `500 Internal Server Error`.
+    pub fn json<T>(value: &T) -> Self
+    where
+        T: Serialize + ?Sized,
+    {
+        match serde_json::to_vec(value) {
+            Ok(body) => Reply::content(body, "application/json"),
+            Err(_) => Reply::new(StatusCode::INTERNAL_SERVER_ERROR),
+        }
+    }
+
+    /// `200 OK` reply with a text body
+    pub fn text<T>(text: T) -> Self
+    where
+        T: Into<String>,
+    {
+        Reply::content(text.into(), "text/plain; charset=utf-8")
+    }
+
+    /// `200 OK` reply with a raw body
+    pub fn bytes<T>(body: T) -> Self
+    where
+        T: Into<Bytes>,
+    {
+        Reply::content(body, "application/octet-stream")
+    }
+
+    /// Set the status
+    pub fn with_status(self, status: StatusCode) -> Self {
+        Reply { status, ..self }
+    }
+
+    /// Add a header
+    pub fn with_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.append(name, value);
+        self
+    }
+
+    /// HTTP status
+    pub fn status(&self) -> &StatusCode {
+        &self.status
+    }
+
+    /// Reply headers
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// Reply body
+    pub fn body(&self) -> &Bytes {
+        &self.body
+    }
+
+    fn content<T>(body: T, content_type: &'static str) -> Self
+    where
+        T: Into<Bytes>,
+    {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static(content_type));
+        Reply {
+            status: StatusCode::OK,
+            headers,
+            body: body.into(),
+        }
+    }
+}
+
+impl From<Reply> for Response<Bytes> {
+    fn from(reply: Reply) -> Self {
+        Response::new(reply.status, reply.headers, reply.body)
+    }
+}
+
+// Match a value against a template like `/users/{id}`
+//
+// Parameters are captured in order. When a separator is set,
+// parameters can't contain this separator.
+pub(crate) fn match_template<'t>(
+    template: &'t str,
+    value: &str,
+    separator: Option<char>,
+) -> Option<Vec<(&'t str, String)>> {
+    let tokens = tokenize(template);
+    let mut params = Vec::new();
+    if match_tokens(&tokens, value, separator, &mut params) {
+        Some(params)
+    } else {
+        None
+    }
+}
+
+enum Token<'t> {
+    Literal(&'t str),
+    Param(&'t str),
+}
+
+fn tokenize(template: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let end = match rest[start..].find('}') {
+            Some(end) => start + end,
+            None => break,
+        };
+        if start > 0 {
+            tokens.push(Token::Literal(&rest[..start]));
+        }
+        tokens.push(Token::Param(&rest[start + 1..end]));
+        rest = &rest[end + 1..];
+    }
+    if !rest.is_empty() {
+        tokens.push(Token::Literal(rest));
+    }
+    tokens
+}
+
+fn match_tokens<'t>(
+    tokens: &[Token<'t>],
+    value: &str,
+    separator: Option<char>,
+    params: &mut Vec<(&'t str, String)>,
+) -> bool {
+    match tokens.split_first() {
+        None => value.is_empty(),
+        Some((Token::Literal(literal), rest)) => {
+            value.starts_with(literal)
+                && match_tokens(rest, &value[literal.len()..], separator, params)
+        }
+        Some((Token::Param(name), rest)) => {
+            let max = match separator {
+                Some(separator) => value.find(separator).unwrap_or(value.len()),
+                None => value.len(),
+            };
+            let ends = (1..=max).filter(|end| value.is_char_boundary(*end));
+            for end in ends {
+                params.push((name, value[..end].to_string()));
+                if match_tokens(rest, &value[end..], separator, params) {
+                    return true;
+                }
+                params.pop();
+            }
+            false
+        }
+    }
+}
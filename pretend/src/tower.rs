@@ -0,0 +1,303 @@
+//! Tower integration
+//!
+//! This module integrates `pretend` with [`tower`] services, in both directions.
+//!
+//! [`ServiceClient`] is a client implementation that executes requests with a tower
+//! `Service` taking `http::Request<Bytes>` and returning `http::Response<Bytes>`.
+//! Tower middlewares, like timeouts, retries or concurrency limits, can then be used
+//! under `pretend`.
+//!
+//! ```rust
+//! use pretend::client::Bytes;
+//! use pretend::http;
+//! use pretend::tower::ServiceClient;
+//! use pretend::{pretend, Pretend, Result, Url};
+//! use std::convert::Infallible;
+//! use tower::service_fn;
+//!
+//! #[pretend]
+//! trait HttpBin {
+//!     #[request(method = "GET", path = "/get")]
+//!     async fn get(&self) -> Result<String>;
+//! }
+//!
+//! let service = service_fn(|request: http::Request<Bytes>| async move {
+//!     let body = Bytes::from(request.uri().to_string());
+//!     Ok::<_, Infallible>(http::Response::new(body))
+//! });
+//!
+//! let url = Url::parse("http://localhost").unwrap();
+//! let pretend = Pretend::for_client(ServiceClient::new(service)).with_url(url);
+//! ```
+//!
+//! [`InProcessClient`] dispatches requests to an application running in-process, like a
+//! router built with `hyper`, `axum` or other tower based frameworks, without binding a
+//! socket. Unlike [`ServiceClient`], it supports any request body that can be built from
+//! `Bytes`, and any response body implementing `http_body::Body`. This is useful for
+//! integration tests.
+//!
+//! ```rust
+//! use pretend::http;
+//! use pretend::tower::InProcessClient;
+//! use pretend::{Pretend, Url};
+//! use std::convert::Infallible;
+//! use tower::service_fn;
+//!
+//! let app = service_fn(|request: http::Request<String>| async move {
+//!     let body = format!("Hello {}", request.body());
+//!     Ok::<_, Infallible>(http::Response::new(body))
+//! });
+//!
+//! let client: InProcessClient<_, String> = InProcessClient::new(app);
+//! let url = Url::parse("http://localhost").unwrap();
+//! let pretend = Pretend::for_client(client).with_url(url);
+//! ```
+//!
+//! [`Pretend`] is also a tower `Service` when its client implements [`Client`]. Requests
+//! with a relative URI are resolved with the URL resolver, and go through the request
+//! interceptor and the metrics recorder. They are reported to these components with the
+//! [`Endpoint`] [`SERVICE_ENDPOINT`].
+//!
+//! [`tower`]: https://docs.rs/tower/latest/tower/
+
+pub use tower_service::Service;
+
+use crate::client::{async_trait, Bytes, Client, Method};
+use crate::interceptor::InterceptRequest;
+use crate::internal::{Body, MacroSupport};
+use crate::metrics::RecordMetrics;
+use crate::resolver::ResolveUrl;
+use crate::{Endpoint, Error, HeaderMap, Pretend, Response, Result, Url};
+use bytes::BufMut;
+use http::header::HOST;
+use http::HeaderValue;
+use http_body::Body as HttpBody;
+use std::future::Future;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Endpoint used when `Pretend` is called as a tower `Service`
+pub const SERVICE_ENDPOINT: Endpoint = Endpoint::new("Service", "call", "");
+
+/// A client implementation for tower services
+///
+/// See module level documentation for more information.
+#[derive(Clone, Debug, Default)]
+pub struct ServiceClient<S> {
+    service: S,
+}
+
+impl<S> ServiceClient<S> {
+    /// Constructor
+    pub fn new(service: S) -> Self {
+        ServiceClient { service }
+    }
+
+    /// Underlying service
+    pub fn service(&self) -> &S {
+        &self.service
+    }
+}
+
+#[async_trait]
+impl<S> Client for ServiceClient<S>
+where
+    S: Service<http::Request<Bytes>, Response = http::Response<Bytes>> + Clone + Send + Sync,
+    S::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    S::Future: Send,
+{
+    async fn execute(
+        &self,
+        method: Method,
+        url: Url,
+        headers: HeaderMap,
+        body: Option<Bytes>,
+    ) -> Result<Response<Bytes>> {
+        let mut request = http::Request::new(body.unwrap_or_default());
+        *request.method_mut() = method;
+        *request.uri_mut() = url.as_str().parse().map_err(Error::request)?;
+        *request.headers_mut() = headers;
+
+        // Services are cloned, as they need to be mutable to be called
+        let mut service = self.service.clone();
+        let ready = poll_fn(|cx| service.poll_ready(cx)).await;
+        ready.map_err(|err| Error::Response(err.into()))?;
+        let response = service.call(request).await;
+        let response = response.map_err(|err| Error::Response(err.into()))?;
+
+        let (parts, body) = response.into_parts();
+        Ok(Response::new(parts.status, parts.headers, body).with_url(url))
+    }
+}
+
+/// A client implementation dispatching to an in-process service
+///
+/// `B` is the request body type expected by the service. It is not inferred and should be
+/// set when the service accepts another body than `Bytes`.
+///
+/// See module level documentation for more information.
+#[derive(Debug)]
+pub struct InProcessClient<S, B = Bytes> {
+    service: S,
+    body: PhantomData<fn() -> B>,
+}
+
+impl<S, B> InProcessClient<S, B> {
+    /// Constructor
+    pub fn new(service: S) -> Self {
+        InProcessClient {
+            service,
+            body: PhantomData,
+        }
+    }
+
+    /// Underlying service
+    pub fn service(&self) -> &S {
+        &self.service
+    }
+}
+
+impl<S, B> Clone for InProcessClient<S, B>
+where
+    S: Clone,
+{
+    fn clone(&self) -> Self {
+        InProcessClient::new(self.service.clone())
+    }
+}
+
+#[async_trait]
+impl<S, B, RB> Client for InProcessClient<S, B>
+where
+    S: Service<http::Request<B>, Response = http::Response<RB>> + Clone + Send + Sync,
+    S::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    S::Future: Send,
+    B: From<Bytes> + Send,
+    RB: HttpBody + Send,
+    RB::Data: Send,
+    RB::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    async fn execute(
+        &self,
+        method: Method,
+        url: Url,
+        mut headers: HeaderMap,
+        body: Option<Bytes>,
+    ) -> Result<Response<Bytes>> {
+        // Applications may rely on the host header, that is usually set by HTTP clients
+        if let (false, Some(host)) = (headers.contains_key(HOST), url.host_str()) {
+            let host = match url.port() {
+                Some(port) => format!("{}:{}", host, port),
+                None => host.to_string(),
+            };
+            let host = HeaderValue::from_str(&host).map_err(Error::request)?;
+            headers.insert(HOST, host);
+        }
+
+        let mut request = http::Request::new(B::from(body.unwrap_or_default()));
+        *request.method_mut() = method;
+        *request.uri_mut() = url.as_str().parse().map_err(Error::request)?;
+        *request.headers_mut() = headers;
+
+        let mut service = self.service.clone();
+        let ready = poll_fn(|cx| service.poll_ready(cx)).await;
+        ready.map_err(|err| Error::Response(err.into()))?;
+        let response = service.call(request).await;
+        let response = response.map_err(|err| Error::Response(err.into()))?;
+
+        let (parts, body) = response.into_parts();
+        let body = read_body(body).await?;
+        Ok(Response::new(parts.status, parts.headers, body).with_url(url))
+    }
+}
+
+async fn read_body<B>(body: B) -> Result<Bytes>
+where
+    B: HttpBody,
+    B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    let mut body = Box::pin(body);
+    let mut bytes = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(|err| Error::Body(err.into()))?;
+        bytes.put(chunk);
+    }
+    Ok(Bytes::from(bytes))
+}
+
+impl<C, R, I, M> Service<http::Request<Bytes>> for Pretend<C, R, I, M>
+where
+    C: Client + Clone + Send + Sync + 'static,
+    R: ResolveUrl + Clone + Send + Sync + 'static,
+    I: InterceptRequest + Clone + Send + Sync + 'static,
+    M: RecordMetrics + Clone + Send + Sync + 'static,
+{
+    type Response = http::Response<Bytes>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<http::Response<Bytes>>> + Send>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: http::Request<Bytes>) -> Self::Future {
+        let pretend = self.clone();
+        Box::pin(async move { call_pretend(&pretend, request).await })
+    }
+}
+
+async fn call_pretend<C, R, I, M>(
+    pretend: &Pretend<C, R, I, M>,
+    request: http::Request<Bytes>,
+) -> Result<http::Response<Bytes>>
+where
+    C: Client + Sync,
+    R: ResolveUrl,
+    I: InterceptRequest,
+    M: RecordMetrics,
+{
+    let support = MacroSupport::new(pretend, SERVICE_ENDPOINT);
+    let (parts, body) = request.into_parts();
+
+    let url = if parts.uri.scheme().is_some() {
+        Url::parse(&parts.uri.to_string()).map_err(Error::request)?
+    } else {
+        let path = parts.uri.path_and_query().map(|path| path.as_str());
+        support.create_url(&parts.method, path.unwrap_or("/"))?
+    };
+    let body = if body.is_empty() {
+        Body::None
+    } else {
+        Body::Raw(body)
+    };
+
+    let response = support.request::<(), Response<Bytes>>(parts.method, url, parts.headers, body);
+    let (status, headers, body) = response.await?.into_parts();
+
+    let mut response = http::Response::new(body);
+    *response.status_mut() = status;
+    *response.headers_mut() = headers;
+    Ok(response)
+}
+
+/// Future polling a closure, as `std::future::poll_fn` requires Rust 1.64
+struct PollFn<F>(F);
+
+impl<F, T> Future for PollFn<F>
+where
+    F: FnMut(&mut Context<'_>) -> Poll<T> + Unpin,
+{
+    type Output = T;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        (self.0)(cx)
+    }
+}
+
+fn poll_fn<F, T>(f: F) -> PollFn<F>
+where
+    F: FnMut(&mut Context<'_>) -> Poll<T> + Unpin,
+{
+    PollFn(f)
+}
@@ -14,4 +14,10 @@ trait Test2 {
     fn test_1(&self) -> Result<()>;
 }
 
+#[pretend(blocking_variant = "Test4")]
+trait Test3 {
+    #[request(method = "GET", path = "/get")]
+    fn test_1(&self) -> Result<()>;
+}
+
 fn main() {}
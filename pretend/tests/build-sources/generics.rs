@@ -5,11 +5,9 @@ use pretend::{pretend, Result};
 #[pretend]
 trait Test {
     #[request(method = "GET", path = "/get")]
-    async fn test_1<T>(&self) -> Result<()>;
+    async fn test_1<const N: usize>(&self) -> Result<()>;
     #[request(method = "GET", path = "/get")]
-    async fn test_2(&self) -> Result<()>
-    where
-        Self: Sized;
+    async fn test_2<T, const N: usize>(&self) -> Result<()>;
 }
 
 fn main() {}
@@ -3,6 +3,7 @@ use pretend::pretend;
 #[pretend]
 trait Test {
     type Item;
+    const ID: u32;
 
     #[request(method = "GET", path = "/get")]
     async fn test(&self) -> Result<()>;
@@ -125,9 +125,11 @@ impl ClientsTester {
         let method_name = method.to_string();
         let url = self.url.join("/method").unwrap();
 
-        let response = client.execute(method, url, HeaderMap::new(), None).unwrap();
+        let response = client.execute(method, url.clone(), HeaderMap::new(), None);
+        let response = response.unwrap();
 
         assert_eq!(response.status().as_u16(), 200);
+        assert_eq!(response.url(), Some(&url));
         let body = String::from_utf8(response.body().to_vec()).unwrap();
         assert_eq!(body, method_name);
     }
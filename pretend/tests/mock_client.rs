@@ -0,0 +1,94 @@
+use pretend::client::{async_trait, BlockingClient, Bytes, Client, Method};
+use pretend::{HeaderMap, Response, Result, StatusCode, Url};
+use std::sync::{Arc, Mutex};
+
+#[allow(unused)]
+#[derive(Clone, Debug)]
+pub struct MockRequest {
+    pub method: Method,
+    pub url: Url,
+    pub headers: HeaderMap,
+    pub body: Option<Bytes>,
+}
+
+type Handler = dyn Fn(&MockRequest) -> Result<Response<Bytes>> + Send + Sync;
+
+#[derive(Clone)]
+pub struct MockClient {
+    handler: Arc<Handler>,
+    requests: Arc<Mutex<Vec<MockRequest>>>,
+}
+
+#[allow(unused)]
+impl MockClient {
+    pub fn new<F>(handler: F) -> Self
+    where
+        F: Fn(&MockRequest) -> Result<Response<Bytes>> + Send + Sync + 'static,
+    {
+        MockClient {
+            handler: Arc::new(handler),
+            requests: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    pub fn ok() -> Self {
+        MockClient::new(|_| Ok(response(StatusCode::OK, HeaderMap::new(), "")))
+    }
+
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    fn handle(
+        &self,
+        method: Method,
+        url: Url,
+        headers: HeaderMap,
+        body: Option<Bytes>,
+    ) -> Result<Response<Bytes>> {
+        let request = MockRequest {
+            method,
+            url,
+            headers,
+            body,
+        };
+        let response = (self.handler)(&request);
+        self.requests.lock().unwrap().push(request);
+        response
+    }
+}
+
+#[async_trait]
+impl Client for MockClient {
+    async fn execute(
+        &self,
+        method: Method,
+        url: Url,
+        headers: HeaderMap,
+        body: Option<Bytes>,
+    ) -> Result<Response<Bytes>> {
+        self.handle(method, url, headers, body)
+    }
+}
+
+impl BlockingClient for MockClient {
+    fn execute(
+        &self,
+        method: Method,
+        url: Url,
+        headers: HeaderMap,
+        body: Option<Bytes>,
+    ) -> Result<Response<Bytes>> {
+        self.handle(method, url, headers, body)
+    }
+}
+
+#[allow(unused)]
+pub fn response(status: StatusCode, headers: HeaderMap, body: &'static str) -> Response<Bytes> {
+    Response::new(status, headers, Bytes::from(body))
+}
+
+#[allow(unused)]
+pub fn url(url: &str) -> Url {
+    Url::parse(url).unwrap()
+}
@@ -0,0 +1,105 @@
+mod mock_client;
+
+use self::mock_client::{url, MockClient};
+use pretend::client::blocking::{BlockOn, BlockingAdapter, CurrentThread};
+use pretend::client::{async_trait, Bytes, LocalClient, Method};
+use pretend::{pretend, Endpoint, HeaderMap, Pretend, Response, Result, StatusCode, Url};
+use std::cell::RefCell;
+use std::future::Future;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
+#[pretend]
+trait TestApi {
+    #[request(method = "GET", path = "/users/{id}")]
+    fn get_user(&self, id: i32) -> Result<String>;
+}
+
+#[test]
+fn test_blocking_adapter() {
+    let client = MockClient::ok();
+    let adapter = BlockingAdapter::new(client.clone());
+    let pretend = Pretend::for_client(adapter).with_url(url("http://localhost"));
+
+    pretend.get_user(1).unwrap();
+    pretend.get_user(2).unwrap();
+
+    let requests = client.requests();
+    assert_eq!(requests[0].method, Method::GET);
+    assert_eq!(requests[1].url.as_str(), "http://localhost/users/2");
+}
+
+#[derive(Clone, Default)]
+struct LocalEndpointClient {
+    endpoints: Rc<RefCell<Vec<Endpoint>>>,
+}
+
+#[async_trait(?Send)]
+impl LocalClient for LocalEndpointClient {
+    async fn execute(
+        &self,
+        _: Method,
+        _: Url,
+        _: HeaderMap,
+        _: Option<Bytes>,
+    ) -> Result<Response<Bytes>> {
+        unreachable!()
+    }
+
+    async fn execute_with_endpoint(
+        &self,
+        endpoint: &Endpoint,
+        _: Method,
+        _: Url,
+        _: HeaderMap,
+        _: Option<Bytes>,
+    ) -> Result<Response<Bytes>> {
+        self.endpoints.borrow_mut().push(*endpoint);
+        let body = Bytes::from(endpoint.name());
+        Ok(Response::new(StatusCode::OK, HeaderMap::new(), body))
+    }
+}
+
+#[test]
+fn test_blocking_adapter_local_client() {
+    let client = LocalEndpointClient::default();
+    let adapter = BlockingAdapter::new(client.clone());
+    let pretend = Pretend::for_client(adapter).with_url(url("http://localhost"));
+
+    let result = pretend.get_user(1).unwrap();
+    assert_eq!(result, "get_user");
+
+    let endpoints = client.endpoints.borrow();
+    assert_eq!(endpoints[0].trait_name(), "TestApi");
+    assert_eq!(endpoints[0].path(), "/users/{id}");
+}
+
+#[derive(Default)]
+struct CountingRuntime {
+    count: Mutex<u32>,
+}
+
+impl BlockOn for CountingRuntime {
+    fn block_on<F>(&self, future: F) -> F::Output
+    where
+        F: Future,
+    {
+        *self.count.lock().unwrap() += 1;
+        CurrentThread.block_on(future)
+    }
+}
+
+#[test]
+fn test_blocking_adapter_shared_runtime() {
+    let runtime = Arc::new(CountingRuntime::default());
+    let client = MockClient::ok();
+
+    let adapter = BlockingAdapter::with_runtime(client.clone(), runtime.clone());
+    let pretend1 = Pretend::for_client(adapter).with_url(url("http://localhost"));
+    let adapter = BlockingAdapter::with_runtime(client, runtime.clone());
+    let pretend2 = Pretend::for_client(adapter).with_url(url("http://localhost"));
+
+    pretend1.get_user(1).unwrap();
+    pretend2.get_user(2).unwrap();
+    assert_eq!(*runtime.count.lock().unwrap(), 2);
+}
@@ -0,0 +1,89 @@
+mod mock_client;
+mod runtimes;
+
+use self::mock_client::{response, url, MockClient};
+use pretend::metrics::{RecordMetrics, RequestMetrics};
+use pretend::{pretend, HeaderMap, Json, Pretend, Result, StatusCode};
+use serde::Deserialize;
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Deserialize)]
+struct User {
+    name: String,
+}
+
+/// Users API
+#[pretend(path = "/api", blocking_variant = "UsersApiBlocking")]
+trait UsersApi {
+    const DEFAULT_ID: i32 = 1;
+
+    #[request(method = "GET", path = "/users/{id}")]
+    async fn get_user(&self, id: i32) -> Result<Json<User>>;
+
+    #[request(method = "DELETE", path = "/users/{id}")]
+    #[header(name = "X-Reason", value = "{reason}")]
+    async fn delete_user(&self, id: i32, reason: &str) -> Result<()>;
+
+    fn default_id(&self) -> i32 {
+        Self::DEFAULT_ID
+    }
+
+    async fn get_default_user(&self) -> Result<Json<User>> {
+        self.get_user(self.default_id()).await
+    }
+}
+
+#[derive(Clone, Default)]
+struct Endpoints {
+    names: Arc<Mutex<Vec<String>>>,
+}
+
+impl RecordMetrics for Endpoints {
+    fn record(&self, metrics: &RequestMetrics) {
+        let endpoint = metrics.endpoint;
+        let name = format!("{}::{}", endpoint.trait_name(), endpoint.name());
+        self.names.lock().unwrap().push(name);
+    }
+}
+
+fn client() -> MockClient {
+    let body = r#"{"name":"Alice"}"#;
+    MockClient::new(move |_| Ok(response(StatusCode::OK, HeaderMap::new(), body)))
+}
+
+#[test]
+fn test_async_variant() {
+    runtimes::block_on(async {
+        let client = client();
+        let pretend = Pretend::for_client(client.clone()).with_url(url("http://localhost"));
+        let user = UsersApi::get_default_user(&pretend).await.unwrap();
+        assert_eq!(user.value().name, "Alice");
+        UsersApi::delete_user(&pretend, 1, "spam").await.unwrap();
+
+        let requests = client.requests();
+        assert_eq!(requests[0].url.as_str(), "http://localhost/api/users/1");
+        assert_eq!(requests[1].headers.get("X-Reason").unwrap(), "spam");
+    })
+}
+
+#[test]
+fn test_blocking_variant() {
+    let client = client();
+    let endpoints = Endpoints::default();
+    let pretend = Pretend::for_client(client.clone())
+        .with_url(url("http://localhost"))
+        .with_metrics_recorder(endpoints.clone());
+
+    let user = UsersApiBlocking::get_user(&pretend, 2).unwrap();
+    assert_eq!(user.value().name, "Alice");
+    UsersApiBlocking::delete_user(&pretend, 2, "spam").unwrap();
+    assert_eq!(UsersApiBlocking::default_id(&pretend), 1);
+
+    let requests = client.requests();
+    assert_eq!(requests[0].url.as_str(), "http://localhost/api/users/2");
+    assert_eq!(requests[1].headers.get("X-Reason").unwrap(), "spam");
+
+    let names = endpoints.names.lock().unwrap().clone();
+    let expected = vec!["UsersApi::get_user", "UsersApi::delete_user"];
+    assert_eq!(names, expected);
+}
@@ -0,0 +1,334 @@
+mod mock_client;
+mod runtimes;
+
+use httpdate::fmt_http_date;
+use mock_client::{response, url, MockClient};
+use pretend::client::{BlockingClient, Bytes, Client, Method};
+use pretend::http::HeaderValue;
+use pretend::middleware::cache::{Cache, CacheStore, DiskStore, MemoryStore};
+use pretend::middleware::MiddlewareClient;
+use pretend::{HeaderMap, Response, StatusCode};
+use std::env;
+use std::fs;
+use std::process;
+use std::time::{Duration, SystemTime};
+
+type CacheClient<S> = MiddlewareClient<MockClient, Cache<S>>;
+
+fn headers(headers: &[(&'static str, &'static str)]) -> HeaderMap {
+    let mut map = HeaderMap::new();
+    for (name, value) in headers {
+        map.append(*name, HeaderValue::from_static(value));
+    }
+    map
+}
+
+fn cached_client(
+    response_headers: &'static [(&'static str, &'static str)],
+) -> CacheClient<MemoryStore> {
+    let mock = MockClient::new(move |request| {
+        let body = if request.method == Method::GET {
+            "Hello"
+        } else {
+            ""
+        };
+        Ok(response(StatusCode::OK, headers(response_headers), body))
+    });
+    MiddlewareClient::new(mock, Cache::new(MemoryStore::new(10)))
+}
+
+fn execute<S>(
+    client: &CacheClient<S>,
+    method: Method,
+    request_headers: HeaderMap,
+) -> Response<Bytes>
+where
+    S: CacheStore,
+{
+    let url = url("http://localhost/");
+    let response = BlockingClient::execute(client, method, url, request_headers, None);
+    response.unwrap()
+}
+
+fn get<S>(client: &CacheClient<S>) -> Response<Bytes>
+where
+    S: CacheStore,
+{
+    execute(client, Method::GET, HeaderMap::new())
+}
+
+fn requests<S>(client: &CacheClient<S>) -> usize {
+    client.client().requests().len()
+}
+
+#[test]
+fn cache_serves_fresh_responses() {
+    let client = cached_client(&[("Cache-Control", "max-age=60")]);
+
+    let response = get(&client);
+    assert_eq!(response.body(), "Hello");
+    assert!(response.headers().get("Age").is_none());
+
+    let response = get(&client);
+    assert_eq!(*response.status(), StatusCode::OK);
+    assert_eq!(response.body(), "Hello");
+    assert_eq!(response.headers().get("Age").unwrap(), "0");
+    assert_eq!(response.url(), Some(&url("http://localhost/")));
+    assert_eq!(requests(&client), 1);
+}
+
+#[test]
+fn cache_uses_expires() {
+    let mock = MockClient::new(|_| {
+        let now = SystemTime::now();
+        let date = HeaderValue::from_str(&fmt_http_date(now)).unwrap();
+        let expires = fmt_http_date(now + Duration::from_secs(60));
+        let expires = HeaderValue::from_str(&expires).unwrap();
+
+        let mut headers = HeaderMap::new();
+        headers.insert("Date", date);
+        headers.insert("Expires", expires);
+        Ok(response(StatusCode::OK, headers, ""))
+    });
+    let client = MiddlewareClient::new(mock, Cache::new(MemoryStore::new(10)));
+
+    get(&client);
+    get(&client);
+    assert_eq!(requests(&client), 1);
+
+    let client = cached_client(&[
+        ("Date", "Mon, 01 Jan 2018 00:00:00 GMT"),
+        ("Expires", "Mon, 01 Jan 2018 00:01:00 GMT"),
+    ]);
+    get(&client);
+    get(&client);
+    assert_eq!(requests(&client), 2);
+}
+
+#[test]
+fn cache_caps_huge_ages() {
+    let client = cached_client(&[("Cache-Control", "max-age=99999999999999999999")]);
+    get(&client);
+    get(&client);
+    assert_eq!(requests(&client), 1);
+
+    let client = cached_client(&[
+        ("Cache-Control", "max-age=60"),
+        ("Age", "18446744073709551615"),
+    ]);
+    get(&client);
+    get(&client);
+    assert_eq!(requests(&client), 2);
+
+    let client = cached_client(&[
+        ("Cache-Control", "max-age=99999999999999999999"),
+        ("Age", "99999999999999999999"),
+    ]);
+    get(&client);
+    get(&client);
+    assert_eq!(requests(&client), 2);
+}
+
+#[test]
+fn cache_does_not_store_uncacheable_responses() {
+    let client = cached_client(&[("Cache-Control", "no-store, max-age=60")]);
+    get(&client);
+    get(&client);
+    assert_eq!(requests(&client), 2);
+
+    let client = cached_client(&[("Cache-Control", "max-age=60"), ("Vary", "*")]);
+    get(&client);
+    get(&client);
+    assert_eq!(requests(&client), 2);
+
+    let client = cached_client(&[]);
+    get(&client);
+    get(&client);
+    assert_eq!(requests(&client), 2);
+}
+
+#[test]
+fn cache_revalidates_with_etag() {
+    let mock = MockClient::new(|request| {
+        if request.headers.get("If-None-Match") == Some(&HeaderValue::from_static("\"v1\"")) {
+            let headers = headers(&[("X-Revalidated", "true")]);
+            return Ok(response(StatusCode::NOT_MODIFIED, headers, ""));
+        }
+        let headers = headers(&[("Cache-Control", "no-cache"), ("ETag", "\"v1\"")]);
+        Ok(response(StatusCode::OK, headers, "Hello"))
+    });
+    let client = MiddlewareClient::new(mock, Cache::new(MemoryStore::new(10)));
+
+    get(&client);
+    let response = get(&client);
+    assert_eq!(*response.status(), StatusCode::OK);
+    assert_eq!(response.body(), "Hello");
+    assert_eq!(response.headers().get("ETag").unwrap(), "\"v1\"");
+    assert_eq!(response.headers().get("X-Revalidated").unwrap(), "true");
+    assert_eq!(response.url(), Some(&url("http://localhost/")));
+
+    let requests = client.client().requests();
+    assert_eq!(requests.len(), 2);
+    assert!(requests[0].headers.get("If-None-Match").is_none());
+    assert_eq!(requests[1].headers.get("If-None-Match").unwrap(), "\"v1\"");
+}
+
+#[test]
+fn cache_revalidates_with_last_modified() {
+    let last_modified = "Mon, 01 Jan 2018 00:00:00 GMT";
+    let mock = MockClient::new(move |request| {
+        if request.headers.contains_key("If-Modified-Since") {
+            return Ok(response(StatusCode::NOT_MODIFIED, HeaderMap::new(), ""));
+        }
+        let headers = headers(&[
+            ("Cache-Control", "max-age=0"),
+            ("Last-Modified", last_modified),
+        ]);
+        Ok(response(StatusCode::OK, headers, "Hello"))
+    });
+    let client = MiddlewareClient::new(mock, Cache::new(MemoryStore::new(10)));
+
+    get(&client);
+    let response = get(&client);
+    assert_eq!(response.body(), "Hello");
+
+    let requests = client.client().requests();
+    let if_modified_since = requests[1].headers.get("If-Modified-Since").unwrap();
+    assert_eq!(if_modified_since, last_modified);
+}
+
+#[test]
+fn cache_replaces_modified_responses() {
+    let mock = MockClient::new(|request| {
+        let body = if request.headers.contains_key("If-None-Match") {
+            "Modified"
+        } else {
+            "Hello"
+        };
+        let headers = headers(&[("Cache-Control", "no-cache"), ("ETag", "\"v1\"")]);
+        Ok(response(StatusCode::OK, headers, body))
+    });
+    let client = MiddlewareClient::new(mock, Cache::new(MemoryStore::new(10)));
+
+    assert_eq!(get(&client).body(), "Hello");
+    assert_eq!(get(&client).body(), "Modified");
+}
+
+#[test]
+fn cache_follows_request_directives() {
+    let client = cached_client(&[("Cache-Control", "max-age=60")]);
+    let no_cache = headers(&[("Cache-Control", "no-cache")]);
+    let only_if_cached = headers(&[("Cache-Control", "only-if-cached")]);
+
+    let response = execute(&client, Method::GET, only_if_cached.clone());
+    assert_eq!(*response.status(), StatusCode::GATEWAY_TIMEOUT);
+    assert_eq!(requests(&client), 0);
+
+    get(&client);
+    execute(&client, Method::GET, no_cache);
+    assert_eq!(requests(&client), 2);
+
+    let response = execute(&client, Method::GET, only_if_cached);
+    assert_eq!(*response.status(), StatusCode::OK);
+    assert_eq!(requests(&client), 2);
+}
+
+#[test]
+fn cache_matches_vary_headers() {
+    let client = cached_client(&[("Cache-Control", "max-age=60"), ("Vary", "Accept")]);
+    let json = headers(&[("Accept", "application/json")]);
+    let xml = headers(&[("Accept", "application/xml")]);
+
+    execute(&client, Method::GET, json.clone());
+    execute(&client, Method::GET, json);
+    assert_eq!(requests(&client), 1);
+
+    execute(&client, Method::GET, xml);
+    assert_eq!(requests(&client), 2);
+}
+
+#[test]
+fn cache_is_invalidated_by_unsafe_requests() {
+    let client = cached_client(&[("Cache-Control", "max-age=60")]);
+
+    get(&client);
+    execute(&client, Method::POST, HeaderMap::new());
+    let response = get(&client);
+    assert_eq!(response.body(), "Hello");
+    assert_eq!(requests(&client), 3);
+}
+
+#[test]
+fn memory_store_evicts_least_recently_used() {
+    let mock = MockClient::new(|_| {
+        let headers = headers(&[("Cache-Control", "max-age=60")]);
+        Ok(response(StatusCode::OK, headers, ""))
+    });
+    let store = MemoryStore::new(2);
+    let client = MiddlewareClient::new(mock, Cache::new(store.clone()));
+    let get = |path: &str| {
+        let url = url(&format!("http://localhost/{}", path));
+        BlockingClient::execute(&client, Method::GET, url, HeaderMap::new(), None).unwrap();
+    };
+
+    get("a");
+    get("b");
+    get("a");
+    get("c");
+    assert_eq!(store.len(), 2);
+    assert!(store.get("http://localhost/a").is_some());
+    assert!(store.get("http://localhost/b").is_none());
+    assert!(store.get("http://localhost/c").is_some());
+}
+
+#[test]
+fn disk_store_persists_responses() {
+    let path = env::temp_dir().join(format!("pretend-test-cache-{}", process::id()));
+    let _ = fs::remove_dir_all(&path);
+
+    let mock = MockClient::new(|_| {
+        let headers = headers(&[("Cache-Control", "max-age=60"), ("Vary", "Accept")]);
+        let response = response(StatusCode::CREATED, headers, "Hello\nWorld");
+        Ok(response.with_url(url("http://localhost/final")))
+    });
+    let client = MiddlewareClient::new(mock, Cache::new(DiskStore::new(&path)));
+    let request_headers = headers(&[("Accept", "text/plain")]);
+    execute(&client, Method::GET, request_headers.clone());
+
+    let store = DiskStore::new(&path);
+    let entry = store.get("http://localhost/").unwrap();
+    assert_eq!(*entry.response().status(), StatusCode::CREATED);
+    assert_eq!(entry.response().body(), "Hello\nWorld");
+    assert_eq!(entry.vary_headers(), &request_headers);
+    assert_eq!(entry.response().url(), Some(&url("http://localhost/final")));
+    assert!(store.get("http://localhost/other").is_none());
+
+    let client = MiddlewareClient::new(MockClient::ok(), Cache::new(store));
+    let response = execute(&client, Method::GET, request_headers);
+    assert_eq!(response.body(), "Hello\nWorld");
+    assert_eq!(response.url(), Some(&url("http://localhost/final")));
+    assert_eq!(requests(&client), 0);
+
+    execute(&client, Method::DELETE, HeaderMap::new());
+    assert!(client
+        .middleware()
+        .store()
+        .get("http://localhost/")
+        .is_none());
+
+    fs::remove_dir_all(&path).unwrap();
+}
+
+#[test]
+fn cache_supports_async_clients() {
+    let client = cached_client(&[("Cache-Control", "max-age=60")]);
+
+    runtimes::block_on(async {
+        for _ in 0..2 {
+            let url = url("http://localhost/");
+            let result = Client::execute(&client, Method::GET, url, HeaderMap::new(), None);
+            assert_eq!(result.await.unwrap().body(), "Hello");
+        }
+    });
+    assert_eq!(requests(&client), 1);
+}
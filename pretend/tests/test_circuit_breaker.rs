@@ -0,0 +1,186 @@
+mod mock_client;
+mod runtimes;
+
+use mock_client::{response, url, MockClient};
+use pretend::client::{BlockingClient, Client, Method};
+use pretend::middleware::circuitbreaker::{CircuitBreaker, CircuitState};
+use pretend::middleware::MiddlewareClient;
+use pretend::{Error, HeaderMap, Result, StatusCode};
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+fn get<C>(client: &C) -> Result<StatusCode>
+where
+    C: BlockingClient,
+{
+    let response = client.execute(
+        Method::GET,
+        url("http://localhost/"),
+        HeaderMap::new(),
+        None,
+    )?;
+    Ok(*response.status())
+}
+
+fn is_open(result: Result<StatusCode>) -> bool {
+    matches!(result, Err(Error::CircuitOpen))
+}
+
+fn failing_client(failing: Arc<AtomicBool>) -> MockClient {
+    MockClient::new(move |_| {
+        if failing.load(Ordering::SeqCst) {
+            Ok(response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                HeaderMap::new(),
+                "",
+            ))
+        } else {
+            Ok(response(StatusCode::OK, HeaderMap::new(), ""))
+        }
+    })
+}
+
+#[test]
+fn circuit_breaker_opens_on_failure_ratio() {
+    let failing = Arc::new(AtomicBool::new(false));
+    let breaker = CircuitBreaker::new()
+        .with_failure_ratio(0.5)
+        .with_window(4)
+        .with_minimum_requests(4);
+    let client = MiddlewareClient::new(failing_client(failing.clone()), breaker);
+
+    assert_eq!(get(&client).unwrap(), StatusCode::OK);
+    assert_eq!(get(&client).unwrap(), StatusCode::OK);
+    assert_eq!(get(&client).unwrap(), StatusCode::OK);
+    assert_eq!(client.middleware().state(), CircuitState::Closed);
+
+    failing.store(true, Ordering::SeqCst);
+    let status = get(&client).unwrap();
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(client.middleware().state(), CircuitState::Closed);
+
+    let status = get(&client).unwrap();
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(client.middleware().state(), CircuitState::Open);
+
+    assert!(is_open(get(&client)));
+    assert_eq!(client.client().requests().len(), 5);
+}
+
+#[test]
+fn circuit_breaker_opens_at_exact_failure_ratio() {
+    let failing = Arc::new(AtomicBool::new(false));
+    let breaker = CircuitBreaker::new()
+        .with_failure_ratio(0.25)
+        .with_window(4)
+        .with_minimum_requests(4);
+    let client = MiddlewareClient::new(failing_client(failing.clone()), breaker);
+
+    for _ in 0..3 {
+        assert_eq!(get(&client).unwrap(), StatusCode::OK);
+    }
+    failing.store(true, Ordering::SeqCst);
+    let status = get(&client).unwrap();
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(client.middleware().state(), CircuitState::Open);
+}
+
+#[test]
+fn circuit_breaker_counts_response_errors() {
+    let mock = MockClient::new(|_| {
+        let err = io::Error::new(io::ErrorKind::ConnectionRefused, "refused");
+        Err(Error::response(err))
+    });
+    let breaker = CircuitBreaker::new().with_window(2);
+    let client = MiddlewareClient::new(mock, breaker);
+
+    assert!(matches!(get(&client), Err(Error::Response(_))));
+    assert!(matches!(get(&client), Err(Error::Response(_))));
+    assert!(is_open(get(&client)));
+}
+
+#[test]
+fn circuit_breaker_ignores_other_errors() {
+    let mock = MockClient::new(|_| {
+        let err = io::Error::new(io::ErrorKind::InvalidInput, "invalid");
+        Err(Error::request(err))
+    });
+    let breaker = CircuitBreaker::new().with_window(2);
+    let client = MiddlewareClient::new(mock, breaker);
+
+    for _ in 0..5 {
+        assert!(matches!(get(&client), Err(Error::Request(_))));
+    }
+    assert_eq!(client.middleware().state(), CircuitState::Closed);
+}
+
+#[test]
+fn circuit_breaker_probes_when_half_open() {
+    let failing = Arc::new(AtomicBool::new(true));
+    let breaker = CircuitBreaker::new()
+        .with_window(1)
+        .with_open_duration(Duration::from_millis(100));
+    let client = MiddlewareClient::new(failing_client(failing.clone()), breaker);
+
+    assert!(get(&client).is_ok());
+    assert!(is_open(get(&client)));
+
+    // A failed probe opens the circuit again
+    thread::sleep(Duration::from_millis(150));
+    assert!(get(&client).is_ok());
+    assert_eq!(client.middleware().state(), CircuitState::Open);
+    assert!(is_open(get(&client)));
+
+    // A successful probe closes the circuit
+    failing.store(false, Ordering::SeqCst);
+    thread::sleep(Duration::from_millis(150));
+    assert!(get(&client).is_ok());
+    assert_eq!(client.middleware().state(), CircuitState::Closed);
+    assert!(get(&client).is_ok());
+    assert_eq!(client.client().requests().len(), 4);
+}
+
+#[test]
+fn circuit_breaker_limits_probes() {
+    let breaker = CircuitBreaker::new()
+        .with_window(1)
+        .with_open_duration(Duration::from_millis(100))
+        .with_half_open_requests(2);
+    let failing = Arc::new(AtomicBool::new(true));
+    let client = MiddlewareClient::new(failing_client(failing.clone()), breaker);
+
+    assert!(get(&client).is_ok());
+    assert!(is_open(get(&client)));
+
+    failing.store(false, Ordering::SeqCst);
+    thread::sleep(Duration::from_millis(150));
+    assert!(get(&client).is_ok());
+    assert_eq!(client.middleware().state(), CircuitState::HalfOpen);
+    assert!(get(&client).is_ok());
+    assert_eq!(client.middleware().state(), CircuitState::Closed);
+}
+
+#[test]
+fn circuit_breaker_supports_async_clients() {
+    let breaker = CircuitBreaker::new().with_window(1);
+    let mock = MockClient::new(|_| {
+        Ok(response(
+            StatusCode::SERVICE_UNAVAILABLE,
+            HeaderMap::new(),
+            "",
+        ))
+    });
+    let client = MiddlewareClient::new(mock, breaker);
+
+    runtimes::block_on(async {
+        let url = url("http://localhost/");
+        let result = Client::execute(&client, Method::GET, url.clone(), HeaderMap::new(), None);
+        assert!(result.await.is_ok());
+
+        let result = Client::execute(&client, Method::GET, url, HeaderMap::new(), None);
+        assert!(matches!(result.await, Err(Error::CircuitOpen)));
+    });
+}
@@ -0,0 +1,173 @@
+mod runtimes;
+mod server;
+
+use pretend::config::{
+    Certificate, ClientConfig, Identity, Proxy, RedirectPolicy, UnsupportedOption,
+};
+use pretend::{pretend, Error, Json, Pretend, Result, Url};
+use std::collections::HashMap;
+use std::time::Duration;
+
+type Headers = Json<HashMap<String, String>>;
+
+#[pretend]
+trait TestApi {
+    #[request(method = "GET", path = "/headers")]
+    async fn headers(&self) -> Result<Headers>;
+}
+
+#[pretend(?Send)]
+trait TestApiLocal {
+    #[request(method = "GET", path = "/headers")]
+    async fn headers(&self) -> Result<Headers>;
+}
+
+#[pretend]
+trait TestApiBlocking {
+    #[request(method = "GET", path = "/headers")]
+    fn headers(&self) -> Result<Headers>;
+}
+
+fn config() -> ClientConfig {
+    ClientConfig::new()
+        .with_connect_timeout(Duration::from_secs(5))
+        .with_timeout(Duration::from_secs(10))
+        .with_redirect_policy(RedirectPolicy::Limit(3))
+        .with_user_agent("pretend-test/1.0")
+        .with_pool_size(2)
+}
+
+fn url() -> Url {
+    Url::parse(server::URL).unwrap()
+}
+
+fn assert_user_agent(headers: Headers) {
+    let headers = headers.value();
+    assert_eq!(headers.get("user-agent").unwrap(), "pretend-test/1.0");
+}
+
+#[actix_web::main]
+async fn awc_headers() -> Result<Headers> {
+    let client = pretend_awc::Client::from_config(&config())?;
+    let pretend = Pretend::for_client(client).with_url(url());
+    TestApiLocal::headers(&pretend).await
+}
+
+#[test]
+fn test_client_config() {
+    server::test(|| {
+        let runtime = runtimes::create_runtime();
+        runtime.block_on(async {
+            let client = pretend_reqwest::Client::from_config(&config()).unwrap();
+            let pretend = Pretend::for_client(client).with_url(url());
+            assert_user_agent(TestApi::headers(&pretend).await.unwrap());
+
+            let client = pretend_isahc::Client::from_config(&config()).unwrap();
+            let pretend = Pretend::for_client(client).with_url(url());
+            assert_user_agent(TestApi::headers(&pretend).await.unwrap());
+        });
+
+        assert_user_agent(awc_headers().unwrap());
+
+        let client = pretend_reqwest::BlockingClient::from_config(&config()).unwrap();
+        let pretend = Pretend::for_client(client).with_url(url());
+        assert_user_agent(TestApiBlocking::headers(&pretend).unwrap());
+
+        let client = pretend_ureq::Client::from_config(&config()).unwrap();
+        let pretend = Pretend::for_client(client).with_url(url());
+        assert_user_agent(TestApiBlocking::headers(&pretend).unwrap());
+
+        let client = pretend_curl::Client::from_config(&config()).unwrap();
+        let pretend = Pretend::for_client(client).with_url(url());
+        assert_user_agent(TestApiBlocking::headers(&pretend).unwrap());
+    });
+}
+
+fn unsupported_option<T>(result: Result<T>) -> &'static str {
+    match result {
+        Err(Error::Client(err)) => {
+            let err = err.downcast_ref::<UnsupportedOption>().unwrap();
+            err.option()
+        }
+        _ => panic!("Expected an unsupported option"),
+    }
+}
+
+#[test]
+fn test_client_config_unsupported_options() {
+    let certificate = Certificate::Pem(b"certificate".to_vec());
+    let config = ClientConfig::new().with_root_certificate(certificate);
+    let result = pretend_ureq::Client::from_config(&config);
+    assert_eq!(unsupported_option(result), "root_certificates");
+
+    let proxy = Proxy::new(Url::parse("http://localhost:3128").unwrap());
+    let config = ClientConfig::new().with_proxy(proxy);
+    let result = pretend_awc::Client::from_config(&config);
+    assert_eq!(unsupported_option(result), "proxy");
+
+    let config = ClientConfig::new()
+        .with_root_certificate(Certificate::Pem(b"first".to_vec()))
+        .with_root_certificate(Certificate::Pem(b"second".to_vec()));
+    let result = pretend_isahc::Client::from_config(&config);
+    assert_eq!(unsupported_option(result), "root_certificates");
+
+    let config = ClientConfig::new().with_timeout(Duration::from_secs(10));
+    let result = pretend_hyper::Client::from_config(&config);
+    assert_eq!(unsupported_option(result), "timeout");
+
+    let config = ClientConfig::new().with_redirect_policy(RedirectPolicy::Limit(3));
+    let result = pretend_hyper::Client::from_config(&config);
+    assert_eq!(unsupported_option(result), "redirect_policy");
+}
+
+#[test]
+fn test_client_config_hyper() {
+    server::test(|| {
+        let runtime = runtimes::create_runtime();
+        runtime.block_on(async {
+            let config = ClientConfig::new()
+                .with_connect_timeout(Duration::from_secs(5))
+                .with_redirect_policy(RedirectPolicy::None)
+                .with_pool_size(2);
+            let client = pretend_hyper::Client::from_config(&config).unwrap();
+            let pretend = Pretend::for_client(client).with_url(url());
+            let headers = TestApi::headers(&pretend).await.unwrap();
+            assert!(!headers.value().contains_key("user-agent"));
+        });
+    });
+}
+
+#[test]
+fn test_client_config_proxy() {
+    let proxy = Url::parse("http://proxy.local:3128").unwrap();
+    let proxy = Proxy::new(proxy).with_basic_auth("user", "password");
+    assert_eq!(proxy.basic_auth(), Some(("user", "password")));
+
+    let config = ClientConfig::new().with_proxy(proxy);
+    assert!(pretend_reqwest::Client::from_config(&config).is_ok());
+    assert!(pretend_isahc::Client::from_config(&config).is_ok());
+    assert!(pretend_ureq::Client::from_config(&config).is_ok());
+    assert!(pretend_curl::Client::from_config(&config).is_ok());
+}
+
+#[test]
+fn test_client_config_redacts_secrets() {
+    let proxy = Url::parse("http://proxy.local:3128").unwrap();
+    let proxy = Proxy::new(proxy).with_basic_auth("user", "secret-password");
+    let debug = format!("{:?}", proxy);
+    assert!(debug.contains("user"));
+    assert!(!debug.contains("secret-password"));
+
+    let identity = Identity::Pkcs12 {
+        der: b"secret-archive".to_vec(),
+        password: "secret-password".to_string(),
+    };
+    let debug = format!("{:?}", identity);
+    assert_eq!(
+        debug,
+        r#"Pkcs12 { der: "<redacted>", password: "<redacted>" }"#
+    );
+
+    let identity = Identity::Pem(b"secret-key".to_vec());
+    assert_eq!(format!("{:?}", identity), r#"Pem("<redacted>")"#);
+}
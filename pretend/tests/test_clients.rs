@@ -5,13 +5,18 @@ mod server;
 use clients_tester::{
     ClientsTester, TestableClient, TokioTestableClient, TokioTestableLocalClient,
 };
+use pretend::client::blocking::{BlockOn, BlockingAdapter};
 use pretend::client::{Bytes, Client, LocalClient, Method};
 use pretend::{HeaderMap, Response, Result, Url};
 use pretend_awc::Client as AClient;
+use pretend_curl::Client as CClient;
+use pretend_hyper::Client as HClient;
 use pretend_isahc::Client as IClient;
 use pretend_reqwest::{BlockingClient as RBlockingClient, Client as RClient};
 use pretend_ureq::ureq::AgentBuilder;
 use pretend_ureq::Client as UClient;
+use std::future::Future;
+use tokio::runtime::Runtime;
 
 fn create_testable<C>(client: C) -> Box<dyn TestableClient>
 where
@@ -30,6 +35,17 @@ where
     ))
 }
 
+struct TokioRuntime(Runtime);
+
+impl BlockOn for TokioRuntime {
+    fn block_on<F>(&self, future: F) -> F::Output
+    where
+        F: Future,
+    {
+        self.0.block_on(future)
+    }
+}
+
 struct TestableAwcClient;
 
 #[actix_web::main]
@@ -68,6 +84,7 @@ fn test_clients(url: Url) {
     let clients = vec![
         create_testable(RClient::default()),
         create_testable(IClient::new().unwrap()),
+        create_testable(HClient::default()),
     ];
     let tester = ClientsTester::new(url, clients);
     tester.test();
@@ -77,6 +94,7 @@ fn test_local_clients(url: Url) {
     let clients: Vec<Box<dyn TestableClient>> = vec![
         create_testable_local(RClient::default()),
         create_testable_local(IClient::new().unwrap()),
+        create_testable_local(HClient::default()),
         Box::new(TestableAwcClient),
     ];
     let tester = ClientsTester::new(url, clients);
@@ -87,6 +105,12 @@ fn test_blocking_clients(url: Url) {
     let clients: Vec<Box<dyn TestableClient>> = vec![
         Box::new(RBlockingClient::default()),
         Box::new(UClient::new(AgentBuilder::new().build())),
+        Box::new(CClient::default()),
+        Box::new(BlockingAdapter::new(IClient::new().unwrap())),
+        Box::new(BlockingAdapter::with_runtime(
+            HClient::default(),
+            TokioRuntime(runtimes::create_runtime()),
+        )),
     ];
     let tester = ClientsTester::new(url, clients);
     tester.test();
@@ -0,0 +1,230 @@
+mod mock_client;
+
+use mock_client::{response, url, MockClient};
+use pretend::client::{BlockingClient, Method};
+use pretend::http::HeaderValue;
+use pretend::middleware::cookie::CookieJar;
+use pretend::middleware::redirect::Redirect;
+use pretend::middleware::MiddlewareClient;
+use pretend::{HeaderMap, StatusCode};
+use std::env;
+use std::fs;
+use std::process;
+
+type CookieClient = MiddlewareClient<MockClient, CookieJar>;
+
+// /set?cookie={value} responds with a Set-Cookie header
+fn cookie_client(jar: CookieJar) -> CookieClient {
+    let mock = MockClient::new(|request| {
+        let mut headers = HeaderMap::new();
+        for (name, value) in request.url.query_pairs() {
+            if name == "cookie" {
+                let value = HeaderValue::from_str(&value).unwrap();
+                headers.append("Set-Cookie", value);
+            }
+        }
+        Ok(response(StatusCode::OK, headers, ""))
+    });
+    MiddlewareClient::new(mock, jar)
+}
+
+fn get(client: &CookieClient, url: &str) {
+    let url = pretend::Url::parse(url).unwrap();
+    BlockingClient::execute(client, Method::GET, url, HeaderMap::new(), None).unwrap();
+}
+
+// Cookie header sent to an URL
+fn sent_cookies(jar: &CookieJar, url: &str) -> Option<String> {
+    let client = MiddlewareClient::new(MockClient::ok(), jar.clone());
+    get(&client, url);
+    let request = client.client().requests().pop().unwrap();
+    let cookie = request.headers.get("Cookie");
+    cookie.map(|cookie| cookie.to_str().unwrap().to_string())
+}
+
+#[test]
+fn cookie_jar_sends_cookies_back() {
+    let jar = CookieJar::new();
+    let client = cookie_client(jar.clone());
+
+    get(
+        &client,
+        "http://localhost/set?cookie=session=1&cookie=user=alice",
+    );
+    let cookies = sent_cookies(&jar, "http://localhost/hello");
+    assert_eq!(cookies.unwrap(), "session=1; user=alice");
+
+    get(&client, "http://localhost/set?cookie=session=2");
+    let cookies = sent_cookies(&jar, "http://localhost/hello");
+    assert_eq!(cookies.unwrap(), "session=2; user=alice");
+}
+
+#[test]
+fn cookie_jar_keeps_request_cookies() {
+    let jar = CookieJar::new();
+    jar.set_cookie(&url("http://localhost"), "session=1");
+    let client = MiddlewareClient::new(MockClient::ok(), jar);
+
+    let mut headers = HeaderMap::new();
+    headers.insert("Cookie", HeaderValue::from_static("theme=dark"));
+    let url = url("http://localhost");
+    BlockingClient::execute(&client, Method::GET, url, headers, None).unwrap();
+    let request = client.client().requests().pop().unwrap();
+    let cookies = request.headers.get("Cookie").unwrap();
+    assert_eq!(cookies, "theme=dark; session=1");
+}
+
+#[test]
+fn cookie_jar_follows_domain_rules() {
+    let jar = CookieJar::new();
+    let url = url("http://api.example.com");
+    jar.set_cookie(&url, "host=1");
+    jar.set_cookie(&url, "domain=1; Domain=.example.com");
+    jar.set_cookie(&url, "other=1; Domain=other.com");
+    jar.set_cookie(&url, "suffix=1; Domain=com");
+
+    let cookies = sent_cookies(&jar, "http://api.example.com");
+    assert_eq!(cookies.unwrap(), "host=1; domain=1");
+    let cookies = sent_cookies(&jar, "http://www.example.com");
+    assert_eq!(cookies.unwrap(), "domain=1");
+    let cookies = sent_cookies(&jar, "http://v1.api.example.com");
+    assert_eq!(cookies.unwrap(), "domain=1");
+    assert!(sent_cookies(&jar, "http://notexample.com").is_none());
+    assert!(sent_cookies(&jar, "http://other.com").is_none());
+}
+
+#[test]
+fn cookie_jar_follows_path_rules() {
+    let jar = CookieJar::new();
+    jar.set_cookie(&url("http://localhost/users/1"), "default=1");
+    jar.set_cookie(&url("http://localhost"), "root=1");
+    jar.set_cookie(&url("http://localhost"), "admin=1; Path=/admin");
+
+    let cookies = sent_cookies(&jar, "http://localhost/users/2");
+    assert_eq!(cookies.unwrap(), "default=1; root=1");
+    let cookies = sent_cookies(&jar, "http://localhost/admin/users");
+    assert_eq!(cookies.unwrap(), "admin=1; root=1");
+    let cookies = sent_cookies(&jar, "http://localhost/administrator");
+    assert_eq!(cookies.unwrap(), "root=1");
+}
+
+#[test]
+fn cookie_jar_follows_secure_rules() {
+    let jar = CookieJar::new();
+    jar.set_cookie(&url("http://localhost"), "insecure=1; Secure");
+    jar.set_cookie(&url("https://localhost"), "secure=1; Secure; HttpOnly");
+
+    assert!(sent_cookies(&jar, "http://localhost").is_none());
+    let cookies = sent_cookies(&jar, "https://localhost");
+    assert_eq!(cookies.unwrap(), "secure=1");
+
+    let cookies = jar.cookies();
+    assert_eq!(cookies.len(), 1);
+    assert!(cookies[0].is_secure());
+    assert!(cookies[0].is_http_only());
+}
+
+#[test]
+fn cookie_jar_follows_expiry_rules() {
+    let jar = CookieJar::new();
+    let url = url("http://localhost");
+    jar.set_cookie(&url, "session=1");
+    jar.set_cookie(&url, "max_age=1; Max-Age=3600");
+    jar.set_cookie(&url, "expires=1; Expires=Wed, 21 Oct 2099 07:28:00 GMT");
+    jar.set_cookie(&url, "dashes=1; Expires=Wed, 21-Oct-2099 07:28:00 GMT");
+    jar.set_cookie(&url, "expired=1; Expires=Wed, 21 Oct 2015 07:28:00 GMT");
+    jar.set_cookie(
+        &url,
+        "precedence=1; Max-Age=0; Expires=Wed, 21 Oct 2099 07:28:00 GMT",
+    );
+
+    let cookies = sent_cookies(&jar, "http://localhost").unwrap();
+    assert_eq!(cookies, "session=1; max_age=1; expires=1; dashes=1");
+    assert!(jar.cookies()[0].expires().is_none());
+    assert!(jar.cookies()[1].expires().is_some());
+
+    jar.set_cookie(&url, "session=; Max-Age=0");
+    jar.set_cookie(&url, "expires=; Expires=Wed, 21 Oct 2015 07:28:00 GMT");
+    let cookies = sent_cookies(&jar, "http://localhost");
+    assert_eq!(cookies.unwrap(), "max_age=1; dashes=1");
+}
+
+#[test]
+fn cookie_jar_is_shared_between_clients() {
+    let jar = CookieJar::new();
+    let first = cookie_client(jar.clone());
+    let second = cookie_client(jar.clone());
+
+    get(&first, "http://localhost/set?cookie=session=1");
+    get(&second, "http://localhost/hello");
+    let request = second.client().requests().pop().unwrap();
+    assert_eq!(request.headers.get("Cookie").unwrap(), "session=1");
+
+    jar.clear();
+    get(&first, "http://localhost/hello");
+    let request = first.client().requests().pop().unwrap();
+    assert!(request.headers.get("Cookie").is_none());
+}
+
+#[test]
+fn cookie_jar_stores_cookies_on_redirects() {
+    let jar = CookieJar::new();
+    let mock = MockClient::new(|request| {
+        let mut headers = HeaderMap::new();
+        if request.url.path() == "/login" {
+            headers.insert("Set-Cookie", HeaderValue::from_static("session=1"));
+            headers.insert("Location", HeaderValue::from_static("/home"));
+            Ok(response(StatusCode::SEE_OTHER, headers, ""))
+        } else {
+            Ok(response(StatusCode::OK, headers, ""))
+        }
+    });
+    let client = MiddlewareClient::new(mock, jar);
+    let client = MiddlewareClient::new(client, Redirect::new());
+
+    let url = url("http://localhost/login");
+    BlockingClient::execute(&client, Method::POST, url, HeaderMap::new(), None).unwrap();
+    let request = client.client().client().requests().pop().unwrap();
+    assert_eq!(request.url.path(), "/home");
+    assert_eq!(request.headers.get("Cookie").unwrap(), "session=1");
+}
+
+#[test]
+fn cookie_jar_persists_cookies() {
+    let path = env::temp_dir().join(format!("pretend-test-cookies-{}.json", process::id()));
+
+    let jar = CookieJar::new();
+    jar.set_cookie(&url("http://localhost"), "session=1");
+    jar.set_cookie(
+        &url("https://example.com/users/1"),
+        "user=1; Domain=example.com; Secure",
+    );
+    jar.set_cookie(
+        &url("http://localhost"),
+        "remember=1; Max-Age=3600; HttpOnly",
+    );
+    jar.save(&path).unwrap();
+
+    let loaded = CookieJar::load(&path).unwrap();
+    let cookies = jar.cookies();
+    let loaded_cookies = loaded.cookies();
+    assert_eq!(loaded_cookies.len(), 3);
+    for (cookie, loaded) in cookies.iter().zip(loaded_cookies.iter()) {
+        assert_eq!(cookie.name(), loaded.name());
+        assert_eq!(cookie.value(), loaded.value());
+        assert_eq!(cookie.domain(), loaded.domain());
+        assert_eq!(cookie.is_host_only(), loaded.is_host_only());
+        assert_eq!(cookie.path(), loaded.path());
+        assert_eq!(cookie.is_secure(), loaded.is_secure());
+        assert_eq!(cookie.is_http_only(), loaded.is_http_only());
+        assert_eq!(cookie.expires().is_some(), loaded.expires().is_some());
+    }
+
+    let cookies = sent_cookies(&loaded, "https://www.example.com/users");
+    assert_eq!(cookies.unwrap(), "user=1");
+
+    fs::write(&path, "{}").unwrap();
+    assert!(CookieJar::load(&path).is_err());
+    fs::remove_file(&path).unwrap();
+    assert!(CookieJar::load(&path).is_err());
+}
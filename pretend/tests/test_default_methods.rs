@@ -0,0 +1,84 @@
+mod mock_client;
+mod runtimes;
+
+use self::mock_client::{response, url, MockClient};
+use pretend::{pretend, HeaderMap, Pretend, Result, StatusCode};
+
+#[pretend]
+trait TestApi {
+    const DEFAULT_ID: i32 = 1;
+
+    #[request(method = "GET", path = "/users/{id}")]
+    async fn get_user(&self, id: i32) -> Result<String>;
+
+    fn default_id(&self) -> i32 {
+        Self::DEFAULT_ID
+    }
+
+    async fn get_default_user(&self) -> Result<String> {
+        self.get_user(self.default_id()).await
+    }
+
+    async fn get_users(&self, ids: &[i32]) -> Result<Vec<String>> {
+        let mut users = Vec::new();
+        for id in ids {
+            users.push(self.get_user(*id).await?);
+        }
+        Ok(users)
+    }
+}
+
+#[pretend(server)]
+trait TestApiBlocking {
+    const PREFIX: &'static str = "user-";
+
+    #[request(method = "GET", path = "/users/{id}")]
+    fn get_user(&self, id: i32) -> Result<String>;
+
+    fn get_prefixed_user(&self, id: i32) -> Result<String> {
+        let user = self.get_user(id)?;
+        Ok(format!("{}{}", Self::PREFIX, user))
+    }
+}
+
+fn client() -> MockClient {
+    MockClient::new(|request| {
+        let path = request.url.path().to_string();
+        let id = path.trim_start_matches("/users/");
+        let body = if id == "1" { "Alice" } else { "Bob" };
+        Ok(response(StatusCode::OK, HeaderMap::new(), body))
+    })
+}
+
+#[test]
+fn test_default_methods() {
+    runtimes::block_on(async {
+        let client = client();
+        let pretend = Pretend::for_client(client.clone()).with_url(url("http://localhost"));
+
+        assert_eq!(pretend.default_id(), 1);
+        assert_eq!(pretend.get_default_user().await.unwrap(), "Alice");
+        let users = pretend.get_users(&[1, 2]).await.unwrap();
+        assert_eq!(users, vec!["Alice", "Bob"]);
+
+        let requests = client.requests();
+        let paths = requests.iter().map(|request| request.url.path());
+        let paths = paths.collect::<Vec<_>>();
+        assert_eq!(paths, vec!["/users/1", "/users/1", "/users/2"]);
+    })
+}
+
+#[test]
+fn test_default_methods_blocking() {
+    struct Handler;
+
+    impl TestApiBlockingHandler for Handler {
+        fn get_user(&self, id: i32) -> pretend::server::Reply {
+            pretend::server::Reply::text(id.to_string())
+        }
+    }
+
+    let server = TestApiBlockingServer::new(Handler);
+    let pretend = Pretend::for_client(server).with_url(url("http://localhost"));
+    assert_eq!(pretend.get_prefixed_user(3).unwrap(), "user-3");
+}
@@ -0,0 +1,179 @@
+mod runtimes;
+
+use pretend::client::{async_trait, BlockingClient, Bytes, Client, Method};
+use pretend::interceptor::{InterceptRequest, Request};
+use pretend::middleware::ratelimit::RateLimiter;
+use pretend::middleware::MiddlewareClient;
+use pretend::{pretend, Endpoint, HeaderMap, Pretend, Response, Result, StatusCode, Url};
+use std::sync::{Arc, Mutex};
+
+#[pretend]
+trait TestApi {
+    #[request(method = "GET", path = "/users/{id}")]
+    async fn get_user(&self, id: i32) -> Result<()>;
+}
+
+#[pretend(?Send)]
+trait TestApiLocal {
+    #[request(method = "GET", path = "/users/{id}")]
+    async fn get_user(&self, id: i32) -> Result<()>;
+}
+
+#[pretend]
+trait TestApiBlocking {
+    #[request(method = "DELETE", path = "/users/{id}")]
+    fn delete_user(&self, id: i32) -> Result<()>;
+}
+
+#[derive(Clone, Default)]
+struct EndpointClient {
+    endpoints: Arc<Mutex<Vec<Option<Endpoint>>>>,
+}
+
+impl EndpointClient {
+    fn endpoints(&self) -> Vec<Option<Endpoint>> {
+        self.endpoints.lock().unwrap().clone()
+    }
+
+    fn record(&self, endpoint: Option<&Endpoint>) -> Result<Response<Bytes>> {
+        self.endpoints.lock().unwrap().push(endpoint.copied());
+        Ok(Response::new(
+            StatusCode::OK,
+            HeaderMap::new(),
+            Bytes::new(),
+        ))
+    }
+}
+
+#[async_trait]
+impl Client for EndpointClient {
+    async fn execute(
+        &self,
+        _: Method,
+        _: Url,
+        _: HeaderMap,
+        _: Option<Bytes>,
+    ) -> Result<Response<Bytes>> {
+        self.record(None)
+    }
+
+    async fn execute_with_endpoint(
+        &self,
+        endpoint: &Endpoint,
+        _: Method,
+        _: Url,
+        _: HeaderMap,
+        _: Option<Bytes>,
+    ) -> Result<Response<Bytes>> {
+        self.record(Some(endpoint))
+    }
+}
+
+impl BlockingClient for EndpointClient {
+    fn execute(
+        &self,
+        _: Method,
+        _: Url,
+        _: HeaderMap,
+        _: Option<Bytes>,
+    ) -> Result<Response<Bytes>> {
+        self.record(None)
+    }
+
+    fn execute_with_endpoint(
+        &self,
+        endpoint: &Endpoint,
+        _: Method,
+        _: Url,
+        _: HeaderMap,
+        _: Option<Bytes>,
+    ) -> Result<Response<Bytes>> {
+        self.record(Some(endpoint))
+    }
+}
+
+#[derive(Clone, Default)]
+struct EndpointInterceptor {
+    endpoints: Arc<Mutex<Vec<Option<Endpoint>>>>,
+}
+
+impl InterceptRequest for EndpointInterceptor {
+    fn intercept(&self, request: Request) -> Result<Request> {
+        self.endpoints.lock().unwrap().push(request.endpoint);
+        Ok(request)
+    }
+}
+
+fn url() -> Url {
+    Url::parse("http://localhost").unwrap()
+}
+
+const GET_USER: Endpoint = Endpoint::new("TestApi", "get_user", "/users/{id}");
+
+#[test]
+fn clients_receive_endpoint() {
+    let client = EndpointClient::default();
+    let pretend = Pretend::for_client(client.clone()).with_url(url());
+    runtimes::block_on(async {
+        TestApi::get_user(&pretend, 1).await.unwrap();
+    });
+
+    let pretend = Pretend::for_client(client.clone()).with_url(url());
+    runtimes::block_on(async {
+        TestApiLocal::get_user(&pretend, 1).await.unwrap();
+    });
+
+    let pretend = Pretend::for_client(client.clone()).with_url(url());
+    pretend.delete_user(1).unwrap();
+
+    let expected = vec![
+        Some(GET_USER),
+        Some(Endpoint::new("TestApiLocal", "get_user", "/users/{id}")),
+        Some(Endpoint::new(
+            "TestApiBlocking",
+            "delete_user",
+            "/users/{id}",
+        )),
+    ];
+    assert_eq!(client.endpoints(), expected);
+}
+
+#[test]
+fn interceptors_receive_endpoint() {
+    let interceptor = EndpointInterceptor::default();
+    let pretend = Pretend::for_client(EndpointClient::default())
+        .with_url(url())
+        .with_request_interceptor(interceptor.clone());
+    runtimes::block_on(async {
+        TestApi::get_user(&pretend, 1).await.unwrap();
+    });
+
+    let endpoints = interceptor.endpoints.lock().unwrap().clone();
+    assert_eq!(endpoints, vec![Some(GET_USER)]);
+}
+
+#[test]
+fn middlewares_forward_endpoint() {
+    let client = EndpointClient::default();
+    let middleware_client = MiddlewareClient::new(client.clone(), RateLimiter::new());
+    let pretend = Pretend::for_client(middleware_client.clone()).with_url(url());
+    runtimes::block_on(async {
+        TestApi::get_user(&pretend, 1).await.unwrap();
+    });
+
+    let pretend = Pretend::for_client(middleware_client.clone()).with_url(url());
+    pretend.delete_user(1).unwrap();
+
+    BlockingClient::execute(
+        &middleware_client,
+        Method::GET,
+        url(),
+        HeaderMap::new(),
+        None,
+    )
+    .unwrap();
+
+    let delete_user = Endpoint::new("TestApiBlocking", "delete_user", "/users/{id}");
+    let expected = vec![Some(GET_USER), Some(delete_user), None];
+    assert_eq!(client.endpoints(), expected);
+}
@@ -0,0 +1,119 @@
+mod mock_client;
+mod runtimes;
+
+use self::mock_client::{response, url, MockClient};
+use pretend::client::Bytes;
+use pretend::{pretend, HeaderMap, Json, Pretend, Result, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+struct User {
+    name: String,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+struct Page {
+    page: u32,
+}
+
+#[pretend]
+trait TestApi {
+    #[request(method = "GET", path = "/items/{id}")]
+    async fn get<T>(&self, id: impl Display + Send) -> Result<Json<T>>
+    where
+        T: DeserializeOwned;
+    #[request(method = "POST", path = "/items")]
+    async fn post<T>(&self, json: &T) -> Result<()>
+    where
+        T: Serialize + Sync;
+    #[request(method = "GET", path = "/items")]
+    async fn list(&self, query: &(impl Serialize + Sync)) -> Result<()>;
+}
+
+#[pretend(?Send)]
+trait TestApiLocal {
+    #[request(method = "POST", path = "/items")]
+    async fn post_form<T: Serialize, R: DeserializeOwned>(&self, form: &T) -> Result<Json<R>>;
+}
+
+#[pretend]
+trait TestApiBlocking {
+    #[request(method = "GET", path = "/items/{id}")]
+    fn get_blocking<T: DeserializeOwned>(&self, id: impl Display) -> Result<Json<T>>;
+    #[request(method = "PUT", path = "/items/{id}")]
+    fn put<B: Into<Bytes>>(&self, id: u32, body: B) -> Result<()>;
+}
+
+fn json_client(body: &'static str) -> MockClient {
+    MockClient::new(move |_| Ok(response(StatusCode::OK, HeaderMap::new(), body)))
+}
+
+#[test]
+fn test_generic_responses() {
+    runtimes::block_on(async {
+        let client = json_client(r#"{"name":"Alice"}"#);
+        let pretend = Pretend::for_client(client.clone()).with_url(url("http://localhost"));
+        let user = pretend.get::<User>(1).await.unwrap();
+        assert_eq!(user.value().name, "Alice");
+
+        let client = json_client(r#"{"page":2}"#);
+        let pretend = Pretend::for_client(client.clone()).with_url(url("http://localhost"));
+        let page = pretend.get::<Page>("first").await.unwrap();
+        assert_eq!(page.value().page, 2);
+
+        let requests = client.requests();
+        assert_eq!(requests[0].url.as_str(), "http://localhost/items/first");
+    })
+}
+
+#[test]
+fn test_generic_bodies() {
+    runtimes::block_on(async {
+        let client = MockClient::ok();
+        let pretend = Pretend::for_client(client.clone()).with_url(url("http://localhost"));
+        let user = User {
+            name: "Alice".to_string(),
+        };
+        pretend.post(&user).await.unwrap();
+        pretend.post(&Page { page: 1 }).await.unwrap();
+        pretend.list(&Page { page: 3 }).await.unwrap();
+
+        let requests = client.requests();
+        let body = requests[0].body.as_ref().unwrap();
+        assert_eq!(body, &Bytes::from(r#"{"name":"Alice"}"#));
+        let body = requests[1].body.as_ref().unwrap();
+        assert_eq!(body, &Bytes::from(r#"{"page":1}"#));
+        assert_eq!(requests[2].url.as_str(), "http://localhost/items?page=3");
+    })
+}
+
+#[test]
+fn test_generic_local() {
+    runtimes::block_on(async {
+        let client = json_client(r#"{"name":"Alice"}"#);
+        let pretend = Pretend::for_client(client.clone()).with_url(url("http://localhost"));
+        let user: Json<User> = pretend.post_form(&Page { page: 1 }).await.unwrap();
+        assert_eq!(user.value().name, "Alice");
+
+        let requests = client.requests();
+        let body = requests[0].body.as_ref().unwrap();
+        assert_eq!(body, &Bytes::from("page=1"));
+    })
+}
+
+#[test]
+fn test_generic_blocking() {
+    let client = json_client(r#"{"name":"Alice"}"#);
+    let pretend = Pretend::for_client(client.clone()).with_url(url("http://localhost"));
+    let user = pretend.get_blocking::<User>(1).unwrap();
+    assert_eq!(user.value().name, "Alice");
+
+    pretend.put(2, "raw").unwrap();
+    pretend.put(3, vec![1, 2, 3]).unwrap();
+
+    let requests = client.requests();
+    assert_eq!(requests[1].body, Some(Bytes::from("raw")));
+    assert_eq!(requests[2].body, Some(Bytes::from(vec![1, 2, 3])));
+}
@@ -0,0 +1,197 @@
+mod mock_client;
+mod runtimes;
+
+use mock_client::{response, url, MockClient};
+use pretend::interceptor::{InterceptRequest, Request};
+use pretend::metrics::{RecordMetrics, RequestMetrics};
+use pretend::{pretend, Endpoint, Error, HeaderMap, Json, Pretend, Result, StatusCode};
+use std::io;
+use std::sync::{Arc, Mutex};
+
+#[pretend]
+trait TestApi {
+    #[request(method = "GET", path = "/users/{id}")]
+    async fn get_user(&self, id: i32) -> Result<()>;
+}
+
+#[pretend]
+trait TestApiJson {
+    #[request(method = "GET", path = "/users/{id}")]
+    async fn get_user(&self, id: i32) -> Result<Json<Vec<i32>>>;
+}
+
+#[pretend(?Send)]
+trait TestApiLocal {
+    #[request(method = "GET", path = "/users/{id}")]
+    async fn get_user(&self, id: i32) -> Result<()>;
+}
+
+#[pretend]
+trait TestApiBlocking {
+    #[request(method = "DELETE", path = "/users/{id}")]
+    fn delete_user(&self, id: i32) -> Result<()>;
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Record {
+    endpoint: Endpoint,
+    method: String,
+    status_class: Option<&'static str>,
+    error_kind: Option<&'static str>,
+}
+
+#[derive(Clone, Default)]
+struct TestRecorder {
+    records: Arc<Mutex<Vec<Record>>>,
+}
+
+impl TestRecorder {
+    fn records(&self) -> Vec<Record> {
+        self.records.lock().unwrap().clone()
+    }
+}
+
+impl RecordMetrics for TestRecorder {
+    fn record(&self, metrics: &RequestMetrics) {
+        let record = Record {
+            endpoint: *metrics.endpoint,
+            method: metrics.method.to_string(),
+            status_class: metrics.status_class(),
+            error_kind: metrics.error_kind(),
+        };
+        self.records.lock().unwrap().push(record);
+    }
+}
+
+struct FailingInterceptor;
+
+impl InterceptRequest for FailingInterceptor {
+    fn intercept(&self, _: Request) -> Result<Request> {
+        let err = io::Error::new(io::ErrorKind::PermissionDenied, "missing credentials");
+        Err(Error::request(err))
+    }
+}
+
+fn status_client() -> MockClient {
+    MockClient::new(|request| {
+        let status = match request.url.path() {
+            "/users/1" => StatusCode::OK,
+            "/users/2" => StatusCode::NOT_FOUND,
+            "/users/4" => StatusCode::from_u16(600).unwrap(),
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        Ok(response(status, HeaderMap::new(), ""))
+    })
+}
+
+const GET_USER: Endpoint = Endpoint::new("TestApi", "get_user", "/users/{id}");
+
+#[test]
+fn metrics_are_recorded_for_each_request() {
+    let recorder = TestRecorder::default();
+    let pretend = Pretend::for_client(status_client())
+        .with_url(url("http://localhost"))
+        .with_metrics_recorder(recorder.clone());
+
+    runtimes::block_on(async {
+        assert!(TestApi::get_user(&pretend, 1).await.is_ok());
+        assert!(TestApi::get_user(&pretend, 2).await.is_err());
+        assert!(TestApi::get_user(&pretend, 3).await.is_err());
+        assert!(TestApi::get_user(&pretend, 4).await.is_err());
+    });
+
+    let record = |status_class, error_kind| Record {
+        endpoint: GET_USER,
+        method: "GET".to_string(),
+        status_class: Some(status_class),
+        error_kind,
+    };
+    let expected = vec![
+        record("2xx", None),
+        record("4xx", Some("status")),
+        record("5xx", Some("status")),
+        record("other", Some("status")),
+    ];
+    assert_eq!(recorder.records(), expected);
+}
+
+#[test]
+fn metrics_are_recorded_for_all_client_kinds() {
+    let recorder = TestRecorder::default();
+    let pretend = Pretend::for_client(status_client())
+        .with_url(url("http://localhost"))
+        .with_metrics_recorder(recorder.clone());
+
+    runtimes::block_on(async {
+        TestApiLocal::get_user(&pretend, 1).await.unwrap();
+    });
+    pretend.delete_user(1).unwrap();
+
+    let expected = vec![
+        Record {
+            endpoint: Endpoint::new("TestApiLocal", "get_user", "/users/{id}"),
+            method: "GET".to_string(),
+            status_class: Some("2xx"),
+            error_kind: None,
+        },
+        Record {
+            endpoint: Endpoint::new("TestApiBlocking", "delete_user", "/users/{id}"),
+            method: "DELETE".to_string(),
+            status_class: Some("2xx"),
+            error_kind: None,
+        },
+    ];
+    assert_eq!(recorder.records(), expected);
+}
+
+#[test]
+fn metrics_record_error_kinds() {
+    let recorder = TestRecorder::default();
+    let client = MockClient::new(|_| Err(Error::CircuitOpen));
+    let pretend = Pretend::for_client(client)
+        .with_url(url("http://localhost"))
+        .with_metrics_recorder(recorder.clone());
+
+    runtimes::block_on(async {
+        assert!(TestApi::get_user(&pretend, 1).await.is_err());
+    });
+
+    let pretend = Pretend::for_client(MockClient::ok())
+        .with_url(url("http://localhost"))
+        .with_request_interceptor(FailingInterceptor)
+        .with_metrics_recorder(recorder.clone());
+    runtimes::block_on(async {
+        assert!(TestApi::get_user(&pretend, 1).await.is_err());
+    });
+
+    let record = |error_kind| Record {
+        endpoint: GET_USER,
+        method: "GET".to_string(),
+        status_class: None,
+        error_kind: Some(error_kind),
+    };
+    let expected = vec![record("circuit_open"), record("request")];
+    assert_eq!(recorder.records(), expected);
+}
+
+#[test]
+fn metrics_record_conversion_errors() {
+    let recorder = TestRecorder::default();
+    let client = MockClient::new(|_| Ok(response(StatusCode::OK, HeaderMap::new(), "{")));
+    let pretend = Pretend::for_client(client)
+        .with_url(url("http://localhost"))
+        .with_metrics_recorder(recorder.clone());
+
+    runtimes::block_on(async {
+        let result = TestApiJson::get_user(&pretend, 1).await;
+        assert!(matches!(result, Err(Error::Body(_))));
+    });
+
+    let expected = vec![Record {
+        endpoint: Endpoint::new("TestApiJson", "get_user", "/users/{id}"),
+        method: "GET".to_string(),
+        status_class: None,
+        error_kind: Some("body"),
+    }];
+    assert_eq!(recorder.records(), expected);
+}
@@ -72,8 +72,7 @@ fn test_output() {
 async fn test_status_unit() {
     let api = new_pretend();
 
-    let result = api.get_unit(200).await.unwrap();
-    assert_eq!(result, ());
+    api.get_unit(200).await.unwrap();
 
     let result = api.get_unit(402).await;
     assert_eq!(get_err_status(result), Some(402));
@@ -134,11 +133,11 @@ async fn test_status_bytes_response() {
 
     let result = api.get_bytes_response(200).await.unwrap();
     assert_eq!(*result.status(), StatusCode::from_u16(200).unwrap());
-    assert_eq!(String::from_utf8_lossy(&result.body()), "Hello World");
+    assert_eq!(String::from_utf8_lossy(result.body()), "Hello World");
 
     let result = api.get_bytes_response(402).await.unwrap();
     assert_eq!(*result.status(), StatusCode::from_u16(402).unwrap());
-    assert_eq!(String::from_utf8_lossy(&result.body()), "Error");
+    assert_eq!(String::from_utf8_lossy(result.body()), "Error");
 }
 
 async fn test_status_json() {
@@ -0,0 +1,223 @@
+mod mock_client;
+mod runtimes;
+
+use mock_client::{response, url, MockClient};
+use pretend::client::{BlockingClient, Client, LocalClient, Method};
+use pretend::http::HeaderValue;
+use pretend::middleware::ratelimit::{Quota, RateLimitError, RateLimiter};
+use pretend::middleware::{LocalMiddlewareClient, MiddlewareClient};
+use pretend::{Endpoint, Error, HeaderMap, Result, StatusCode};
+use std::time::{Duration, Instant};
+
+fn get<C>(client: &C, path: &str) -> Result<()>
+where
+    C: BlockingClient,
+{
+    let response = client.execute(Method::GET, url(path), HeaderMap::new(), None)?;
+    assert_eq!(*response.status(), StatusCode::OK);
+    Ok(())
+}
+
+fn get_retry_after(result: Result<()>) -> Option<Duration> {
+    match result {
+        Err(Error::Request(err)) => {
+            let err = err.downcast_ref::<RateLimitError>()?;
+            Some(err.retry_after())
+        }
+        _ => None,
+    }
+}
+
+#[test]
+fn rate_limiter_fails_fast() {
+    let limiter = RateLimiter::new()
+        .with_quota(Quota::per_minute(2))
+        .with_fail_fast(true);
+    let client = MiddlewareClient::new(MockClient::ok(), limiter);
+
+    assert!(get(&client, "http://localhost/1").is_ok());
+    assert!(get(&client, "http://localhost/2").is_ok());
+
+    let retry_after = get_retry_after(get(&client, "http://localhost/3")).unwrap();
+    assert!(retry_after > Duration::from_secs(25));
+    assert_eq!(client.client().requests().len(), 2);
+}
+
+#[test]
+fn rate_limiter_waits() {
+    let limiter = RateLimiter::new().with_quota(Quota::new(1, Duration::from_millis(200)));
+    let client = MiddlewareClient::new(MockClient::ok(), limiter);
+
+    let start = Instant::now();
+    assert!(get(&client, "http://localhost/1").is_ok());
+    assert!(get(&client, "http://localhost/2").is_ok());
+    assert!(start.elapsed() >= Duration::from_millis(150));
+    assert_eq!(client.client().requests().len(), 2);
+}
+
+#[test]
+fn rate_limiter_uses_host_quotas() {
+    let limiter = RateLimiter::new()
+        .with_host_quota("limited", Quota::per_minute(1))
+        .with_fail_fast(true);
+    let client = MiddlewareClient::new(MockClient::ok(), limiter);
+
+    assert!(get(&client, "http://limited/1").is_ok());
+    assert!(get_retry_after(get(&client, "http://limited/2")).is_some());
+
+    for _ in 0..5 {
+        assert!(get(&client, "http://unlimited/").is_ok());
+    }
+}
+
+#[test]
+fn rate_limiter_is_shared_between_clones() {
+    let limiter = RateLimiter::new()
+        .with_quota(Quota::per_minute(1))
+        .with_fail_fast(true);
+    let client1 = MiddlewareClient::new(MockClient::ok(), limiter.clone());
+    let client2 = MiddlewareClient::new(MockClient::ok(), limiter);
+
+    assert!(get(&client1, "http://localhost/").is_ok());
+    assert!(get_retry_after(get(&client2, "http://localhost/")).is_some());
+}
+
+#[test]
+fn rate_limiter_adapts_to_retry_after() {
+    let mock = MockClient::new(|request| {
+        if request.url.path() == "/limited" {
+            let mut headers = HeaderMap::new();
+            headers.insert("Retry-After", HeaderValue::from_static("60"));
+            Ok(response(StatusCode::TOO_MANY_REQUESTS, headers, ""))
+        } else {
+            Ok(response(StatusCode::OK, HeaderMap::new(), ""))
+        }
+    });
+    let limiter = RateLimiter::new().with_adaptive(true).with_fail_fast(true);
+    let client = MiddlewareClient::new(mock, limiter);
+
+    let result = BlockingClient::execute(
+        &client,
+        Method::GET,
+        url("http://localhost/limited"),
+        HeaderMap::new(),
+        None,
+    );
+    assert_eq!(*result.unwrap().status(), StatusCode::TOO_MANY_REQUESTS);
+
+    let retry_after = get_retry_after(get(&client, "http://localhost/")).unwrap();
+    assert!(retry_after > Duration::from_secs(55));
+    assert!(get(&client, "http://other/").is_ok());
+}
+
+#[test]
+fn rate_limiter_adapts_to_remaining() {
+    let mock = MockClient::new(|_| {
+        let mut headers = HeaderMap::new();
+        headers.insert("X-RateLimit-Remaining", HeaderValue::from_static("0"));
+        headers.insert("X-RateLimit-Reset", HeaderValue::from_static("30"));
+        Ok(response(StatusCode::OK, headers, ""))
+    });
+    let limiter = RateLimiter::new()
+        .with_quota(Quota::per_second(100))
+        .with_adaptive(true)
+        .with_fail_fast(true);
+    let client = MiddlewareClient::new(mock, limiter);
+
+    assert!(get(&client, "http://localhost/").is_ok());
+    let retry_after = get_retry_after(get(&client, "http://localhost/")).unwrap();
+    assert!(retry_after > Duration::from_secs(25));
+}
+
+#[test]
+fn rate_limiter_caps_huge_delays() {
+    check_delay_is_capped("1000000000000");
+    check_delay_is_capped("18446744073709551615");
+}
+
+fn check_delay_is_capped(delay: &'static str) {
+    let mock = MockClient::new(move |request| {
+        let mut headers = HeaderMap::new();
+        let huge = HeaderValue::from_static(delay);
+        if request.url.path() == "/limited" {
+            headers.insert("Retry-After", huge);
+            Ok(response(StatusCode::TOO_MANY_REQUESTS, headers, ""))
+        } else {
+            headers.insert("X-RateLimit-Remaining", HeaderValue::from_static("0"));
+            headers.insert("X-RateLimit-Reset", huge);
+            Ok(response(StatusCode::OK, headers, ""))
+        }
+    });
+    let limiter = RateLimiter::new().with_adaptive(true).with_fail_fast(true);
+    let client = MiddlewareClient::new(mock, limiter);
+
+    let result = BlockingClient::execute(
+        &client,
+        Method::GET,
+        url("http://localhost/limited"),
+        HeaderMap::new(),
+        None,
+    );
+    assert_eq!(*result.unwrap().status(), StatusCode::TOO_MANY_REQUESTS);
+    let retry_after = get_retry_after(get(&client, "http://localhost/")).unwrap();
+    assert!(retry_after > Duration::from_secs(23 * 60 * 60));
+    assert!(retry_after <= Duration::from_secs(24 * 60 * 60));
+
+    assert!(get(&client, "http://other/").is_ok());
+    let retry_after = get_retry_after(get(&client, "http://other/")).unwrap();
+    assert!(retry_after > Duration::from_secs(23 * 60 * 60));
+    assert!(retry_after <= Duration::from_secs(24 * 60 * 60));
+}
+
+#[test]
+fn rate_limiter_supports_async_clients() {
+    let limiter = RateLimiter::new()
+        .with_quota(Quota::per_minute(1))
+        .with_fail_fast(true);
+    let client = MiddlewareClient::new(MockClient::ok(), limiter.clone());
+    let local_client = LocalMiddlewareClient::new(MockClient::ok(), limiter);
+
+    runtimes::block_on(async {
+        let result = Client::execute(
+            &client,
+            Method::GET,
+            url("http://localhost/"),
+            HeaderMap::new(),
+            None,
+        )
+        .await;
+        assert!(result.is_ok());
+
+        let result = LocalClient::execute(
+            &local_client,
+            Method::GET,
+            url("http://localhost/"),
+            HeaderMap::new(),
+            None,
+        )
+        .await;
+        assert!(result.is_err());
+    });
+}
+
+#[test]
+fn rate_limiter_uses_endpoint_quotas() {
+    let get_user = Endpoint::new("UsersApi", "get_user", "/users/{id}");
+    let limiter = RateLimiter::new()
+        .with_endpoint_quota(get_user, Quota::per_minute(1))
+        .with_fail_fast(true);
+    let client = MiddlewareClient::new(MockClient::ok(), limiter);
+
+    let execute = |endpoint: &Endpoint| {
+        let url = url("http://localhost/users/1");
+        let headers = HeaderMap::new();
+        BlockingClient::execute_with_endpoint(&client, endpoint, Method::GET, url, headers, None)
+    };
+
+    assert!(execute(&get_user).is_ok());
+    assert!(execute(&get_user).is_err());
+
+    let other = Endpoint::new("UsersApi", "get_user_v2", "/users/{id}");
+    assert!(execute(&other).is_ok());
+    assert!(get(&client, "http://localhost/users/1").is_ok());
+}
@@ -0,0 +1,202 @@
+mod mock_client;
+
+use mock_client::{response, url, MockClient, MockRequest};
+use pretend::client::{BlockingClient, Bytes, Method};
+use pretend::http::HeaderValue;
+use pretend::middleware::redirect::{Redirect, TooManyRedirects};
+use pretend::middleware::MiddlewareClient;
+use pretend::{Error, HeaderMap, Response, Result, StatusCode};
+
+type RedirectClient = MiddlewareClient<MockClient, Redirect>;
+
+// /chain/{n} redirects to /chain/{n - 1}, until 0
+// /redirect/{status}?to={location} redirects to location
+fn redirect_client(redirect: Redirect) -> RedirectClient {
+    let mock = MockClient::new(|request| {
+        let mut segments = request.url.path_segments().unwrap();
+        let location = match (segments.next(), segments.next()) {
+            (Some("chain"), Some(count)) => match count.parse::<u32>().unwrap() {
+                0 => None,
+                count => Some((StatusCode::FOUND, format!("/chain/{}", count - 1))),
+            },
+            (Some("redirect"), Some(status)) => {
+                let status = StatusCode::from_bytes(status.as_bytes()).unwrap();
+                let mut query = request.url.query_pairs();
+                let location = query.find(|(name, _)| name == "to").unwrap().1;
+                Some((status, location.into_owned()))
+            }
+            _ => None,
+        };
+
+        match location {
+            Some((status, location)) => {
+                let mut headers = HeaderMap::new();
+                let location = HeaderValue::from_str(&location).unwrap();
+                headers.insert("Location", location);
+                Ok(response(status, headers, ""))
+            }
+            None => Ok(response(StatusCode::OK, HeaderMap::new(), "Hello")),
+        }
+    });
+    MiddlewareClient::new(mock, redirect)
+}
+
+fn execute(
+    client: &RedirectClient,
+    method: Method,
+    path: &str,
+    headers: HeaderMap,
+    body: Option<&'static str>,
+) -> Result<Response<Bytes>> {
+    let url = url("http://localhost").join(path).unwrap();
+    let body = body.map(Bytes::from);
+    BlockingClient::execute(client, method, url, headers, body)
+}
+
+fn last_request(client: &RedirectClient) -> MockRequest {
+    client.client().requests().pop().unwrap()
+}
+
+#[test]
+fn redirect_follows_redirects() {
+    let client = redirect_client(Redirect::new());
+
+    let response = execute(&client, Method::GET, "/chain/2", HeaderMap::new(), None).unwrap();
+    assert_eq!(*response.status(), StatusCode::OK);
+    assert_eq!(response.body(), "Hello");
+    let final_url = url("http://localhost/chain/0");
+    assert_eq!(response.url(), Some(&final_url));
+    assert_eq!(client.client().requests().len(), 3);
+}
+
+#[test]
+fn redirect_sets_url_without_redirects() {
+    let client = redirect_client(Redirect::new());
+
+    let response = execute(&client, Method::GET, "/hello", HeaderMap::new(), None).unwrap();
+    let final_url = url("http://localhost/hello");
+    assert_eq!(response.url(), Some(&final_url));
+}
+
+#[test]
+fn redirect_url_is_ignored_by_equality() {
+    let response = response(StatusCode::OK, HeaderMap::new(), "Hello");
+    let with_url = response.clone().with_url(url("http://localhost/hello"));
+    assert_eq!(response, with_url);
+}
+
+#[test]
+fn redirect_resolves_relative_locations() {
+    let client = redirect_client(Redirect::new());
+
+    let path = "/redirect/307?to=../hello?page=2";
+    let response = execute(&client, Method::GET, path, HeaderMap::new(), None).unwrap();
+    let final_url = url("http://localhost/hello?page=2");
+    assert_eq!(response.url(), Some(&final_url));
+}
+
+#[test]
+fn redirect_only_follows_http_locations() {
+    let client = redirect_client(Redirect::new());
+
+    let path = "/redirect/302?to=file:///etc/passwd";
+    let response = execute(&client, Method::GET, path, HeaderMap::new(), None).unwrap();
+    assert_eq!(*response.status(), StatusCode::FOUND);
+    assert_eq!(client.client().requests().len(), 1);
+
+    let path = "/redirect/302?to=https://example.com/hello";
+    execute(&client, Method::GET, path, HeaderMap::new(), None).unwrap();
+    assert_eq!(
+        last_request(&client).url.as_str(),
+        "https://example.com/hello"
+    );
+}
+
+#[test]
+fn redirect_uses_get_after_303() {
+    let client = redirect_client(Redirect::new());
+    let mut headers = HeaderMap::new();
+    headers.insert("Content-Type", HeaderValue::from_static("text/plain"));
+    headers.insert("X-Test", HeaderValue::from_static("value"));
+
+    let path = "/redirect/303?to=/hello";
+    execute(&client, Method::PUT, path, headers, Some("body")).unwrap();
+    let request = last_request(&client);
+    assert_eq!(request.method, Method::GET);
+    assert_eq!(request.body, None);
+    assert!(request.headers.get("Content-Type").is_none());
+    assert_eq!(request.headers.get("X-Test").unwrap(), "value");
+
+    execute(&client, Method::HEAD, path, HeaderMap::new(), None).unwrap();
+    assert_eq!(last_request(&client).method, Method::HEAD);
+}
+
+#[test]
+fn redirect_uses_get_for_post_after_302() {
+    let client = redirect_client(Redirect::new());
+
+    let path = "/redirect/302?to=/hello";
+    execute(&client, Method::POST, path, HeaderMap::new(), Some("body")).unwrap();
+    let request = last_request(&client);
+    assert_eq!(request.method, Method::GET);
+    assert_eq!(request.body, None);
+
+    execute(&client, Method::PUT, path, HeaderMap::new(), Some("body")).unwrap();
+    let request = last_request(&client);
+    assert_eq!(request.method, Method::PUT);
+    assert_eq!(request.body, Some(Bytes::from("body")));
+}
+
+#[test]
+fn redirect_keeps_method_after_307_and_308() {
+    let client = redirect_client(Redirect::new());
+
+    for path in &["/redirect/307?to=/hello", "/redirect/308?to=/hello"] {
+        execute(&client, Method::POST, path, HeaderMap::new(), Some("body")).unwrap();
+        let request = last_request(&client);
+        assert_eq!(request.method, Method::POST);
+        assert_eq!(request.body, Some(Bytes::from("body")));
+    }
+}
+
+#[test]
+fn redirect_strips_sensitive_headers_across_origins() {
+    let client = redirect_client(Redirect::new());
+    let mut headers = HeaderMap::new();
+    headers.insert("Authorization", HeaderValue::from_static("Bearer token"));
+    headers.insert("Cookie", HeaderValue::from_static("session=1"));
+    headers.insert("Proxy-Authorization", HeaderValue::from_static("Basic abc"));
+    headers.insert("X-Test", HeaderValue::from_static("value"));
+
+    let path = "/redirect/302?to=/hello";
+    execute(&client, Method::GET, path, headers.clone(), None).unwrap();
+    let request = last_request(&client);
+    assert_eq!(request.headers.len(), 4);
+
+    let path = "/redirect/302?to=http://localhost:8080/hello";
+    execute(&client, Method::GET, path, headers, None).unwrap();
+    let request = last_request(&client);
+    assert_eq!(request.url.as_str(), "http://localhost:8080/hello");
+    assert!(request.headers.get("Authorization").is_none());
+    assert!(request.headers.get("Cookie").is_none());
+    assert!(request.headers.get("Proxy-Authorization").is_none());
+    assert_eq!(request.headers.get("X-Test").unwrap(), "value");
+}
+
+#[test]
+fn redirect_fails_after_limit() {
+    let client = redirect_client(Redirect::new().with_limit(1));
+
+    let result = execute(&client, Method::GET, "/chain/2", HeaderMap::new(), None);
+    let err = match result {
+        Err(Error::Response(err)) => err,
+        _ => panic!("Expected a response error"),
+    };
+    let err = err.downcast_ref::<TooManyRedirects>().unwrap();
+    assert_eq!(err.limit(), 1);
+    assert_eq!(client.client().requests().len(), 2);
+
+    let client = redirect_client(Redirect::new().with_limit(2));
+    let result = execute(&client, Method::GET, "/chain/2", HeaderMap::new(), None);
+    assert!(result.is_ok());
+}
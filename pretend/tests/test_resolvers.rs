@@ -0,0 +1,142 @@
+mod runtimes;
+mod server;
+
+use self::api::{TestApi, TestApiBlocking};
+use pretend::client::{async_trait, Method};
+use pretend::resolver::{
+    AsyncResolveUrl, BasePathUrlResolver, ParseError, ResolveContext, ResolveUrl, UrlResolver,
+};
+use pretend::{Pretend, Result, Url};
+use pretend_reqwest::{BlockingClient, Client};
+
+fn resolve<R>(resolver: &R, path: &str) -> String
+where
+    R: ResolveUrl,
+{
+    resolver.resolve_url(path).unwrap().to_string()
+}
+
+#[test]
+fn url_resolver_joins_path() {
+    let url = Url::parse("http://localhost/api/v2").unwrap();
+    let resolver = UrlResolver::new(url);
+    assert_eq!(resolve(&resolver, "/users"), "http://localhost/users");
+    assert_eq!(resolve(&resolver, "users"), "http://localhost/api/users");
+}
+
+#[test]
+fn base_path_url_resolver_appends_path() {
+    let url = Url::parse("http://localhost/api/v2").unwrap();
+    let resolver = BasePathUrlResolver::new(url);
+    assert_eq!(
+        resolve(&resolver, "/users"),
+        "http://localhost/api/v2/users"
+    );
+    assert_eq!(resolve(&resolver, "users"), "http://localhost/api/v2/users");
+    assert_eq!(
+        resolve(&resolver, "/users?id=1"),
+        "http://localhost/api/v2/users?id=1"
+    );
+}
+
+#[test]
+fn base_path_url_resolver_ignores_trailing_slash() {
+    let url = Url::parse("http://localhost/api/v2/").unwrap();
+    let resolver = BasePathUrlResolver::new(url);
+    assert_eq!(
+        resolve(&resolver, "/users"),
+        "http://localhost/api/v2/users"
+    );
+}
+
+#[test]
+fn base_path_url_resolver_without_base_path() {
+    let url = Url::parse("http://localhost").unwrap();
+    let resolver = BasePathUrlResolver::new(url);
+    assert_eq!(resolve(&resolver, "/users"), "http://localhost/users");
+}
+
+#[test]
+fn resolvers_are_async_resolvers() {
+    runtimes::block_on(async {
+        let url = Url::parse("http://localhost/api/v2").unwrap();
+        let resolver = BasePathUrlResolver::new(url);
+        let endpoint = pretend::Endpoint::new("TestApi", "get", "/users");
+        let context = ResolveContext::new(&Method::GET, "/users", &endpoint);
+
+        let url = resolver.resolve_url("/users").unwrap();
+        let async_url = resolver.resolve_url_async(&context).await.unwrap();
+        assert_eq!(url, async_url);
+    })
+}
+
+mod api {
+    use pretend::{pretend, Result};
+
+    #[pretend]
+    pub trait TestApi {
+        #[request(method = "GET", path = "/{name}")]
+        async fn get(&self, name: &str) -> Result<String>;
+    }
+
+    #[pretend]
+    pub trait TestApiBlocking {
+        #[request(method = "GET", path = "/{name}")]
+        fn get(&self, name: &str) -> Result<String>;
+    }
+}
+
+struct DiscoveryResolver;
+
+#[async_trait]
+impl AsyncResolveUrl for DiscoveryResolver {
+    async fn resolve_url_async(&self, context: &ResolveContext<'_>) -> Result<Url> {
+        assert_eq!(*context.method, Method::GET);
+        assert_eq!(context.path, "/not-found");
+        assert_eq!(context.endpoint.trait_name(), "TestApi");
+        assert_eq!(context.endpoint.name(), "get");
+        assert_eq!(context.endpoint.path(), "/{name}");
+
+        let url = Url::parse(server::URL).unwrap();
+        Ok(url.join("/method").unwrap())
+    }
+}
+
+struct ContextResolver;
+
+impl ResolveUrl for ContextResolver {
+    fn resolve_url(&self, _: &str) -> std::result::Result<Url, ParseError> {
+        Err(ParseError::EmptyHost)
+    }
+
+    fn resolve_url_with_context(
+        &self,
+        context: &ResolveContext,
+    ) -> std::result::Result<Url, ParseError> {
+        assert_eq!(context.endpoint.trait_name(), "TestApiBlocking");
+        let url = Url::parse(server::URL)?;
+        url.join("/method")
+    }
+}
+
+fn pretend_uses_async_resolver() {
+    runtimes::block_on(async {
+        let client = Pretend::for_client(Client::default()).with_url_resolver(DiscoveryResolver);
+        let result = TestApi::get(&client, "not-found").await.unwrap();
+        assert_eq!(result, "GET");
+    })
+}
+
+fn pretend_uses_resolver_context() {
+    let client = Pretend::for_client(BlockingClient::default()).with_url_resolver(ContextResolver);
+    let result = TestApiBlocking::get(&client, "not-found").unwrap();
+    assert_eq!(result, "GET");
+}
+
+#[test]
+fn pretend_resolvers() {
+    server::test(|| {
+        pretend_uses_async_resolver();
+        pretend_uses_resolver_context();
+    })
+}
@@ -0,0 +1,259 @@
+mod runtimes;
+
+use pretend::client::Bytes;
+use pretend::server::Reply;
+use pretend::{pretend, Error, Json, Pretend, Response, Result, StatusCode, Url};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::{Arc, Mutex};
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+struct User {
+    id: i32,
+    name: String,
+}
+
+#[derive(Deserialize, Serialize)]
+struct Filter {
+    name: String,
+}
+
+#[pretend(server)]
+trait TestApi {
+    #[request(method = "GET", path = "/users/{id}")]
+    async fn get_user(&self, id: i32) -> Result<Json<User>>;
+    #[request(method = "POST", path = "/users")]
+    #[header(name = "Authorization", value = "Bearer {token}")]
+    async fn create_user(&self, token: &str, json: &User) -> Result<Response<()>>;
+}
+
+#[pretend(?Send, server)]
+trait TestApiLocal {
+    #[request(method = "GET", path = "/users/{id}")]
+    async fn get_local_user(&self, id: i32) -> Result<Json<User>>;
+}
+
+#[pretend(server)]
+trait TestApiBlocking {
+    #[request(method = "GET", path = "/users/{id}/name")]
+    fn get_name(&self, id: i32) -> Result<String>;
+    #[request(method = "GET", path = "/users?page={page}")]
+    fn list_users(&self, page: u32, query: &Filter) -> Result<String>;
+    #[request(method = "PUT", path = "/users/{id}/avatar")]
+    fn set_avatar(&self, id: i32, body: &'static [u8]) -> Result<Vec<u8>>;
+    #[request(method = "DELETE", path = "/users")]
+    fn delete_users(&self) -> Result<()>;
+}
+
+#[derive(Default)]
+struct Handler {
+    users: Mutex<HashMap<i32, User>>,
+}
+
+impl Handler {
+    fn with_user(id: i32, name: &str) -> Self {
+        let handler = Handler::default();
+        let name = name.to_string();
+        handler.users.lock().unwrap().insert(id, User { id, name });
+        handler
+    }
+
+    fn user(&self, id: i32) -> Option<User> {
+        self.users.lock().unwrap().get(&id).cloned()
+    }
+}
+
+#[pretend::client::async_trait]
+impl TestApiHandler for Handler {
+    async fn get_user(&self, id: i32) -> Reply {
+        match self.user(id) {
+            Some(user) => Reply::json(&user),
+            None => Reply::new(StatusCode::NOT_FOUND),
+        }
+    }
+
+    async fn create_user(&self, token: String, json: User) -> Reply {
+        if token != "secret" {
+            return Reply::new(StatusCode::UNAUTHORIZED);
+        }
+        self.users.lock().unwrap().insert(json.id, json);
+        Reply::new(StatusCode::CREATED)
+    }
+}
+
+#[pretend::client::async_trait(?Send)]
+impl TestApiLocalHandler for Handler {
+    async fn get_local_user(&self, id: i32) -> Reply {
+        TestApiHandler::get_user(self, id).await
+    }
+}
+
+impl TestApiBlockingHandler for Handler {
+    fn get_name(&self, id: i32) -> Reply {
+        match self.user(id) {
+            Some(user) => Reply::text(user.name),
+            None => Reply::new(StatusCode::NOT_FOUND),
+        }
+    }
+
+    fn list_users(&self, page: u32, query: Filter) -> Reply {
+        Reply::text(format!("{} {}", page, query.name))
+    }
+
+    fn set_avatar(&self, id: i32, body: Bytes) -> Reply {
+        let mut avatar = body.to_vec();
+        avatar.push(id as u8);
+        Reply::bytes(avatar)
+    }
+
+    fn delete_users(&self) -> Reply {
+        self.users.lock().unwrap().clear();
+        Reply::ok()
+    }
+}
+
+fn url() -> Url {
+    Url::parse("http://localhost/api/").unwrap()
+}
+
+#[test]
+fn test_stub_server() {
+    runtimes::block_on(async {
+        let server = TestApiServer::new(Handler::default());
+        let pretend = Pretend::for_client(server).with_url(url());
+
+        let user = User {
+            id: 1,
+            name: "Alice".to_string(),
+        };
+        let response = pretend.create_user("secret", &user).await.unwrap();
+        assert_eq!(*response.status(), StatusCode::CREATED);
+
+        let result = pretend.get_user(1).await.unwrap();
+        assert_eq!(result.value(), user);
+
+        let result = pretend.get_user(2).await;
+        assert!(matches!(result, Err(Error::Status(StatusCode::NOT_FOUND))));
+
+        let response = pretend.create_user("wrong", &user).await.unwrap();
+        assert_eq!(*response.status(), StatusCode::UNAUTHORIZED);
+    })
+}
+
+#[test]
+fn test_stub_server_local() {
+    runtimes::block_on(async {
+        let server = TestApiLocalServer::new(Handler::with_user(1, "Alice"));
+        let pretend = Pretend::for_client(server).with_url(url());
+
+        let result = pretend.get_local_user(1).await.unwrap();
+        assert_eq!(result.value().name, "Alice");
+    })
+}
+
+#[test]
+fn test_stub_server_blocking() {
+    let server = TestApiBlockingServer::new(Handler::with_user(1, "Alice"));
+    let pretend = Pretend::for_client(server).with_url(url());
+
+    let name = pretend.get_name(1).unwrap();
+    assert_eq!(name, "Alice");
+
+    let filter = Filter {
+        name: "Al ice".to_string(),
+    };
+    let result = pretend.list_users(2, &filter).unwrap();
+    assert_eq!(result, "2 Al ice");
+
+    let avatar = pretend.set_avatar(3, &[1, 2]).unwrap();
+    assert_eq!(avatar, vec![1, 2, 3]);
+
+    pretend.delete_users().unwrap();
+    let result = pretend.get_name(1);
+    assert!(matches!(result, Err(Error::Status(StatusCode::NOT_FOUND))));
+}
+
+#[test]
+fn test_stub_server_not_found() {
+    let server = TestApiBlockingServer::new(Handler::default());
+    let url = Url::parse("http://localhost/api/users/1/").unwrap();
+    let pretend = Pretend::for_client(server).with_url(url);
+
+    let result = pretend.get_name(1);
+    assert!(matches!(result, Err(Error::Status(StatusCode::NOT_FOUND))));
+}
+
+#[test]
+fn test_stub_server_bad_request() {
+    #[pretend]
+    trait InvalidApi {
+        #[request(method = "GET", path = "/users/{id}")]
+        fn get_user(&self, id: &str) -> Result<()>;
+    }
+
+    #[pretend(server)]
+    trait ValidApi {
+        #[request(method = "GET", path = "/users/{id}")]
+        fn get_user(&self, id: i32) -> Result<()>;
+    }
+
+    struct Counter(Arc<Mutex<i32>>);
+
+    impl ValidApiHandler for Counter {
+        fn get_user(&self, _: i32) -> Reply {
+            *self.0.lock().unwrap() += 1;
+            Reply::ok()
+        }
+    }
+
+    let calls = Arc::new(Mutex::new(0));
+    let server = ValidApiServer::new(Counter(calls.clone()));
+    assert_eq!(*server.handler().0.lock().unwrap(), 0);
+    let pretend = Pretend::for_client(server).with_url(url());
+
+    let result = InvalidApi::get_user(&pretend, "abc");
+    assert!(matches!(
+        result,
+        Err(Error::Status(StatusCode::BAD_REQUEST))
+    ));
+
+    ValidApi::get_user(&pretend, 1).unwrap();
+    assert_eq!(*calls.lock().unwrap(), 1);
+}
+
+#[test]
+fn test_stub_server_generics() {
+    #[pretend(server)]
+    trait GenericApi {
+        #[request(method = "GET", path = "/items/{id}")]
+        fn get_item<T: DeserializeOwned>(&self, id: impl Display) -> Result<Json<T>>;
+        #[request(method = "POST", path = "/items")]
+        fn create_item<T: Serialize>(&self, json: &T, query: &T) -> Result<String>;
+    }
+
+    struct Items;
+
+    impl GenericApiHandler for Items {
+        fn get_item(&self, id: String) -> Reply {
+            let name = id;
+            Reply::json(&User { id: 1, name })
+        }
+
+        fn create_item(&self, json: Bytes, query: String) -> Reply {
+            let json = String::from_utf8(json.to_vec()).unwrap();
+            Reply::text(format!("{} {}", json, query))
+        }
+    }
+
+    let pretend = Pretend::for_client(GenericApiServer::new(Items)).with_url(url());
+    let user = pretend.get_item::<User>("Alice").unwrap();
+    assert_eq!(user.value().name, "Alice");
+
+    let filter = Filter {
+        name: "Bob".to_string(),
+    };
+    let result = pretend.create_item(&filter, &filter).unwrap();
+    assert_eq!(result, r#"{"name":"Bob"} name=Bob"#);
+}
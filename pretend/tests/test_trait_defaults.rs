@@ -0,0 +1,123 @@
+mod mock_client;
+
+use self::mock_client::{url, MockClient};
+use pretend::server::Reply;
+use pretend::{pretend, Pretend, Result};
+use serde::Serialize;
+
+#[derive(Serialize)]
+struct Query {
+    version: u32,
+}
+
+#[pretend(
+    path = "/api/v1/",
+    header(name = "X-Client", value = "pretend"),
+    header(name = "X-Trace", value = "default"),
+    query(name = "version", value = "1"),
+    accept = "application/json"
+)]
+trait TestApi {
+    #[request(method = "GET", path = "/users")]
+    fn get_users(&self) -> Result<()>;
+
+    #[request(method = "GET", path = "/users?version={version}")]
+    #[header(name = "X-Trace", value = "{trace}")]
+    #[header(name = "Accept", value = "text/plain")]
+    fn get_users_with_overrides(&self, version: u32, trace: &str) -> Result<()>;
+
+    #[request(method = "GET", path = "/users")]
+    fn get_users_with_query(&self, query: &Query) -> Result<()>;
+
+    #[request(method = "GET", path = "groups")]
+    fn get_groups(&self) -> Result<()>;
+}
+
+#[pretend(server, path = "/api")]
+trait UserApi {
+    #[request(method = "GET", path = "/users/{id}")]
+    fn get_user(&self, id: i32) -> Result<String>;
+
+    #[request(method = "GET", path = "users")]
+    fn list_users(&self) -> Result<String>;
+}
+
+fn new_pretend(client: &MockClient) -> impl TestApi {
+    Pretend::for_client(client.clone()).with_url(url("http://localhost"))
+}
+
+#[test]
+fn test_trait_defaults() {
+    let client = MockClient::ok();
+    new_pretend(&client).get_users().unwrap();
+
+    let request = &client.requests()[0];
+    assert_eq!(
+        request.url.as_str(),
+        "http://localhost/api/v1/users?version=1"
+    );
+    assert_eq!(request.headers.get("X-Client").unwrap(), "pretend");
+    assert_eq!(request.headers.get("X-Trace").unwrap(), "default");
+    assert_eq!(request.headers.get("Accept").unwrap(), "application/json");
+}
+
+#[test]
+fn test_trait_defaults_relative_path() {
+    let client = MockClient::ok();
+    new_pretend(&client).get_groups().unwrap();
+
+    let request = &client.requests()[0];
+    assert_eq!(
+        request.url.as_str(),
+        "http://localhost/api/v1/groups?version=1"
+    );
+}
+
+#[test]
+fn test_trait_defaults_overrides() {
+    let client = MockClient::ok();
+    let pretend = new_pretend(&client);
+    pretend.get_users_with_overrides(2, "custom").unwrap();
+    pretend.get_users_with_query(&Query { version: 3 }).unwrap();
+
+    let requests = client.requests();
+    let request = &requests[0];
+    assert_eq!(
+        request.url.as_str(),
+        "http://localhost/api/v1/users?version=2"
+    );
+    assert_eq!(request.headers.get("X-Client").unwrap(), "pretend");
+    let traces = request
+        .headers
+        .get_all("X-Trace")
+        .iter()
+        .collect::<Vec<_>>();
+    assert_eq!(traces, vec!["custom"]);
+    assert_eq!(request.headers.get("Accept").unwrap(), "text/plain");
+
+    let request = &requests[1];
+    assert_eq!(
+        request.url.as_str(),
+        "http://localhost/api/v1/users?version=3"
+    );
+}
+
+#[test]
+fn test_trait_defaults_server() {
+    struct Handler;
+
+    impl UserApiHandler for Handler {
+        fn get_user(&self, id: i32) -> Reply {
+            Reply::text(id.to_string())
+        }
+
+        fn list_users(&self) -> Reply {
+            Reply::text("users")
+        }
+    }
+
+    let server = UserApiServer::new(Handler);
+    let pretend = Pretend::for_client(server).with_url(url("http://localhost"));
+    assert_eq!(pretend.get_user(1).unwrap(), "1");
+    assert_eq!(pretend.list_users().unwrap(), "users");
+}
@@ -0,0 +1,64 @@
+#![cfg(unix)]
+
+mod runtimes;
+
+use hyper::server::conn::Http;
+use hyper::service::service_fn;
+use hyper::{Body, Request};
+use pretend::{pretend, Pretend, Response, Result, Url};
+use pretend_hyper::Client;
+use std::convert::Infallible;
+use std::env;
+use std::fs;
+use tokio::net::UnixListener;
+
+#[pretend]
+trait DaemonApi {
+    #[request(method = "GET", path = "/containers/{id}")]
+    async fn get_container(&self, id: &str) -> Result<Response<String>>;
+    #[request(method = "POST", path = "/containers/create")]
+    async fn create_container(&self, body: &'static str) -> Result<String>;
+}
+
+async fn handle(request: Request<Body>) -> std::result::Result<hyper::Response<Body>, Infallible> {
+    let host = request.headers().get("Host").cloned();
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    let body = hyper::body::to_bytes(request.into_body()).await.unwrap();
+    let body = String::from_utf8(body.to_vec()).unwrap();
+
+    let mut response = hyper::Response::new(Body::from(format!("{} {} {}", method, path, body)));
+    if let Some(host) = host {
+        response.headers_mut().insert("X-Host", host);
+    }
+    Ok(response)
+}
+
+#[test]
+fn test_unix_socket() {
+    let path = env::temp_dir().join(format!("pretend-{}.sock", std::process::id()));
+    let _ = fs::remove_file(&path);
+
+    runtimes::block_on(async {
+        let listener = UnixListener::bind(&path).unwrap();
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                let connection = Http::new().serve_connection(stream, service_fn(handle));
+                tokio::spawn(connection);
+            }
+        });
+
+        let url = Url::parse("http://localhost").unwrap();
+        let pretend = Pretend::for_client(Client::unix(&path)).with_url(url);
+
+        let response = pretend.get_container("abc").await.unwrap();
+        assert_eq!(response.body(), "GET /containers/abc ");
+        assert_eq!(response.headers().get("X-Host").unwrap(), "localhost");
+
+        let result = pretend.create_container("hello").await.unwrap();
+        assert_eq!(result, "POST /containers/create hello");
+    });
+
+    fs::remove_file(&path).unwrap();
+}
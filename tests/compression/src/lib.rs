@@ -0,0 +1 @@
+//! Test crate for the `gzip`, `brotli` and `zstd` features
@@ -0,0 +1,252 @@
+use pretend::client::{BlockingClient, Bytes, Method};
+use pretend::http::HeaderValue;
+use pretend::middleware::compression::{
+    Compression, DecodedBodyTooLarge, Encoding, RequestCompression,
+};
+use pretend::middleware::MiddlewareClient;
+use pretend::{pretend, Endpoint, Error, HeaderMap, Pretend, Response, Result, StatusCode, Url};
+use std::io::{Read, Write};
+use std::sync::{Arc, Mutex};
+
+#[pretend]
+trait UploadApi {
+    #[request(method = "POST", path = "/upload")]
+    fn upload(&self, body: Vec<u8>) -> Result<()>;
+
+    #[request(method = "POST", path = "/upload/raw")]
+    fn upload_raw(&self, body: Vec<u8>) -> Result<()>;
+}
+
+#[pretend]
+trait OtherApi {
+    #[request(method = "POST", path = "/other")]
+    fn post(&self, body: Vec<u8>) -> Result<()>;
+}
+
+#[derive(Clone, Debug)]
+struct RecordedRequest {
+    headers: HeaderMap,
+    body: Option<Bytes>,
+}
+
+type Encoder = fn(&[u8]) -> Vec<u8>;
+
+#[derive(Clone)]
+struct EncodingClient {
+    encoding: &'static str,
+    encoder: Encoder,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+}
+
+impl EncodingClient {
+    fn new(encoding: &'static str, encoder: Encoder) -> Self {
+        EncodingClient {
+            encoding,
+            encoder,
+            requests: Arc::default(),
+        }
+    }
+
+    fn identity() -> Self {
+        EncodingClient::new("identity", |body| body.to_vec())
+    }
+
+    fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+impl BlockingClient for EncodingClient {
+    fn execute(
+        &self,
+        _: Method,
+        _: Url,
+        headers: HeaderMap,
+        body: Option<Bytes>,
+    ) -> Result<Response<Bytes>> {
+        let request = RecordedRequest { headers, body };
+        self.requests.lock().unwrap().push(request);
+
+        let body = (self.encoder)(b"Hello World");
+        let mut headers = HeaderMap::new();
+        let encoding = HeaderValue::from_static(self.encoding);
+        headers.insert("Content-Encoding", encoding);
+        headers.insert("Content-Length", HeaderValue::from(body.len()));
+        Ok(Response::new(StatusCode::OK, headers, Bytes::from(body)))
+    }
+}
+
+fn gzip(data: &[u8]) -> Vec<u8> {
+    let level = flate2::Compression::default();
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), level);
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+fn deflate(data: &[u8]) -> Vec<u8> {
+    let level = flate2::Compression::default();
+    let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), level);
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+fn brotli(data: &[u8]) -> Vec<u8> {
+    let mut output = Vec::new();
+    {
+        let mut encoder = brotli::CompressorWriter::new(&mut output, 4096, 5, 22);
+        encoder.write_all(data).unwrap();
+    }
+    output
+}
+
+fn zstd(data: &[u8]) -> Vec<u8> {
+    zstd::stream::encode_all(data, 3).unwrap()
+}
+
+fn gunzip(data: &[u8]) -> Vec<u8> {
+    let mut output = Vec::new();
+    let mut decoder = flate2::read::GzDecoder::new(data);
+    decoder.read_to_end(&mut output).unwrap();
+    output
+}
+
+fn get(client: &EncodingClient, headers: HeaderMap) -> Result<Response<Bytes>> {
+    get_with(client, Compression::new(), headers)
+}
+
+fn get_with(
+    client: &EncodingClient,
+    compression: Compression,
+    headers: HeaderMap,
+) -> Result<Response<Bytes>> {
+    let client = MiddlewareClient::new(client.clone(), compression);
+    let url = Url::parse("http://localhost").unwrap();
+    BlockingClient::execute(&client, Method::GET, url, headers, None)
+}
+
+#[test]
+fn compression_sets_accept_encoding() {
+    let client = EncodingClient::identity();
+    get(&client, HeaderMap::new()).unwrap();
+
+    let mut headers = HeaderMap::new();
+    headers.insert("Accept-Encoding", HeaderValue::from_static("gzip"));
+    get(&client, headers).unwrap();
+
+    let requests = client.requests();
+    let accept_encoding = requests[0].headers.get("Accept-Encoding").unwrap();
+    assert_eq!(accept_encoding, "gzip, deflate, br, zstd");
+    let accept_encoding = requests[1].headers.get("Accept-Encoding").unwrap();
+    assert_eq!(accept_encoding, "gzip");
+}
+
+#[test]
+fn compression_decodes_responses() {
+    let clients = vec![
+        EncodingClient::new("gzip", gzip),
+        EncodingClient::new("x-gzip", gzip),
+        EncodingClient::new("deflate", deflate),
+        EncodingClient::new("br", brotli),
+        EncodingClient::new("zstd", zstd),
+        EncodingClient::new("gzip, br", |body| brotli(&gzip(body))),
+    ];
+
+    for client in clients {
+        let response = get(&client, HeaderMap::new()).unwrap();
+        assert_eq!(response.body(), "Hello World", "{}", client.encoding);
+        assert!(response.headers().get("Content-Encoding").is_none());
+        assert!(response.headers().get("Content-Length").is_none());
+    }
+}
+
+#[test]
+fn compression_returns_unsupported_encodings_as_is() {
+    let client = EncodingClient::new("compress", |body| body.to_vec());
+    let response = get(&client, HeaderMap::new()).unwrap();
+    assert_eq!(response.body(), "Hello World");
+    assert_eq!(
+        response.headers().get("Content-Encoding").unwrap(),
+        "compress"
+    );
+}
+
+#[test]
+fn compression_fails_on_invalid_bodies() {
+    let client = EncodingClient::new("gzip", |body| body.to_vec());
+    let result = get(&client, HeaderMap::new());
+    assert!(matches!(result, Err(Error::Body(_))));
+}
+
+#[test]
+fn compression_limits_decoded_size() {
+    let clients = vec![
+        EncodingClient::new("gzip", gzip),
+        EncodingClient::new("deflate", deflate),
+        EncodingClient::new("br", brotli),
+        EncodingClient::new("zstd", zstd),
+        EncodingClient::new("gzip, br", |body| brotli(&gzip(body))),
+    ];
+
+    for client in clients {
+        let compression = Compression::new().with_max_decoded_size(10);
+        let result = get_with(&client, compression, HeaderMap::new());
+        let err = match result {
+            Err(Error::Body(err)) => err,
+            _ => panic!("Expected a body error for {}", client.encoding),
+        };
+        let err = err.downcast_ref::<DecodedBodyTooLarge>().unwrap();
+        assert_eq!(err.max_size(), 10);
+    }
+
+    let client = EncodingClient::new("gzip", gzip);
+    let compression = Compression::new().with_max_decoded_size(11);
+    let response = get_with(&client, compression, HeaderMap::new()).unwrap();
+    assert_eq!(response.body(), "Hello World");
+}
+
+#[test]
+fn compression_compresses_large_request_bodies() {
+    let client = EncodingClient::identity();
+    let compression = RequestCompression::new(Encoding::Gzip).with_min_size(16);
+    let compression = Compression::new().with_request_compression(compression);
+    let middleware_client = MiddlewareClient::new(client.clone(), compression);
+    let url = Url::parse("http://localhost").unwrap();
+    let pretend = Pretend::for_client(middleware_client).with_url(url);
+
+    let large = vec![b'a'; 64];
+    pretend.upload(b"small".to_vec()).unwrap();
+    pretend.upload(large.clone()).unwrap();
+
+    let requests = client.requests();
+    assert!(requests[0].headers.get("Content-Encoding").is_none());
+    assert_eq!(requests[0].body.as_ref().unwrap(), "small");
+
+    assert_eq!(requests[1].headers.get("Content-Encoding").unwrap(), "gzip");
+    let body = requests[1].body.as_ref().unwrap();
+    assert_eq!(gunzip(body), large);
+}
+
+#[test]
+fn compression_is_configured_per_trait_and_endpoint() {
+    let client = EncodingClient::identity();
+    let compression = RequestCompression::new(Encoding::Zstd).with_min_size(0);
+    let upload_raw = Endpoint::new("UploadApi", "upload_raw", "/upload/raw");
+    let compression = Compression::new()
+        .with_trait_request_compression("UploadApi", Some(compression))
+        .with_endpoint_request_compression(upload_raw, None);
+    let middleware_client = MiddlewareClient::new(client.clone(), compression);
+    let url = Url::parse("http://localhost").unwrap();
+    let pretend = Pretend::for_client(middleware_client).with_url(url);
+
+    pretend.upload(b"body".to_vec()).unwrap();
+    pretend.upload_raw(b"body".to_vec()).unwrap();
+    pretend.post(b"body".to_vec()).unwrap();
+
+    let requests = client.requests();
+    let encodings = requests
+        .iter()
+        .map(|request| request.headers.get("Content-Encoding").cloned())
+        .collect::<Vec<_>>();
+    let expected = vec![Some(HeaderValue::from_static("zstd")), None, None];
+    assert_eq!(encodings, expected);
+}
@@ -0,0 +1,19 @@
+use pretend_generator::{Flavor, Generator};
+use std::env;
+use std::path::Path;
+
+fn main() {
+    let spec = "../../pretend-generator/tests/petstore.yaml";
+    let out_dir = env::var("OUT_DIR").unwrap();
+    let out_dir = Path::new(&out_dir);
+
+    let flavors = [
+        (Flavor::Async, "petstore_async.rs"),
+        (Flavor::AsyncLocal, "petstore_local.rs"),
+        (Flavor::Blocking, "petstore_blocking.rs"),
+    ];
+    for (flavor, file) in flavors.iter() {
+        let generator = Generator::new().with_flavor(*flavor);
+        generator.generate_file(spec, out_dir.join(file)).unwrap();
+    }
+}
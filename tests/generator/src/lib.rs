@@ -0,0 +1,13 @@
+//! Test crate for `pretend-generator`
+
+pub mod petstore_async {
+    include!(concat!(env!("OUT_DIR"), "/petstore_async.rs"));
+}
+
+pub mod petstore_local {
+    include!(concat!(env!("OUT_DIR"), "/petstore_local.rs"));
+}
+
+pub mod petstore_blocking {
+    include!(concat!(env!("OUT_DIR"), "/petstore_blocking.rs"));
+}
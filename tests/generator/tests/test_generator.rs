@@ -0,0 +1,144 @@
+use pretend::client::{BlockingClient, Bytes, Method};
+use pretend::http::HeaderValue;
+use pretend::{HeaderMap, JsonResult, Pretend, Response, Result, StatusCode, Url};
+use pretend_test_generator::petstore_blocking::{
+    ListPetsQuery, NewPet, NewPetType, PetStatus, PetStoreApi,
+};
+use std::sync::{Arc, Mutex};
+
+#[derive(Clone, Debug)]
+struct RecordedRequest {
+    method: Method,
+    url: Url,
+    headers: HeaderMap,
+    body: Option<Bytes>,
+}
+
+#[derive(Clone)]
+struct RecordingClient {
+    status: StatusCode,
+    body: &'static str,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+}
+
+impl RecordingClient {
+    fn new(status: StatusCode, body: &'static str) -> Self {
+        RecordingClient {
+            status,
+            body,
+            requests: Arc::default(),
+        }
+    }
+
+    fn last_request(&self) -> RecordedRequest {
+        self.requests.lock().unwrap().last().cloned().unwrap()
+    }
+}
+
+impl BlockingClient for RecordingClient {
+    fn execute(
+        &self,
+        method: Method,
+        url: Url,
+        headers: HeaderMap,
+        body: Option<Bytes>,
+    ) -> Result<Response<Bytes>> {
+        let request = RecordedRequest {
+            method,
+            url,
+            headers,
+            body,
+        };
+        self.requests.lock().unwrap().push(request);
+        let mut headers = HeaderMap::new();
+        let content_type = HeaderValue::from_static("application/json");
+        headers.insert("Content-Type", content_type);
+        Ok(Response::new(self.status, headers, Bytes::from(self.body)))
+    }
+}
+
+fn pretend(client: RecordingClient) -> impl PetStoreApi {
+    let url = Url::parse("http://localhost").unwrap();
+    Pretend::for_client(client).with_url(url)
+}
+
+const PET: &str = r#"{"id":1,"name":"Rex","status":"available","birthDate":null}"#;
+
+#[test]
+fn test_list_pets() {
+    let client = RecordingClient::new(StatusCode::OK, "[]");
+    let query = ListPetsQuery {
+        limit: Some(10),
+        status: Some(PetStatus::Sold),
+    };
+    let pets = pretend(client.clone()).list_pets(&query).unwrap();
+    assert!(pets.value().is_empty());
+
+    let request = client.last_request();
+    assert_eq!(request.method, Method::GET);
+    assert_eq!(
+        request.url.as_str(),
+        "http://localhost/pets?limit=10&status=sold"
+    );
+}
+
+#[test]
+fn test_get_pet() {
+    let client = RecordingClient::new(StatusCode::OK, PET);
+    let pet = pretend(client.clone()).get_pet(1).unwrap();
+    match pet {
+        JsonResult::Ok(pet) => {
+            assert_eq!(pet.id, 1);
+            assert_eq!(pet.name, "Rex");
+            assert_eq!(pet.status, Some(PetStatus::Available));
+            assert_eq!(pet.birth_date, None);
+        }
+        JsonResult::Err(_) => panic!("Expected a pet"),
+    }
+
+    let request = client.last_request();
+    assert_eq!(request.url.as_str(), "http://localhost/pets/1");
+}
+
+#[test]
+fn test_get_pet_error() {
+    let error = r#"{"code":404,"message":"Not found"}"#;
+    let client = RecordingClient::new(StatusCode::NOT_FOUND, error);
+    let pet = pretend(client).get_pet(2).unwrap();
+    match pet {
+        JsonResult::Ok(_) => panic!("Expected an error"),
+        JsonResult::Err(error) => {
+            assert_eq!(error.code, 404);
+            assert_eq!(error.message, "Not found");
+        }
+    }
+}
+
+#[test]
+fn test_create_pet() {
+    let client = RecordingClient::new(StatusCode::CREATED, PET);
+    let pet = NewPet {
+        name: "Rex".to_string(),
+        type_: Some(NewPetType::Dog),
+    };
+    pretend(client.clone()).create_pet("abc", &pet).unwrap();
+
+    let request = client.last_request();
+    assert_eq!(request.method, Method::POST);
+    assert_eq!(request.headers.get("X-Request-ID").unwrap(), "abc");
+    let body = request.body.unwrap();
+    assert_eq!(body, Bytes::from(r#"{"name":"Rex","type":"dog"}"#));
+}
+
+#[test]
+fn test_upload_photo() {
+    let client = RecordingClient::new(StatusCode::OK, "");
+    let photo = vec![1, 2, 3];
+    pretend(client.clone()).upload_photo(3, photo).unwrap();
+
+    let request = client.last_request();
+    assert_eq!(request.method, Method::PUT);
+    assert_eq!(request.url.as_str(), "http://localhost/pets/3/photo");
+    assert_eq!(request.headers.get("Content-Type").unwrap(), "image/png");
+    assert_eq!(request.body.unwrap(), Bytes::from(vec![1, 2, 3]));
+}
@@ -0,0 +1,101 @@
+use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+use metrics_util::MetricKind;
+use pretend::client::{BlockingClient, Bytes, Method};
+use pretend::metrics::MetricsRecorder;
+use pretend::{pretend, Error, HeaderMap, Pretend, Response, Result, StatusCode, Url};
+
+#[pretend]
+trait TestApi {
+    #[request(method = "GET", path = "/users/{id}")]
+    fn get_user(&self, id: i32) -> Result<()>;
+}
+
+struct StatusClient;
+
+impl BlockingClient for StatusClient {
+    fn execute(
+        &self,
+        _: Method,
+        url: Url,
+        _: HeaderMap,
+        _: Option<Bytes>,
+    ) -> Result<Response<Bytes>> {
+        let status = match url.path() {
+            "/users/1" => StatusCode::OK,
+            "/users/2" => StatusCode::SERVICE_UNAVAILABLE,
+            _ => return Err(Error::CircuitOpen),
+        };
+        Ok(Response::new(status, HeaderMap::new(), Bytes::new()))
+    }
+}
+
+type Labels = Vec<(String, String)>;
+
+fn labels(extra: (&str, &str)) -> Labels {
+    let labels = [
+        ("trait", "TestApi"),
+        ("method", "get_user"),
+        ("route", "/users/{id}"),
+        extra,
+    ];
+    let labels = labels.iter().map(|(k, v)| (k.to_string(), v.to_string()));
+    labels.collect()
+}
+
+#[test]
+fn metrics_recorder_records_requests() {
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+
+    metrics::with_local_recorder(&recorder, || {
+        let url = Url::parse("http://localhost").unwrap();
+        let pretend = Pretend::for_client(StatusClient)
+            .with_url(url)
+            .with_metrics_recorder(MetricsRecorder);
+        assert!(pretend.get_user(1).is_ok());
+        assert!(pretend.get_user(1).is_ok());
+        assert!(pretend.get_user(2).is_err());
+        assert!(pretend.get_user(3).is_err());
+    });
+
+    let mut counters = Vec::new();
+    let mut histograms = Vec::new();
+    for (key, _, _, value) in snapshotter.snapshot().into_vec() {
+        let (kind, key) = key.into_parts();
+        let name = key.name().to_string();
+        let labels = key
+            .labels()
+            .map(|label| (label.key().to_string(), label.value().to_string()))
+            .collect::<Labels>();
+        match (kind, value) {
+            (MetricKind::Counter, DebugValue::Counter(count)) => {
+                counters.push((name, labels, count));
+            }
+            (MetricKind::Histogram, DebugValue::Histogram(values)) => {
+                histograms.push((name, labels, values.len()));
+            }
+            _ => panic!("Unexpected metric {}", name),
+        }
+    }
+    counters.sort();
+    histograms.sort();
+
+    let requests = "pretend_requests_total".to_string();
+    let errors = "pretend_errors_total".to_string();
+    let expected_counters = vec![
+        (errors.clone(), labels(("error", "circuit_open")), 1),
+        (errors, labels(("error", "status")), 1),
+        (requests.clone(), labels(("status_class", "2xx")), 2),
+        (requests.clone(), labels(("status_class", "5xx")), 1),
+        (requests, labels(("status_class", "error")), 1),
+    ];
+    assert_eq!(counters, expected_counters);
+
+    let duration = "pretend_request_duration_seconds".to_string();
+    let expected_histograms = vec![
+        (duration.clone(), labels(("status_class", "2xx")), 2),
+        (duration.clone(), labels(("status_class", "5xx")), 1),
+        (duration, labels(("status_class", "error")), 1),
+    ];
+    assert_eq!(histograms, expected_histograms);
+}
@@ -0,0 +1,205 @@
+#![allow(dead_code)]
+
+use pretend::openapi::Document;
+use pretend::{pretend, Json, JsonResult, Response, Result};
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fmt::Display;
+
+#[derive(Deserialize, JsonSchema)]
+struct User {
+    id: i32,
+    name: String,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct ApiError {
+    message: String,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct Filter {
+    name: String,
+    limit: Option<u32>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct NewUser {
+    name: String,
+}
+
+#[pretend(openapi)]
+trait UserApi {
+    #[request(method = "GET", path = "/users/{id}")]
+    #[header(name = "Authorization", value = "Bearer {token}")]
+    #[header(name = "Accept", value = "application/json")]
+    async fn get_user(&self, id: i32, token: &str) -> Result<Json<User>>;
+
+    #[request(method = "GET", path = "/users")]
+    async fn list_users(&self, query: &Filter) -> Result<Json<Vec<User>>>;
+
+    #[request(method = "POST", path = "/users")]
+    async fn create_user(&self, json: &NewUser) -> Result<JsonResult<User, ApiError>>;
+
+    #[request(method = "DELETE", path = "/users/{id}?force={force}")]
+    async fn delete_user(&self, id: i32, force: bool) -> Result<Response<()>>;
+}
+
+#[pretend(?Send, openapi)]
+trait UploadApi {
+    #[request(method = "PUT", path = "/files/{name}")]
+    async fn upload(&self, name: &str, body: Vec<u8>) -> Result<String>;
+}
+
+#[pretend(openapi)]
+trait FormApi {
+    #[request(method = "POST", path = "/form")]
+    fn post_form(&self, form: &NewUser) -> Result<Vec<u8>>;
+}
+
+#[pretend(openapi)]
+trait GenericApi {
+    #[request(method = "POST", path = "/items/{id}")]
+    fn create_item<T, R>(&self, id: impl Display, json: &T) -> Result<Json<R>>
+    where
+        T: Serialize,
+        R: DeserializeOwned;
+}
+
+#[pretend(openapi)]
+trait ConstApi {
+    const DEFAULT_ID: i32 = 1;
+
+    #[request(method = "GET", path = "/users/{id}")]
+    async fn get_user(&self, id: i32) -> Result<Json<User>>;
+
+    async fn get_default_user(&self) -> Result<Json<User>> {
+        self.get_user(Self::DEFAULT_ID).await
+    }
+}
+
+fn schema_ref(name: &str) -> Value {
+    json!({ "$ref": format!("#/components/schemas/{}", name) })
+}
+
+#[test]
+fn openapi_describes_operations() {
+    let document = Document::new("Users", "1.0").with_api::<UserApiDescription>();
+    let paths = document.paths();
+
+    let get_user = &paths["/users/{id}"]["get"];
+    assert_eq!(get_user["operationId"], "get_user");
+    assert_eq!(get_user["tags"], json!(["UserApi"]));
+    let parameters = json!([
+        {
+            "name": "id",
+            "in": "path",
+            "required": true,
+            "schema": { "type": "integer", "format": "int32" },
+        },
+        {
+            "name": "Authorization",
+            "in": "header",
+            "required": true,
+            "schema": { "type": "string" },
+        },
+    ]);
+    assert_eq!(get_user["parameters"], parameters);
+    let response = &get_user["responses"]["200"]["content"]["application/json"];
+    assert_eq!(response["schema"], schema_ref("User"));
+
+    let delete_user = &paths["/users/{id}"]["delete"];
+    let parameters = delete_user["parameters"].as_array().unwrap();
+    assert_eq!(parameters[1]["name"], "force");
+    assert_eq!(parameters[1]["in"], "query");
+    assert_eq!(parameters[1]["schema"], json!({ "type": "boolean" }));
+    let responses = json!({ "200": { "description": "Successful response" } });
+    assert_eq!(delete_user["responses"], responses);
+}
+
+#[test]
+fn openapi_describes_queries_and_bodies() {
+    let document = Document::new("Users", "1.0").with_api::<UserApiDescription>();
+    let paths = document.paths();
+
+    let list_users = &paths["/users"]["get"];
+    let parameters = list_users["parameters"].as_array().unwrap();
+    let parameters = parameters
+        .iter()
+        .map(|parameter| (parameter["name"].clone(), parameter["required"].clone()))
+        .collect::<Vec<_>>();
+    let expected = vec![(json!("limit"), json!(false)), (json!("name"), json!(true))];
+    assert_eq!(parameters, expected);
+
+    let create_user = &paths["/users"]["post"];
+    let body = &create_user["requestBody"]["content"]["application/json"];
+    assert_eq!(body["schema"], schema_ref("NewUser"));
+    let responses = &create_user["responses"];
+    let success = &responses["200"]["content"]["application/json"]["schema"];
+    assert_eq!(*success, schema_ref("User"));
+    let error = &responses["default"]["content"]["application/json"]["schema"];
+    assert_eq!(*error, schema_ref("ApiError"));
+}
+
+#[test]
+fn openapi_supports_all_client_kinds() {
+    let document = Document::new("Files", "1.0")
+        .with_api::<UploadApiDescription>()
+        .with_api::<FormApiDescription>();
+    let paths = document.paths();
+
+    let upload = &paths["/files/{name}"]["put"];
+    let body = &upload["requestBody"]["content"]["application/octet-stream"];
+    assert_eq!(
+        body["schema"],
+        json!({ "type": "string", "format": "binary" })
+    );
+    let response = &upload["responses"]["200"]["content"]["text/plain"];
+    assert_eq!(response["schema"], json!({ "type": "string" }));
+
+    let post_form = &paths["/form"]["post"];
+    let body = &post_form["requestBody"]["content"]["application/x-www-form-urlencoded"];
+    assert_eq!(body["schema"], schema_ref("NewUser"));
+}
+
+#[test]
+fn openapi_skips_generic_types() {
+    let document = Document::new("Items", "1.0").with_api::<GenericApiDescription>();
+    let paths = document.paths();
+
+    let create_item = &paths["/items/{id}"]["post"];
+    let parameters = &create_item["parameters"][0];
+    assert_eq!(parameters["name"], "id");
+    assert_eq!(parameters["schema"], json!({ "type": "string" }));
+    assert!(create_item.get("requestBody").is_none());
+    assert!(document.schemas().as_object().unwrap().is_empty());
+}
+
+#[test]
+fn openapi_generates_documents() {
+    let document = Document::new("Users", "1.0").with_api::<UserApiDescription>();
+    let value = document.to_value();
+
+    assert_eq!(value["openapi"], "3.0.3");
+    assert_eq!(value["info"], json!({ "title": "Users", "version": "1.0" }));
+    assert_eq!(value["paths"], document.paths());
+
+    let schemas = value["components"]["schemas"].as_object().unwrap();
+    let names = schemas.keys().collect::<Vec<_>>();
+    assert_eq!(names, vec!["ApiError", "Filter", "NewUser", "User"]);
+    let user = &schemas["User"];
+    assert_eq!(user["type"], "object");
+    assert_eq!(user["required"], json!(["id", "name"]));
+}
+
+#[test]
+fn openapi_supports_traits_with_constants() {
+    let document = Document::new("Users", "1.0").with_api::<ConstApiDescription>();
+    let paths = document.paths();
+
+    let get_user = &paths["/users/{id}"]["get"];
+    assert_eq!(get_user["operationId"], "get_user");
+    assert_eq!(paths.as_object().unwrap().len(), 1);
+}
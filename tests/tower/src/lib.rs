@@ -0,0 +1 @@
+//! Test crate for the `tower` feature
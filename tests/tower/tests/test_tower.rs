@@ -0,0 +1,195 @@
+use pretend::client::{async_trait, Bytes, Client, Method};
+use pretend::http::{self, HeaderValue};
+use pretend::interceptor::{InterceptRequest, Request};
+use pretend::tower::{InProcessClient, ServiceClient, SERVICE_ENDPOINT};
+use pretend::{pretend, Error, HeaderMap, Json, Pretend, Response, Result, StatusCode, Url};
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tower::{service_fn, ServiceBuilder, ServiceExt};
+
+#[pretend]
+trait TestApi {
+    #[request(method = "POST", path = "/users/{id}")]
+    #[header(name = "X-Test", value = "{value}")]
+    async fn post_user(&self, id: i32, value: &str, body: &'static str) -> Result<String>;
+    #[request(method = "GET", path = "/users")]
+    async fn get_users(&self) -> Result<String>;
+}
+
+#[pretend]
+trait AppApi {
+    #[request(method = "GET", path = "/hello/{name}")]
+    async fn hello(&self, name: &str) -> Result<Response<String>>;
+    #[request(method = "POST", path = "/echo")]
+    async fn echo(&self, json: &[u32]) -> Result<Json<Vec<u32>>>;
+}
+
+async fn echo(
+    request: http::Request<Bytes>,
+) -> std::result::Result<http::Response<Bytes>, Infallible> {
+    let header = request.headers().get("X-Test");
+    let header = header.and_then(|value| value.to_str().ok()).unwrap_or("");
+    let body = String::from_utf8(request.body().to_vec()).unwrap();
+    let body = format!("{} {} {} {}", request.method(), request.uri(), header, body);
+    Ok(http::Response::new(Bytes::from(body)))
+}
+
+fn url() -> Url {
+    Url::parse("http://localhost").unwrap()
+}
+
+#[tokio::test]
+async fn service_client_executes_requests() {
+    let client = ServiceClient::new(service_fn(echo));
+    let pretend = Pretend::for_client(client).with_url(url());
+
+    let result = pretend.post_user(1, "value", "hello").await.unwrap();
+    assert_eq!(result, "POST http://localhost/users/1 value hello");
+}
+
+#[tokio::test]
+async fn service_client_uses_tower_middlewares() {
+    let service = ServiceBuilder::new()
+        .timeout(Duration::from_millis(10))
+        .service(service_fn(|request| async move {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            echo(request).await
+        }));
+    let pretend = Pretend::for_client(ServiceClient::new(service)).with_url(url());
+
+    let result = pretend.get_users().await;
+    assert!(matches!(result, Err(Error::Response(_))));
+}
+
+type RecordedRequest = (Method, Url, HeaderMap, Option<Bytes>);
+
+#[derive(Clone, Default)]
+struct RecordingClient {
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+}
+
+#[async_trait]
+impl Client for RecordingClient {
+    async fn execute(
+        &self,
+        method: Method,
+        url: Url,
+        headers: HeaderMap,
+        body: Option<Bytes>,
+    ) -> Result<Response<Bytes>> {
+        let request = (method, url, headers, body);
+        self.requests.lock().unwrap().push(request);
+        let headers = HeaderMap::new();
+        Ok(Response::new(
+            StatusCode::CREATED,
+            headers,
+            Bytes::from("ok"),
+        ))
+    }
+}
+
+#[derive(Clone)]
+struct EndpointInterceptor;
+
+impl InterceptRequest for EndpointInterceptor {
+    fn intercept(&self, mut request: Request) -> Result<Request> {
+        assert_eq!(request.endpoint, Some(SERVICE_ENDPOINT));
+        let value = HeaderValue::from_static("intercepted");
+        request.headers.insert("X-Test", value);
+        Ok(request)
+    }
+}
+
+#[tokio::test]
+async fn pretend_is_a_service() {
+    let client = RecordingClient::default();
+    let pretend = Pretend::for_client(client.clone())
+        .with_url(url())
+        .with_request_interceptor(EndpointInterceptor);
+
+    let request = http::Request::post("/users?page=2")
+        .body(Bytes::from("hello"))
+        .unwrap();
+    let response = pretend.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    assert_eq!(response.body(), &Bytes::from("ok"));
+
+    let service = ServiceBuilder::new().concurrency_limit(1).service(pretend);
+    let request = http::Request::get("http://example.com/users")
+        .body(Bytes::new())
+        .unwrap();
+    service.oneshot(request).await.unwrap();
+
+    let requests = client.requests.lock().unwrap();
+    let (method, url, headers, body) = &requests[0];
+    assert_eq!(method, Method::POST);
+    assert_eq!(url.as_str(), "http://localhost/users?page=2");
+    assert_eq!(headers.get("X-Test").unwrap(), "intercepted");
+    assert_eq!(body, &Some(Bytes::from("hello")));
+
+    let (method, url, _, body) = &requests[1];
+    assert_eq!(method, Method::GET);
+    assert_eq!(url.as_str(), "http://example.com/users");
+    assert_eq!(body, &None);
+}
+
+#[tokio::test]
+async fn pretend_service_stacks_with_service_client() {
+    let inner = Pretend::for_client(ServiceClient::new(service_fn(echo))).with_url(url());
+    let pretend = Pretend::for_client(ServiceClient::new(inner)).with_url(url());
+
+    let result = pretend.post_user(1, "value", "hello").await.unwrap();
+    assert_eq!(result, "POST http://localhost/users/1 value hello");
+}
+
+async fn app(request: http::Request<hyper::Body>) -> http::Result<http::Response<hyper::Body>> {
+    let host = request.headers().get(http::header::HOST).cloned();
+    let path = request.uri().path().to_string();
+    let body = hyper::body::to_bytes(request.into_body()).await.unwrap();
+
+    let response = http::Response::builder();
+    let response = match host {
+        Some(host) => response.header("X-Host", host),
+        None => response,
+    };
+    if let Some(name) = path.strip_prefix("/hello/") {
+        let body = format!("Hello {}", name);
+        response.body(hyper::Body::from(body))
+    } else if path == "/echo" {
+        response.body(hyper::Body::from(body))
+    } else {
+        let response = response.status(StatusCode::NOT_FOUND);
+        response.body(hyper::Body::empty())
+    }
+}
+
+fn in_process_client() -> impl Client + Clone {
+    let service = hyper::service::service_fn(app);
+    InProcessClient::<_, hyper::Body>::new(service)
+}
+
+#[tokio::test]
+async fn in_process_client_dispatches_to_app() {
+    let url = Url::parse("http://localhost:8080").unwrap();
+    let pretend = Pretend::for_client(in_process_client()).with_url(url);
+
+    let response = pretend.hello("Alice").await.unwrap();
+    assert_eq!(response.body(), "Hello Alice");
+    let host = response.headers().get("X-Host").unwrap();
+    assert_eq!(host, "localhost:8080");
+    let url = response.url().unwrap();
+    assert_eq!(url.as_str(), "http://localhost:8080/hello/Alice");
+
+    let result = pretend.echo(&[1, 2, 3]).await.unwrap();
+    assert_eq!(result.value(), vec![1, 2, 3]);
+}
+
+#[tokio::test]
+async fn in_process_client_returns_statuses() {
+    let url = Url::parse("http://localhost").unwrap();
+    let pretend = Pretend::for_client(in_process_client()).with_url(url);
+
+    let result = pretend.get_users().await;
+    assert!(matches!(result, Err(Error::Status(StatusCode::NOT_FOUND))));
+}
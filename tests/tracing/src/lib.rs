@@ -0,0 +1 @@
+//! Test crate for the `tracing` and `opentelemetry` features